@@ -0,0 +1,310 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rusqlite::blob::Blob;
+use rusqlite::{params, Connection, OptionalExtension, MAIN_DB};
+use serde_json::{Map, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::compression::StoredScene;
+use crate::error::AppResult;
+
+/// Key that replaces `dataURL` on a file entry once its bytes live in the `assets` table.
+pub(crate) const ASSET_HASH_KEY: &str = "assetHash";
+pub(crate) const DATA_URL_KEY: &str = "dataURL";
+
+/// Moves the image bytes embedded in a scene's `files` map into the content-addressed
+/// `assets` table and returns the scene with each `dataURL` replaced by an `assetHash`.
+/// Every asset is referenced from `board_assets` for `board_id`; `sync_board_assets` drops the
+/// references the board no longer needs once the scene has been written.
+pub(crate) fn extract_assets(conn: &Connection, board_id: &str, data: &str) -> AppResult<String> {
+    let Ok(mut scene) = serde_json::from_str::<JsonValue>(data) else {
+        return Ok(data.to_string());
+    };
+    let Some(files) = scene.get_mut("files").and_then(JsonValue::as_object_mut) else {
+        return Ok(data.to_string());
+    };
+
+    if !extract_file_assets(conn, board_id, files)? {
+        return Ok(data.to_string());
+    }
+    Ok(serde_json::to_string(&scene)?)
+}
+
+/// `extract_assets` for a bare `files` map, such as the one in a scene patch. Returns whether
+/// any entry was rewritten.
+pub(crate) fn extract_file_assets(
+    conn: &Connection,
+    board_id: &str,
+    files: &mut Map<String, JsonValue>,
+) -> AppResult<bool> {
+    let mut changed = false;
+    for (file_id, file) in files.iter_mut() {
+        let Some(file) = file.as_object_mut() else {
+            continue;
+        };
+        let Some(data_url) = file.get(DATA_URL_KEY).and_then(JsonValue::as_str) else {
+            continue;
+        };
+
+        // Excalidraw never reuses a file id for different content, so a file this board has
+        // already stored does not need to be decoded and hashed again on every autosave.
+        let hash = match known_asset_hash(conn, board_id, file_id)? {
+            Some(hash) => hash,
+            None => {
+                let Some((mime, bytes)) = decode_data_url(data_url) else {
+                    continue;
+                };
+                let hash = format!("{:x}", Sha256::digest(&bytes));
+                conn.execute(
+                    "INSERT OR IGNORE INTO assets (hash, mime, data, size_bytes)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![hash, mime, bytes, bytes.len() as i64],
+                )?;
+                conn.execute(
+                    "INSERT OR REPLACE INTO board_assets (board_id, file_id, hash)
+                     VALUES (?1, ?2, ?3)",
+                    params![board_id, file_id, hash],
+                )?;
+                hash
+            }
+        };
+
+        file.remove(DATA_URL_KEY);
+        file.insert(ASSET_HASH_KEY.to_string(), JsonValue::String(hash));
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// Inverse of `extract_assets`: puts the `dataURL` back on every file entry so the scene can
+/// be handed to Excalidraw or written to an export. Files whose asset is missing are left
+/// without image data, which Excalidraw renders as a placeholder.
+pub(crate) fn hydrate_assets(conn: &Connection, data: String) -> AppResult<String> {
+    if !data.contains(ASSET_HASH_KEY) {
+        return Ok(data);
+    }
+    let Ok(mut scene) = serde_json::from_str::<JsonValue>(&data) else {
+        return Ok(data);
+    };
+    let Some(files) = scene.get_mut("files").and_then(JsonValue::as_object_mut) else {
+        return Ok(data);
+    };
+
+    for file in files.values_mut() {
+        if let Some(file) = file.as_object_mut() {
+            hydrate_file(conn, file)?;
+        }
+    }
+    Ok(serde_json::to_string(&scene)?)
+}
+
+fn hydrate_file(conn: &Connection, file: &mut Map<String, JsonValue>) -> AppResult<()> {
+    let Some(hash) = file.remove(ASSET_HASH_KEY) else {
+        return Ok(());
+    };
+    let Some(hash) = hash.as_str() else {
+        return Ok(());
+    };
+
+    let asset: Option<(String, Vec<u8>)> = conn
+        .query_row(
+            "SELECT mime, data FROM assets WHERE hash = ?1",
+            params![hash],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    if let Some((mime, bytes)) = asset {
+        file.insert(
+            DATA_URL_KEY.to_string(),
+            JsonValue::String(format!("data:{mime};base64,{}", STANDARD.encode(bytes))),
+        );
+    }
+    Ok(())
+}
+
+/// Gives `target_board_id` the same asset references as `source_board_id`, for boards created
+/// from a copy of another board's stored scene.
+pub(crate) fn copy_board_assets(
+    conn: &Connection,
+    source_board_id: &str,
+    target_board_id: &str,
+) -> AppResult<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO board_assets (board_id, file_id, hash)
+         SELECT ?2, file_id, hash FROM board_assets WHERE board_id = ?1",
+        params![source_board_id, target_board_id],
+    )?;
+    Ok(())
+}
+
+/// Rebuilds a board's references from `scene`, its current scene, and the revisions it keeps,
+/// then deletes any asset left unreferenced. Called after every write to the scene or its
+/// history. Revisions are only read when a referenced file has left the current scene.
+pub(crate) fn sync_board_assets(conn: &Connection, board_id: &str, scene: &str) -> AppResult<()> {
+    let mut referenced = HashMap::new();
+    collect_asset_refs(scene, &mut referenced);
+
+    let stored: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT file_id FROM board_assets WHERE board_id = ?1")?;
+        let rows = stmt.query_map(params![board_id], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    if stored
+        .iter()
+        .all(|file_id| referenced.contains_key(file_id))
+    {
+        return Ok(());
+    }
+
+    let revisions: Vec<StoredScene> = {
+        let mut stmt =
+            conn.prepare("SELECT data, compression FROM board_revisions WHERE board_id = ?1")?;
+        let rows = stmt.query_map(params![board_id], |row| StoredScene::from_row(row, 0, 1))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for revision in revisions {
+        collect_asset_refs(&revision.decode()?, &mut referenced);
+    }
+
+    conn.execute(
+        "DELETE FROM board_assets WHERE board_id = ?1",
+        params![board_id],
+    )?;
+    for (file_id, hash) in &referenced {
+        conn.execute(
+            "INSERT INTO board_assets (board_id, file_id, hash) VALUES (?1, ?2, ?3)",
+            params![board_id, file_id, hash],
+        )?;
+    }
+    collect_garbage(conn)?;
+    Ok(())
+}
+
+/// Adds the `file id -> asset hash` pairs of a stored scene's files to `refs`. The first hash
+/// seen for a file id wins; Excalidraw never reuses one for different content.
+fn collect_asset_refs(data: &str, refs: &mut HashMap<String, String>) {
+    if !data.contains(ASSET_HASH_KEY) {
+        return;
+    }
+    let Ok(scene) = serde_json::from_str::<JsonValue>(data) else {
+        return;
+    };
+    let Some(files) = scene.get("files").and_then(JsonValue::as_object) else {
+        return;
+    };
+    for (file_id, file) in files {
+        if let Some(hash) = file.get(ASSET_HASH_KEY).and_then(JsonValue::as_str) {
+            refs.entry(file_id.clone())
+                .or_insert_with(|| hash.to_string());
+        }
+    }
+}
+
+/// Opens the bytes of the asset `hash` for reading straight from the `assets` table, so they
+/// can be copied out without loading the whole image into memory.
+pub(crate) fn open_asset<'conn>(
+    conn: &'conn Connection,
+    hash: &str,
+) -> AppResult<Option<Blob<'conn>>> {
+    let rowid: Option<i64> = conn
+        .query_row(
+            "SELECT rowid FROM assets WHERE hash = ?1",
+            params![hash],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match rowid {
+        Some(rowid) => Ok(Some(
+            conn.blob_open(MAIN_DB, "assets", "data", rowid, true)?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Deletes assets that no board references any more. Returns the number removed.
+pub(crate) fn collect_garbage(conn: &Connection) -> AppResult<usize> {
+    Ok(conn.execute(
+        "DELETE FROM assets WHERE hash NOT IN (SELECT DISTINCT hash FROM board_assets)",
+        [],
+    )?)
+}
+
+fn known_asset_hash(conn: &Connection, board_id: &str, file_id: &str) -> AppResult<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT hash FROM board_assets WHERE board_id = ?1 AND file_id = ?2",
+            params![board_id, file_id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Splits a base64 `data:` URL into its MIME type, empty if the URL leaves it out, and its
+/// decoded bytes.
+pub(crate) fn decode_data_url(data_url: &str) -> Option<(String, Vec<u8>)> {
+    let (header, payload) = data_url.split_once(',')?;
+    let mut params = header.strip_prefix("data:")?.split(';');
+    let mime = params.next().unwrap_or_default();
+    if !params.any(|param| param == "base64") {
+        return None;
+    }
+    let bytes = STANDARD.decode(payload).ok()?;
+    Some((mime.to_string(), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::board_content::store_board_data;
+    use crate::commands::boards::insert_new_board;
+    use crate::db::open_test_db;
+
+    fn scene_with_files(files: JsonValue) -> String {
+        serde_json::json!({ "elements": [], "appState": {}, "files": files }).to_string()
+    }
+
+    fn asset_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM assets", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn assets_dropped_from_a_scene_are_collected() {
+        let conn = open_test_db();
+        insert_new_board(&conn, "board".to_string(), "Board".to_string()).unwrap();
+
+        let image = serde_json::json!({
+            "image": {
+                "id": "image",
+                "mimeType": "image/png",
+                "dataURL": "data:image/png;base64,AAAA"
+            }
+        });
+        store_board_data(&conn, "board", &scene_with_files(image), None).unwrap();
+        assert_eq!(asset_count(&conn), 1);
+
+        store_board_data(
+            &conn,
+            "board",
+            &scene_with_files(serde_json::json!({})),
+            None,
+        )
+        .unwrap();
+        assert_eq!(asset_count(&conn), 0);
+    }
+
+    #[test]
+    fn decodes_base64_data_urls_only() {
+        assert_eq!(
+            decode_data_url("data:image/png;base64,AAAA"),
+            Some(("image/png".to_string(), vec![0, 0, 0]))
+        );
+        assert_eq!(
+            decode_data_url("data:;base64,AAAA"),
+            Some((String::new(), vec![0, 0, 0]))
+        );
+        assert_eq!(decode_data_url("data:text/plain,hello"), None);
+        assert_eq!(decode_data_url("image/png;base64,AAAA"), None);
+    }
+}
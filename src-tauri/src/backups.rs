@@ -0,0 +1,368 @@
+use chrono::Utc;
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, MAIN_DB};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::db::{
+    datetime_from_millis, get_boards_dir, get_db_path, get_setting, lock_db, set_setting,
+};
+use crate::error::{AppError, AppResult};
+use crate::journal;
+use crate::migrations::{run_migrations, LATEST_SCHEMA_VERSION};
+use crate::models::{BackupInfo, BackupSchedule, BackupSettings};
+use crate::thumbnails;
+
+const SCHEDULE_SETTING_KEY: &str = "backup.schedule";
+const DIRECTORY_SETTING_KEY: &str = "backup.directory";
+const KEEP_COUNT_SETTING_KEY: &str = "backup.keep_count";
+const MAX_AGE_SETTING_KEY: &str = "backup.max_age_days";
+const DEFAULT_KEEP_COUNT: u32 = 14;
+const DEFAULT_MAX_AGE_DAYS: u32 = 30;
+/// Emitted with the `AppError` when a scheduled backup fails, since no command is waiting on it.
+const BACKUP_FAILED_EVENT: &str = "scheduled-backup-failed";
+
+const DEFAULT_BACKUPS_SUBDIR: &str = "backups";
+const BACKUP_PREFIX: &str = "backup-";
+const PARTIAL_EXTENSION: &str = "partial";
+const SNAPSHOT_FILE_NAME: &str = "boards.db";
+const THUMBNAILS_SUBDIR: &str = "thumbnails";
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+/// How often the scheduler checks whether a backup is due. Checking against the newest
+/// backup on disk, rather than sleeping for the whole interval, means a backup missed while
+/// the app was closed is taken shortly after the next launch.
+const SCHEDULER_TICK: Duration = Duration::from_secs(5 * 60);
+
+pub(crate) fn load_settings(conn: &Connection) -> AppResult<BackupSettings> {
+    let schedule = match get_setting(conn, SCHEDULE_SETTING_KEY)?.as_deref() {
+        None => BackupSchedule::default(),
+        Some("off") => BackupSchedule::Off,
+        Some("hourly") => BackupSchedule::Hourly,
+        Some("daily") => BackupSchedule::Daily,
+        Some(_) => return Err(invalid_setting(SCHEDULE_SETTING_KEY)),
+    };
+
+    Ok(BackupSettings {
+        schedule,
+        directory: get_setting(conn, DIRECTORY_SETTING_KEY)?,
+        keep_count: u32_setting(conn, KEEP_COUNT_SETTING_KEY, DEFAULT_KEEP_COUNT)?,
+        max_age_days: u32_setting(conn, MAX_AGE_SETTING_KEY, DEFAULT_MAX_AGE_DAYS)?,
+    })
+}
+
+pub(crate) fn save_settings(conn: &Connection, settings: &BackupSettings) -> AppResult<()> {
+    if settings.keep_count == 0 {
+        return Err(AppError::validation("At least one backup must be kept"));
+    }
+    let directory = settings
+        .directory
+        .as_deref()
+        .map(str::trim)
+        .filter(|directory| !directory.is_empty());
+    if directory.is_some_and(|directory| !Path::new(directory).is_absolute()) {
+        return Err(AppError::validation(
+            "Backup folder must be an absolute path",
+        ));
+    }
+
+    let schedule = match settings.schedule {
+        BackupSchedule::Off => "off",
+        BackupSchedule::Hourly => "hourly",
+        BackupSchedule::Daily => "daily",
+    };
+    set_setting(conn, SCHEDULE_SETTING_KEY, Some(schedule))?;
+    set_setting(conn, DIRECTORY_SETTING_KEY, directory)?;
+    set_setting(
+        conn,
+        KEEP_COUNT_SETTING_KEY,
+        Some(&settings.keep_count.to_string()),
+    )?;
+    set_setting(
+        conn,
+        MAX_AGE_SETTING_KEY,
+        Some(&settings.max_age_days.to_string()),
+    )
+}
+
+fn u32_setting(conn: &Connection, key: &str, default: u32) -> AppResult<u32> {
+    let Some(raw) = get_setting(conn, key)? else {
+        return Ok(default);
+    };
+    raw.trim().parse().map_err(|_| invalid_setting(key))
+}
+
+fn invalid_setting(key: &str) -> AppError {
+    AppError::validation(format!("Invalid value for setting '{key}'"))
+}
+
+fn schedule_interval_ms(schedule: BackupSchedule) -> Option<i64> {
+    match schedule {
+        BackupSchedule::Off => None,
+        BackupSchedule::Hourly => Some(HOUR_MS),
+        BackupSchedule::Daily => Some(DAY_MS),
+    }
+}
+
+fn backups_dir(app: &AppHandle, settings: &BackupSettings) -> AppResult<PathBuf> {
+    let dir = match &settings.directory {
+        Some(directory) => PathBuf::from(directory),
+        None => get_boards_dir(app)?.join(DEFAULT_BACKUPS_SUBDIR),
+    };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Backups are directories named `backup-<created_at_ms>`, which doubles as their id.
+fn backup_created_at_ms(backup_id: &str) -> Option<i64> {
+    backup_id.strip_prefix(BACKUP_PREFIX)?.parse().ok()
+}
+
+/// Completed backups in `dir`, newest first.
+fn backup_dirs(dir: &Path) -> AppResult<Vec<(i64, PathBuf)>> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(created_at_ms) = name.to_str().and_then(backup_created_at_ms) else {
+            continue;
+        };
+        if entry.path().join(SNAPSHOT_FILE_NAME).is_file() {
+            backups.push((created_at_ms, entry.path()));
+        }
+    }
+    backups.sort_by_key(|(created_at_ms, _)| std::cmp::Reverse(*created_at_ms));
+    Ok(backups)
+}
+
+fn backup_info(created_at_ms: i64, path: &Path) -> AppResult<BackupInfo> {
+    Ok(BackupInfo {
+        id: format!("{BACKUP_PREFIX}{created_at_ms}"),
+        created_at: datetime_from_millis(created_at_ms)?,
+        size_bytes: dir_size(path)?,
+    })
+}
+
+pub(crate) fn list_backups(app: &AppHandle) -> AppResult<Vec<BackupInfo>> {
+    let settings = load_settings(&*lock_db(app)?)?;
+    backup_dirs(&backups_dir(app, &settings)?)?
+        .iter()
+        .map(|(created_at_ms, path)| backup_info(*created_at_ms, path))
+        .collect()
+}
+
+/// Takes a backup now and applies the retention policy.
+pub(crate) fn create_backup(app: &AppHandle) -> AppResult<BackupInfo> {
+    let conn = lock_db(app)?;
+    let settings = load_settings(&conn)?;
+    let dir = backups_dir(app, &settings)?;
+    let backup = write_backup(app, &conn, &dir)?;
+    rotate_backups(&dir, &settings, backup.created_at.timestamp_millis())?;
+    Ok(backup)
+}
+
+/// Snapshots the database with SQLite's online backup API, plus the thumbnail files, into a
+/// new backup directory. The caller holds the connection lock, so the database and
+/// thumbnails are copied in the same state. The copy is made under a `.partial` name and
+/// renamed when complete, so an interrupted backup is never listed.
+fn write_backup(app: &AppHandle, conn: &Connection, dir: &Path) -> AppResult<BackupInfo> {
+    let created_at_ms = Utc::now().timestamp_millis();
+    let backup_id = format!("{BACKUP_PREFIX}{created_at_ms}");
+    let partial_path = dir.join(format!("{backup_id}.{PARTIAL_EXTENSION}"));
+
+    let written = (|| -> AppResult<()> {
+        fs::create_dir_all(&partial_path)?;
+        conn.backup(MAIN_DB, partial_path.join(SNAPSHOT_FILE_NAME), None)?;
+        copy_dir(
+            &thumbnails::thumbnails_dir(app)?,
+            &partial_path.join(THUMBNAILS_SUBDIR),
+        )
+    })();
+    if let Err(error) = written {
+        let _ = fs::remove_dir_all(&partial_path);
+        return Err(error);
+    }
+
+    let backup_path = dir.join(&backup_id);
+    fs::rename(&partial_path, &backup_path)?;
+    backup_info(created_at_ms, &backup_path)
+}
+
+/// Deletes backups beyond the configured count or age, always keeping the newest, along
+/// with directories left behind by interrupted backups.
+fn rotate_backups(dir: &Path, settings: &BackupSettings, now_ms: i64) -> AppResult<()> {
+    let max_age_ms = i64::from(settings.max_age_days) * DAY_MS;
+    for (position, (created_at_ms, path)) in backup_dirs(dir)?.iter().enumerate().skip(1) {
+        let beyond_count = position >= settings.keep_count as usize;
+        let beyond_age = settings.max_age_days > 0 && now_ms - created_at_ms > max_age_ms;
+        if beyond_count || beyond_age {
+            fs::remove_dir_all(path)?;
+        }
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_partial = path.extension().and_then(|ext| ext.to_str()) == Some(PARTIAL_EXTENSION);
+        let is_backup = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.starts_with(BACKUP_PREFIX));
+        if is_partial && is_backup && path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the database and thumbnails with a backup. The snapshot is checked with
+/// `PRAGMA integrity_check` before anything is touched, and the current state is backed up
+/// first so the restore can itself be undone. The backup's thumbnails are copied aside
+/// before the database is restored and only swapped in afterwards; if either step fails the
+/// database is rolled back from that safety backup. Backup settings are kept as they are now
+/// rather than reverting to the snapshot's.
+pub(crate) fn restore_backup(app: &AppHandle, backup_id: &str) -> AppResult<()> {
+    let mut conn = lock_db(app)?;
+    let settings = load_settings(&conn)?;
+    let dir = backups_dir(app, &settings)?;
+
+    let backup_path = dir.join(backup_id);
+    let snapshot_path = backup_path.join(SNAPSHOT_FILE_NAME);
+    if backup_created_at_ms(backup_id).is_none() || !snapshot_path.is_file() {
+        return Err(AppError::not_found("Backup not found"));
+    }
+    validate_snapshot(&snapshot_path)?;
+
+    let safety = write_backup(app, &conn, &dir)?;
+    let thumbnails_dir = thumbnails::thumbnails_dir(app)?;
+    let staged_dir = thumbnails_dir.with_extension(PARTIAL_EXTENSION);
+    if staged_dir.exists() {
+        fs::remove_dir_all(&staged_dir)?;
+    }
+    if let Err(error) = copy_dir(&backup_path.join(THUMBNAILS_SUBDIR), &staged_dir) {
+        let _ = fs::remove_dir_all(&staged_dir);
+        return Err(error);
+    }
+
+    let restored = (|| -> AppResult<()> {
+        conn.restore(MAIN_DB, &snapshot_path, None::<fn(Progress)>)?;
+        run_migrations(&conn, &get_boards_dir(app)?, &get_db_path(app)?)?;
+        save_settings(&conn, &settings)?;
+        replace_dir(&staged_dir, &thumbnails_dir)
+    })();
+    if let Err(error) = restored {
+        let _ = fs::remove_dir_all(&staged_dir);
+        let safety_snapshot = dir.join(&safety.id).join(SNAPSHOT_FILE_NAME);
+        conn.restore(MAIN_DB, &safety_snapshot, None::<fn(Progress)>)?;
+        return Err(error);
+    }
+    drop(conn);
+
+    // Journaled changes were made against the replaced scenes.
+    for board_id in journal::journaled_board_ids(app)? {
+        journal::clear(app, &board_id)?;
+    }
+    Ok(())
+}
+
+fn validate_snapshot(path: &Path) -> AppResult<()> {
+    let conn = Connection::open(path)?;
+    let problems: Vec<String> = {
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    if problems != ["ok"] {
+        return Err(AppError::validation(format!(
+            "Backup failed the integrity check: {}",
+            problems.join("; ")
+        )));
+    }
+
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > LATEST_SCHEMA_VERSION {
+        return Err(AppError::migration(format!(
+            "Backup schema version {version} is newer than this app supports \
+             ({LATEST_SCHEMA_VERSION}). Please update ExcaStoneBoard."
+        )));
+    }
+    Ok(())
+}
+
+/// Starts the background thread that takes scheduled backups. Called from `setup` once the
+/// database is managed.
+pub(crate) fn start_scheduler(app: AppHandle) {
+    thread::spawn(move || loop {
+        if let Err(error) = run_scheduled_backup(&app) {
+            let _ = app.emit(BACKUP_FAILED_EVENT, &error);
+        }
+        thread::sleep(SCHEDULER_TICK);
+    });
+}
+
+fn run_scheduled_backup(app: &AppHandle) -> AppResult<()> {
+    let settings = load_settings(&*lock_db(app)?)?;
+    let Some(interval_ms) = schedule_interval_ms(settings.schedule) else {
+        return Ok(());
+    };
+
+    let now_ms = Utc::now().timestamp_millis();
+    let newest_ms = backup_dirs(&backups_dir(app, &settings)?)?
+        .first()
+        .map(|(created_at_ms, _)| *created_at_ms);
+    if newest_ms.is_some_and(|created_at_ms| now_ms - created_at_ms < interval_ms) {
+        return Ok(());
+    }
+    create_backup(app)?;
+    Ok(())
+}
+
+/// Moves `staged` to `target`, keeping the old `target` until the move has succeeded.
+fn replace_dir(staged: &Path, target: &Path) -> AppResult<()> {
+    let previous = target.with_extension("previous");
+    if previous.exists() {
+        fs::remove_dir_all(&previous)?;
+    }
+    fs::rename(target, &previous)?;
+    if let Err(error) = fs::rename(staged, target) {
+        let _ = fs::rename(&previous, target);
+        return Err(error.into());
+    }
+    // The swap is done; a leftover is removed by the next restore.
+    let _ = fs::remove_dir_all(&previous);
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> AppResult<()> {
+    fs::create_dir_all(to)?;
+    if !from.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> AppResult<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
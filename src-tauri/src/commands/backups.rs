@@ -0,0 +1,37 @@
+use tauri::AppHandle;
+
+use crate::backups;
+use crate::commands::boards::load_resolved_boards_index;
+use crate::db::lock_db;
+use crate::error::AppResult;
+use crate::models::{BackupInfo, BackupSettings, BoardsIndex};
+
+/// Backups in the configured folder, newest first.
+#[tauri::command]
+pub(crate) fn list_backups(app: AppHandle) -> AppResult<Vec<BackupInfo>> {
+    backups::list_backups(&app)
+}
+
+#[tauri::command]
+pub(crate) fn create_backup(app: AppHandle) -> AppResult<BackupInfo> {
+    backups::create_backup(&app)
+}
+
+#[tauri::command]
+pub(crate) fn restore_backup(app: AppHandle, backup_id: String) -> AppResult<BoardsIndex> {
+    backups::restore_backup(&app, &backup_id)?;
+    let conn = lock_db(&app)?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+#[tauri::command]
+pub(crate) fn get_backup_settings(app: AppHandle) -> AppResult<BackupSettings> {
+    let conn = lock_db(&app)?;
+    backups::load_settings(&conn)
+}
+
+#[tauri::command]
+pub(crate) fn set_backup_settings(app: AppHandle, settings: BackupSettings) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    backups::save_settings(&conn, &settings)
+}
@@ -0,0 +1,450 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::assets::{decode_data_url, open_asset, ASSET_HASH_KEY, DATA_URL_KEY};
+use crate::commands::board_transfer::collect_export_boards;
+use crate::db::{default_board_data, load_board_data_value, lock_db};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Board, BoardsArchiveEntry, BoardsArchiveManifest, BoardsExportEntry, BoardsExportFile,
+};
+use crate::thumbnails;
+
+const ARCHIVE_FORMAT: &str = "excastoneboard-archive";
+/// Version 2 stores the nested folder tree in the manifest index.
+const ARCHIVE_VERSION: u8 = 2;
+/// JSON export schema version that an archive's contents correspond to.
+const ARCHIVE_EXPORT_FILE_VERSION: u8 = 3;
+const MANIFEST_PATH: &str = "manifest.json";
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+const DEFAULT_MIME: &str = "application/octet-stream";
+
+/// Exports all boards as a zip archive (`.esb`): a `manifest.json`, one scene JSON per board
+/// under `boards/`, embedded images stored once as binary entries under `files/`, and
+/// thumbnails as image files under `thumbnails/`. Images are copied straight from the
+/// `assets` table and thumbnails from their files, so no board is held in memory as base64.
+#[tauri::command]
+pub(crate) fn export_boards_archive(app: AppHandle, file_path: String) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    let (boards, index) = collect_export_boards(&conn)?;
+
+    let file = File::create(file_path)?;
+    let mut writer = ZipWriter::new(file);
+    let mut written_files = HashSet::new();
+    let mut manifest = BoardsArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        boards: Vec::with_capacity(boards.len()),
+        index,
+    };
+
+    for board in &boards {
+        let thumbnail = thumbnails::thumbnail_file_path(
+            &app,
+            board
+                .thumbnail
+                .as_deref()
+                .map(thumbnails::RelativeThumbnailPath::from),
+        )?;
+        manifest.boards.push(write_archive_board(
+            &mut writer,
+            &conn,
+            &mut written_files,
+            board,
+            thumbnail.as_deref(),
+        )?);
+    }
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    write_entry(
+        &mut writer,
+        MANIFEST_PATH,
+        &manifest_json,
+        CompressionMethod::Deflated,
+    )?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// How much of an export `read_boards_export` loads.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportContents {
+    /// Board metadata and scenes without their images or thumbnails, enough to plan an import.
+    Scenes,
+    /// Everything, with images and thumbnails inlined as data URLs.
+    Full,
+}
+
+/// Reads either export format into memory: an `.esb` archive or a JSON bundle. `contents`
+/// only affects archives; a JSON bundle carries its images inline either way.
+pub(crate) fn read_boards_export(
+    path: &Path,
+    contents: ExportContents,
+) -> AppResult<BoardsExportFile> {
+    if is_boards_archive(path)? {
+        return read_boards_archive(path, contents);
+    }
+
+    let payload = fs::read_to_string(path)?;
+    serde_json::from_str(&payload).map_err(AppError::from)
+}
+
+/// Whether `path` is a zip archive rather than a JSON export, judged by its magic bytes.
+fn is_boards_archive(path: &Path) -> AppResult<bool> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == ZIP_MAGIC),
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Reads an `.esb` archive back into the in-memory export representation. With
+/// `ExportContents::Full`, image files and thumbnails are re-inlined as data URLs so it can go
+/// through the regular import path.
+fn read_boards_archive(path: &Path, contents: ExportContents) -> AppResult<BoardsExportFile> {
+    let file = File::open(path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    let manifest: BoardsArchiveManifest =
+        serde_json::from_slice(&read_entry(&mut archive, MANIFEST_PATH)?)?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(AppError::validation("Unsupported archive format"));
+    }
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(AppError::validation(format!(
+            "Archive version {} is newer than supported version {ARCHIVE_VERSION}",
+            manifest.version
+        )));
+    }
+
+    let mut boards = Vec::with_capacity(manifest.boards.len());
+    for board in manifest.boards {
+        boards.push(read_archive_board(&mut archive, board, contents)?);
+    }
+
+    Ok(BoardsExportFile {
+        version: ARCHIVE_EXPORT_FILE_VERSION,
+        exported_at: manifest.exported_at,
+        boards,
+        index: manifest.index,
+    })
+}
+
+fn write_archive_board<W: Write + Seek>(
+    writer: &mut ZipWriter<W>,
+    conn: &Connection,
+    written_files: &mut HashSet<String>,
+    board: &Board,
+    thumbnail: Option<&Path>,
+) -> AppResult<BoardsArchiveEntry> {
+    let stored = load_board_data_value(conn, &board.id)?.unwrap_or_else(default_board_data);
+    let mut data: JsonValue = serde_json::from_str(&stored).unwrap_or(JsonValue::Null);
+    if let Some(files) = data.get_mut("files").and_then(JsonValue::as_object_mut) {
+        for (file_id, file) in files.iter_mut() {
+            let Some(file) = file.as_object_mut() else {
+                continue;
+            };
+            let asset_hash = file.remove(ASSET_HASH_KEY);
+            let data_url = file.remove(DATA_URL_KEY);
+
+            let path = archive_file_path(file_id);
+            if written_files.contains(&path) {
+                continue;
+            }
+            // Scenes saved before assets were split out may still carry their data URL.
+            let written = if let Some(hash) = asset_hash.as_ref().and_then(JsonValue::as_str) {
+                match open_asset(conn, hash)? {
+                    Some(mut asset) => {
+                        copy_entry(writer, &path, &mut asset, CompressionMethod::Stored)?;
+                        true
+                    }
+                    None => false,
+                }
+            } else if let Some((_, bytes)) = data_url
+                .as_ref()
+                .and_then(JsonValue::as_str)
+                .and_then(decode_data_url)
+            {
+                write_entry(writer, &path, &bytes, CompressionMethod::Stored)?;
+                true
+            } else {
+                false
+            };
+            if written {
+                written_files.insert(path);
+            }
+        }
+    }
+
+    let scene_path = format!("boards/{}.json", archive_name(&board.id));
+    let scene_json = serde_json::to_vec(&data)?;
+    write_entry(
+        writer,
+        &scene_path,
+        &scene_json,
+        CompressionMethod::Deflated,
+    )?;
+
+    let mut thumbnail_path = None;
+    if let Some(source) = thumbnail {
+        let extension = source
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("png");
+        let path = format!("thumbnails/{}.{extension}", archive_name(&board.id));
+        copy_entry(
+            writer,
+            &path,
+            &mut File::open(source)?,
+            CompressionMethod::Stored,
+        )?;
+        thumbnail_path = Some(path);
+    }
+
+    Ok(BoardsArchiveEntry {
+        id: board.id.clone(),
+        name: board.name.clone(),
+        created_at: board.created_at,
+        updated_at: board.updated_at,
+        collaboration_link: board.collaboration_link.clone(),
+        scene_path,
+        thumbnail_path,
+    })
+}
+
+/// Entries an archive lists under `files/` or `thumbnails/` but lacks are skipped, leaving the
+/// image out rather than failing the whole import.
+fn read_archive_board<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    board: BoardsArchiveEntry,
+    contents: ExportContents,
+) -> AppResult<BoardsExportEntry> {
+    let mut data: JsonValue = serde_json::from_slice(&read_entry(archive, &board.scene_path)?)?;
+    if contents == ExportContents::Scenes {
+        return Ok(archive_board_entry(board, None, data));
+    }
+
+    if let Some(files) = data.get_mut("files").and_then(JsonValue::as_object_mut) {
+        for (file_id, file) in files.iter_mut() {
+            let Some(file) = file.as_object_mut() else {
+                continue;
+            };
+            let Ok(bytes) = read_entry(archive, &archive_file_path(file_id)) else {
+                continue;
+            };
+            let mime = file
+                .get("mimeType")
+                .and_then(JsonValue::as_str)
+                .unwrap_or(DEFAULT_MIME)
+                .to_string();
+            file.insert(
+                "dataURL".to_string(),
+                JsonValue::String(encode_data_url(&mime, &bytes)),
+            );
+        }
+    }
+
+    let thumbnail = board.thumbnail_path.as_deref().and_then(|path| {
+        read_entry(archive, path)
+            .ok()
+            .map(|bytes| encode_data_url(mime_for_path(path), &bytes))
+    });
+
+    Ok(archive_board_entry(board, thumbnail, data))
+}
+
+fn archive_board_entry(
+    board: BoardsArchiveEntry,
+    thumbnail: Option<String>,
+    data: JsonValue,
+) -> BoardsExportEntry {
+    BoardsExportEntry {
+        id: board.id,
+        name: board.name,
+        created_at: board.created_at,
+        updated_at: board.updated_at,
+        collaboration_link: board.collaboration_link,
+        thumbnail,
+        data: Some(data),
+    }
+}
+
+fn write_entry<W: Write + Seek>(
+    writer: &mut ZipWriter<W>,
+    path: &str,
+    bytes: &[u8],
+    compression: CompressionMethod,
+) -> AppResult<()> {
+    let options = SimpleFileOptions::default().compression_method(compression);
+    writer.start_file(path, options)?;
+    writer.write_all(bytes).map_err(AppError::from)
+}
+
+fn copy_entry<W: Write + Seek>(
+    writer: &mut ZipWriter<W>,
+    path: &str,
+    reader: &mut impl Read,
+    compression: CompressionMethod,
+) -> AppResult<()> {
+    let options = SimpleFileOptions::default().compression_method(compression);
+    writer.start_file(path, options)?;
+    io::copy(reader, writer)?;
+    Ok(())
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, path: &str) -> AppResult<Vec<u8>> {
+    let mut entry = archive.by_name(path).map_err(|error| {
+        AppError::validation(format!("Archive entry '{path}' unreadable: {error}"))
+    })?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Excalidraw file ids are content hashes, so keying archive entries by id stores each image
+/// once even when several boards embed it.
+fn archive_file_path(file_id: &str) -> String {
+    format!("files/{}", archive_name(file_id))
+}
+
+fn archive_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '_') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn encode_data_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{mime};base64,{}", STANDARD.encode(bytes))
+}
+
+fn mime_for_path(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "image/png",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::board_content::store_board_data;
+    use crate::commands::boards::insert_new_board;
+    use crate::db::open_test_db;
+
+    fn test_manifest(boards: Vec<BoardsArchiveEntry>) -> BoardsArchiveManifest {
+        BoardsArchiveManifest {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            boards,
+            index: Vec::new(),
+        }
+    }
+
+    fn write_test_archive(path: &Path) {
+        let mut writer = ZipWriter::new(File::create(path).unwrap());
+        let manifest = test_manifest(vec![BoardsArchiveEntry {
+            id: "board".to_string(),
+            name: "Board".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            collaboration_link: None,
+            scene_path: "boards/board.json".to_string(),
+            thumbnail_path: Some("thumbnails/board.png".to_string()),
+        }]);
+        let scene = serde_json::json!({
+            "elements": [],
+            "files": { "image": { "id": "image", "mimeType": "image/png" } }
+        });
+        for (entry_path, bytes) in [
+            (MANIFEST_PATH, serde_json::to_vec(&manifest).unwrap()),
+            ("boards/board.json", serde_json::to_vec(&scene).unwrap()),
+            ("files/image", vec![0, 0, 0]),
+        ] {
+            write_entry(&mut writer, entry_path, &bytes, CompressionMethod::Stored).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn image_data_url(export: &BoardsExportFile) -> Option<&str> {
+        export.boards[0].data.as_ref()?["files"]["image"]["dataURL"].as_str()
+    }
+
+    #[test]
+    fn archive_previews_skip_images_and_imports_skip_missing_thumbnails() {
+        let path = std::env::temp_dir().join(format!("{}.esb", uuid::Uuid::new_v4()));
+        write_test_archive(&path);
+
+        let preview = read_boards_export(&path, ExportContents::Scenes).unwrap();
+        let full = read_boards_export(&path, ExportContents::Full);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(image_data_url(&preview), None);
+        let full = full.unwrap();
+        assert_eq!(image_data_url(&full), Some("data:image/png;base64,AAAA"));
+        assert_eq!(full.boards[0].thumbnail, None);
+    }
+
+    #[test]
+    fn exported_images_are_copied_from_the_asset_table() {
+        let conn = open_test_db();
+        let board = insert_new_board(&conn, "board".to_string(), "Board".to_string()).unwrap();
+        let scene = serde_json::json!({
+            "elements": [],
+            "files": {
+                "image": {
+                    "id": "image",
+                    "mimeType": "image/png",
+                    "dataURL": "data:image/png;base64,AAAA"
+                }
+            }
+        });
+        store_board_data(&conn, "board", &scene.to_string(), None).unwrap();
+        let stored = load_board_data_value(&conn, "board").unwrap().unwrap();
+        assert!(!stored.contains(DATA_URL_KEY));
+
+        let path = std::env::temp_dir().join(format!("{}.esb", uuid::Uuid::new_v4()));
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        let entry =
+            write_archive_board(&mut writer, &conn, &mut HashSet::new(), &board, None).unwrap();
+        let manifest = serde_json::to_vec(&test_manifest(vec![entry])).unwrap();
+        write_entry(
+            &mut writer,
+            MANIFEST_PATH,
+            &manifest,
+            CompressionMethod::Stored,
+        )
+        .unwrap();
+        writer.finish().unwrap();
+
+        let export = read_boards_export(&path, ExportContents::Full);
+        fs::remove_file(&path).unwrap();
+        let export = export.unwrap();
+        assert_eq!(image_data_url(&export), Some("data:image/png;base64,AAAA"));
+        assert_eq!(
+            export.boards[0].data.as_ref().unwrap()["files"]["image"][ASSET_HASH_KEY],
+            JsonValue::Null
+        );
+    }
+}
@@ -1,49 +1,225 @@
 use chrono::Utc;
-use rusqlite::params;
-use tauri::AppHandle;
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+use tauri::{AppHandle, Emitter};
 
-use crate::db::{board_id_exists, default_board_data, load_board_data_value, open_db};
+use crate::assets;
+use crate::commands::board_revisions::{record_revision_before_save, revision_window_elapsed};
+use crate::db::{
+    append_board_data_patch, board_data_revision, board_id_exists, default_board_data,
+    load_board_data_value, lock_db, write_board_data,
+};
+use crate::error::{AppError, AppResult};
+use crate::journal;
+use crate::models::{BoardContent, BoardDataPatch, BoardMergeResult};
+use crate::scene;
+use crate::scene_merge;
+use crate::search_index;
 use crate::thumbnails;
 
+const JOURNAL_CLEAR_FAILED_EVENT: &str = "board-journal-clear-failed";
+
+/// Saves a board's scene and returns its new revision. When `expected_revision` is given and
+/// another save has happened since, nothing is written and a conflict carrying the current
+/// revision is returned.
 #[tauri::command]
 pub(crate) fn save_board_data(
     app: AppHandle,
     board_id: String,
     data: String,
-) -> Result<(), String> {
-    let mut conn = open_db(&app)?;
-    let tx = conn.transaction().map_err(|error| error.to_string())?;
-
-    let updated = tx
-        .execute(
-            "UPDATE boards SET updated_at = ?1 WHERE id = ?2",
-            params![Utc::now().timestamp_millis(), board_id],
-        )
-        .map_err(|error| error.to_string())?;
+    expected_revision: Option<i64>,
+) -> AppResult<i64> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let revision = store_board_data(&tx, &board_id, &data, expected_revision)?;
+    tx.commit()?;
+    clear_journal_after_save(&app, &board_id);
+    Ok(revision)
+}
+
+/// Drops a board's journal once its changes are committed. The save has happened either way, so
+/// a failure is reported through `JOURNAL_CLEAR_FAILED_EVENT` instead of failing it; the stale
+/// journal no longer matches the stored revision and is discarded on next startup.
+fn clear_journal_after_save(app: &AppHandle, board_id: &str) {
+    if let Err(error) = journal::clear(app, board_id) {
+        let _ = app.emit(JOURNAL_CLEAR_FAILED_EVENT, &error.with_board_id(board_id));
+    }
+}
+
+/// Replaces a board's scene, bumping `updated_at`, moving embedded images to the asset
+/// store, recording a history entry when due and reindexing it for search. Returns the new
+/// scene revision. Runs in the caller's transaction.
+pub(crate) fn store_board_data(
+    conn: &Connection,
+    board_id: &str,
+    data: &str,
+    expected_revision: Option<i64>,
+) -> AppResult<i64> {
+    let now = Utc::now().timestamp_millis();
+
+    if let Some(expected_revision) = expected_revision {
+        let current_revision = board_data_revision(conn, board_id)?;
+        if current_revision != expected_revision {
+            return Err(AppError::stale_board_data(board_id, current_revision));
+        }
+    }
+
+    let updated = conn.execute(
+        "UPDATE boards SET updated_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![now, board_id],
+    )?;
     if updated == 0 {
-        return Err("Board not found".to_string());
+        return Err(AppError::board_not_found(board_id));
     }
 
-    tx.execute(
-        "INSERT OR REPLACE INTO board_data (board_id, data) VALUES (?1, ?2)",
-        params![board_id, data],
-    )
-    .map_err(|error| error.to_string())?;
+    let data = assets::extract_assets(conn, board_id, data)?;
+    let previous = load_board_data_value(conn, board_id)?;
+    record_revision_before_save(conn, board_id, previous.as_deref(), &data, now)?;
 
-    tx.commit().map_err(|error| error.to_string())?;
-    Ok(())
+    let revision = write_board_data(conn, board_id, &data)?;
+    search_index::index_board_scene(conn, board_id, &data)?;
+    assets::sync_board_assets(conn, board_id, &data)?;
+    Ok(revision)
 }
 
+/// Applies changed and deleted elements to a board's stored scene without resending the rest
+/// of it, and returns the new revision. A patch only makes sense against the scene it was
+/// computed from, so callers should pass `expected_revision`; as with `save_board_data`,
+/// leaving it out applies the patch to whatever is stored.
 #[tauri::command]
-pub(crate) fn load_board_data(app: AppHandle, board_id: String) -> Result<String, String> {
-    let conn = open_db(&app)?;
-    if let Some(data) = load_board_data_value(&conn, &board_id)? {
-        return Ok(data);
+pub(crate) fn patch_board_data(
+    app: AppHandle,
+    board_id: String,
+    patch: BoardDataPatch,
+    expected_revision: Option<i64>,
+) -> AppResult<i64> {
+    let mut conn = lock_db(&app)?;
+    let revision = apply_board_data_patch(&mut conn, &board_id, patch, expected_revision)?;
+    clear_journal_after_save(&app, &board_id);
+    Ok(revision)
+}
+
+pub(crate) fn apply_board_data_patch(
+    conn: &mut Connection,
+    board_id: &str,
+    mut patch: BoardDataPatch,
+    expected_revision: Option<i64>,
+) -> AppResult<i64> {
+    let tx = conn.transaction()?;
+    let now = Utc::now().timestamp_millis();
+
+    if let Some(expected_revision) = expected_revision {
+        let current_revision = board_data_revision(&tx, board_id)?;
+        if current_revision != expected_revision {
+            return Err(AppError::stale_board_data(board_id, current_revision));
+        }
     }
+    let updated = tx.execute(
+        "UPDATE boards SET updated_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![now, board_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::board_not_found(board_id));
+    }
+
+    assets::extract_file_assets(&tx, board_id, &mut patch.files)?;
+
+    // Recording a revision needs the whole scene, so only materialize it when one may be due.
+    // Otherwise the asset references cannot go stale: a patch adds files but never drops one.
+    let mut patched_scene = None;
+    if deletes_elements(&patch) || revision_window_elapsed(&tx, board_id, now)? {
+        if let Some(previous) = load_board_data_value(&tx, board_id)? {
+            let next = scene::apply_patch(&serde_json::from_str(&previous)?, &patch).to_string();
+            record_revision_before_save(&tx, board_id, Some(&previous), &next, now)?;
+            patched_scene = Some(next);
+        }
+    }
+
+    let revision = match append_board_data_patch(&tx, board_id, &patch, now)? {
+        Some(revision) => {
+            search_index::index_scene_patch(&tx, board_id, &patch)?;
+            revision
+        }
+        None => {
+            let base: JsonValue = serde_json::from_str(&default_board_data())?;
+            let data = scene::apply_patch(&base, &patch).to_string();
+            let revision = write_board_data(&tx, board_id, &data)?;
+            search_index::index_board_scene(&tx, board_id, &data)?;
+            revision
+        }
+    };
+    if let Some(scene) = patched_scene {
+        assets::sync_board_assets(&tx, board_id, &scene)?;
+    }
+
+    tx.commit()?;
+    Ok(revision)
+}
+
+/// Whether a patch removes elements, either outright or by flagging them `isDeleted` the way
+/// Excalidraw does. Either may clear the board, which always records a revision.
+fn deletes_elements(patch: &BoardDataPatch) -> bool {
+    !patch.deleted_element_ids.is_empty() || patch.elements.iter().any(scene::is_deleted_element)
+}
+
+#[tauri::command]
+pub(crate) fn load_board_data(app: AppHandle, board_id: String) -> AppResult<BoardContent> {
+    let conn = lock_db(&app)?;
     if !board_id_exists(&conn, &board_id)? {
-        return Err("Board not found".to_string());
+        return Err(AppError::board_not_found(&board_id));
+    }
+    let data = match load_board_data_value(&conn, &board_id)? {
+        Some(data) => assets::hydrate_assets(&conn, data)?,
+        None => default_board_data(),
+    };
+    Ok(BoardContent {
+        data,
+        revision: board_data_revision(&conn, &board_id)?,
+    })
+}
+
+/// Merges `data` into the board's stored scene element by element (see
+/// `scene_merge::merge_scenes`) and saves the result. Used to keep edits from a window whose
+/// save was rejected as stale.
+#[tauri::command]
+pub(crate) fn merge_board_data(
+    app: AppHandle,
+    board_id: String,
+    data: String,
+) -> AppResult<BoardMergeResult> {
+    let incoming: JsonValue = serde_json::from_str(&data)?;
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let result = merge_into_board(&tx, &board_id, &incoming)?;
+    tx.commit()?;
+    clear_journal_after_save(&app, &board_id);
+    Ok(result)
+}
+
+/// Merges `incoming` into a board's stored scene and saves the result, returning the merged
+/// scene with its images inlined. Runs in the caller's transaction.
+pub(crate) fn merge_into_board(
+    conn: &Connection,
+    board_id: &str,
+    incoming: &JsonValue,
+) -> AppResult<BoardMergeResult> {
+    if !board_id_exists(conn, board_id)? {
+        return Err(AppError::board_not_found(board_id));
     }
-    Ok(default_board_data())
+    let current = match load_board_data_value(conn, board_id)? {
+        Some(data) => assets::hydrate_assets(conn, data)?,
+        None => default_board_data(),
+    };
+    let current: JsonValue = serde_json::from_str(&current)?;
+
+    let merge = scene_merge::merge_scenes(&current, incoming);
+    let data = merge.scene.to_string();
+    let revision = store_board_data(conn, board_id, &data, None)?;
+    Ok(BoardMergeResult {
+        data,
+        revision,
+        conflicts: merge.conflicts,
+    })
 }
 
 #[tauri::command]
@@ -51,41 +227,117 @@ pub(crate) fn set_collaboration_link(
     app: AppHandle,
     board_id: String,
     link: Option<String>,
-) -> Result<(), String> {
-    let conn = open_db(&app)?;
-    let updated = conn
-        .execute(
-            "UPDATE boards SET collaboration_link = ?1, updated_at = ?2 WHERE id = ?3",
-            params![link, Utc::now().timestamp_millis(), board_id],
-        )
-        .map_err(|error| error.to_string())?;
+) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    let updated = conn.execute(
+        "UPDATE boards SET collaboration_link = ?1, updated_at = ?2
+         WHERE id = ?3 AND deleted_at IS NULL",
+        params![link, Utc::now().timestamp_millis(), board_id],
+    )?;
     if updated == 0 {
-        return Err("Board not found".to_string());
+        return Err(AppError::board_not_found(&board_id));
     }
     Ok(())
 }
 
+/// Holds the database lock while writing the file, so a thumbnail garbage collection pass
+/// never sees the new file before the row that references it.
 #[tauri::command]
 pub(crate) fn save_board_thumbnail(
     app: AppHandle,
     board_id: String,
     thumbnail: Option<String>,
-) -> Result<(), String> {
+) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
     let relative_path = thumbnails::save_thumbnail(
         &app,
         thumbnails::BoardId::from(board_id.as_str()),
         thumbnail.as_deref(),
     )?;
 
-    let conn = open_db(&app)?;
-    let updated = conn
-        .execute(
-            "UPDATE boards SET thumbnail = ?1 WHERE id = ?2",
-            params![relative_path, board_id],
-        )
-        .map_err(|error| error.to_string())?;
-    if updated == 0 {
-        return Err("Board not found".to_string());
-    }
+    conn.execute(
+        "UPDATE boards SET thumbnail = ?1 WHERE id = ?2",
+        params![relative_path, board_id],
+    )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::boards::insert_new_board;
+    use crate::commands::trash::move_board_to_trash;
+    use crate::db::open_test_db;
+
+    #[test]
+    fn trashed_boards_reject_writes() {
+        let mut conn = open_test_db();
+        insert_new_board(&conn, "board".to_string(), "Board".to_string()).unwrap();
+        let revision = board_data_revision(&conn, "board").unwrap();
+        move_board_to_trash(&conn, "board", 0).unwrap();
+
+        let saved = store_board_data(&conn, "board", &default_board_data(), None);
+        assert!(matches!(saved, Err(AppError::NotFound { .. })));
+        let patched = apply_board_data_patch(
+            &mut conn,
+            "board",
+            BoardDataPatch::default(),
+            Some(revision),
+        );
+        assert!(matches!(patched, Err(AppError::NotFound { .. })));
+        assert_eq!(board_data_revision(&conn, "board").unwrap(), revision);
+    }
+
+    fn assert_stale(result: AppResult<i64>, current: i64) {
+        match result {
+            Err(AppError::Conflict {
+                current_revision, ..
+            }) => assert_eq!(current_revision, Some(current)),
+            other => panic!("expected a revision conflict, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stale_saves_and_patches_are_rejected_with_the_current_revision() {
+        let mut conn = open_test_db();
+        insert_new_board(&conn, "board".to_string(), "Board".to_string()).unwrap();
+        let loaded = board_data_revision(&conn, "board").unwrap();
+        let current =
+            store_board_data(&conn, "board", &default_board_data(), Some(loaded)).unwrap();
+        assert_ne!(current, loaded);
+
+        let saved = store_board_data(&conn, "board", &default_board_data(), Some(loaded));
+        assert_stale(saved, current);
+        let patched =
+            apply_board_data_patch(&mut conn, "board", BoardDataPatch::default(), Some(loaded));
+        assert_stale(patched, current);
+        assert_eq!(board_data_revision(&conn, "board").unwrap(), current);
+    }
+
+    fn revision_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM board_revisions", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn patches_flagging_the_last_element_deleted_record_a_revision() {
+        let mut conn = open_test_db();
+        insert_new_board(&conn, "board".to_string(), "Board".to_string()).unwrap();
+        let scene = serde_json::json!({ "elements": [{ "id": "a", "type": "rectangle" }] });
+        let revision = store_board_data(&conn, "board", &scene.to_string(), None).unwrap();
+        let revisions_before = revision_count(&conn);
+
+        let patch = BoardDataPatch {
+            elements: vec![
+                serde_json::json!({ "id": "a", "type": "rectangle", "isDeleted": true }),
+            ],
+            ..BoardDataPatch::default()
+        };
+        apply_board_data_patch(&mut conn, "board", patch, Some(revision)).unwrap();
+
+        assert_eq!(revision_count(&conn), revisions_before + 1);
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use tauri::AppHandle;
+
+use crate::commands::board_content::apply_board_data_patch;
+use crate::db::{board_data_revision, datetime_from_millis, lock_db};
+use crate::error::{AppError, AppResult};
+use crate::journal::{self, JournalEntry};
+use crate::models::{BoardDataPatch, JournalRecovery};
+
+/// Records changes that are not saved yet, so they survive the app exiting before the next
+/// save. `patch` holds every change since the save that produced `base_revision`.
+#[tauri::command]
+pub(crate) fn append_board_journal(
+    app: AppHandle,
+    board_id: String,
+    base_revision: i64,
+    patch: BoardDataPatch,
+) -> AppResult<()> {
+    let entry = JournalEntry {
+        written_at: Utc::now().timestamp_millis(),
+        base_revision,
+        patch,
+    };
+    journal::append(&app, &board_id, &entry)
+}
+
+/// Boards with journaled changes that were never saved. Journals that no longer apply, because
+/// the board is gone or was saved since, are removed.
+#[tauri::command]
+pub(crate) fn list_board_journals(app: AppHandle) -> AppResult<Vec<JournalRecovery>> {
+    let conn = lock_db(&app)?;
+    let mut recoveries = Vec::new();
+
+    for board_id in journal::journaled_board_ids(&app)? {
+        let board_name: Option<String> = conn
+            .query_row(
+                "SELECT name FROM boards WHERE id = ?1 AND deleted_at IS NULL",
+                params![board_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let entry = match board_name {
+            Some(_) => {
+                let revision = board_data_revision(&conn, &board_id)?;
+                journal::latest_entry(&app, &board_id, revision)?
+            }
+            None => None,
+        };
+
+        match (board_name, entry) {
+            (Some(board_name), Some(entry)) => recoveries.push(JournalRecovery {
+                board_id,
+                board_name,
+                written_at: datetime_from_millis(entry.written_at)?,
+                changed_elements: entry.patch.elements.len(),
+                deleted_elements: entry.patch.deleted_element_ids.len(),
+            }),
+            _ => journal::clear(&app, &board_id)?,
+        }
+    }
+
+    recoveries.sort_by_key(|recovery| std::cmp::Reverse(recovery.written_at));
+    Ok(recoveries)
+}
+
+/// Applies a board's journaled changes to its stored scene and returns the new revision.
+#[tauri::command]
+pub(crate) fn recover_board_journal(app: AppHandle, board_id: String) -> AppResult<i64> {
+    let mut conn = lock_db(&app)?;
+    let revision = board_data_revision(&conn, &board_id)?;
+    let entry = journal::latest_entry(&app, &board_id, revision)?.ok_or_else(|| {
+        AppError::not_found("No unsaved changes to recover").with_board_id(&board_id)
+    })?;
+
+    let revision = apply_board_data_patch(&mut conn, &board_id, entry.patch, Some(revision))?;
+    journal::clear(&app, &board_id)?;
+    Ok(revision)
+}
+
+#[tauri::command]
+pub(crate) fn discard_board_journal(app: AppHandle, board_id: String) -> AppResult<()> {
+    journal::clear(&app, &board_id)
+}
@@ -0,0 +1,184 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::AppHandle;
+
+use crate::assets;
+use crate::compression::{encode_scene, StoredScene, COMPRESSION_ZSTD};
+use crate::db::{
+    board_id_exists, datetime_from_millis, load_board_data_value, lock_db, write_board_data,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{BoardContent, BoardRevision};
+use crate::scene;
+use crate::search_index;
+
+/// Saves landing within this window after the latest revision are coalesced into it, so
+/// the 1s autosave debounce doesn't produce a revision per keystroke.
+const REVISION_WINDOW_MS: i64 = 5 * 60 * 1000;
+const MAX_REVISIONS_PER_BOARD: i64 = 50;
+
+#[tauri::command]
+pub(crate) fn list_board_revisions(
+    app: AppHandle,
+    board_id: String,
+) -> AppResult<Vec<BoardRevision>> {
+    let conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, board_id, created_at, element_count, size_bytes FROM board_revisions
+             WHERE board_id = ?1 ORDER BY created_at DESC, id DESC",
+    )?;
+    let mut rows = stmt.query(params![board_id])?;
+
+    let mut revisions = Vec::new();
+    while let Some(row) = rows.next()? {
+        let created_at_ms: i64 = row.get(2)?;
+        revisions.push(BoardRevision {
+            id: row.get(0)?,
+            board_id: row.get(1)?,
+            created_at: datetime_from_millis(created_at_ms)?,
+            element_count: row.get(3)?,
+            size_bytes: row.get(4)?,
+        });
+    }
+
+    Ok(revisions)
+}
+
+#[tauri::command]
+pub(crate) fn load_board_revision(
+    app: AppHandle,
+    board_id: String,
+    revision_id: i64,
+) -> AppResult<String> {
+    let conn = lock_db(&app)?;
+    let data = load_revision_data(&conn, &board_id, revision_id)?;
+    assets::hydrate_assets(&conn, data)
+}
+
+/// Replaces the board's current scene with the given revision. The current scene is
+/// snapshotted first so that a restore can itself be undone. Returns the restored data with
+/// the scene revision it was written as.
+#[tauri::command]
+pub(crate) fn restore_board_revision(
+    app: AppHandle,
+    board_id: String,
+    revision_id: i64,
+) -> AppResult<BoardContent> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    if !board_id_exists(&tx, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+
+    let data = load_revision_data(&tx, &board_id, revision_id)?;
+    let now = Utc::now().timestamp_millis();
+    if let Some(current) = load_board_data_value(&tx, &board_id)? {
+        if current != data {
+            insert_revision(&tx, &board_id, &current, now)?;
+        }
+    }
+
+    tx.execute(
+        "UPDATE boards SET updated_at = ?1 WHERE id = ?2",
+        params![now, board_id],
+    )?;
+    let revision = write_board_data(&tx, &board_id, &data)?;
+    search_index::index_board_scene(&tx, &board_id, &data)?;
+    prune_revisions(&tx, &board_id)?;
+    assets::sync_board_assets(&tx, &board_id, &data)?;
+
+    let data = assets::hydrate_assets(&tx, data)?;
+    tx.commit()?;
+    Ok(BoardContent { data, revision })
+}
+
+/// Called by `save_board_data` before the stored scene is overwritten. Snapshots the
+/// outgoing scene when the latest revision is older than `REVISION_WINDOW_MS`, or
+/// unconditionally when the incoming scene would clear a non-empty board.
+pub(crate) fn record_revision_before_save(
+    conn: &Connection,
+    board_id: &str,
+    previous: Option<&str>,
+    next: &str,
+    now_ms: i64,
+) -> AppResult<()> {
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    if previous == next {
+        return Ok(());
+    }
+
+    let window_elapsed = revision_window_elapsed(conn, board_id, now_ms)?;
+    let clears_board =
+        scene::live_element_count(next) == 0 && scene::live_element_count(previous) > 0;
+
+    if window_elapsed || clears_board {
+        insert_revision(conn, board_id, previous, now_ms)?;
+        prune_revisions(conn, board_id)?;
+    }
+    Ok(())
+}
+
+/// Whether the latest revision of a board is older than `REVISION_WINDOW_MS`, i.e. whether
+/// the next change records one.
+pub(crate) fn revision_window_elapsed(
+    conn: &Connection,
+    board_id: &str,
+    now_ms: i64,
+) -> AppResult<bool> {
+    let latest_created_at: Option<i64> = conn.query_row(
+        "SELECT MAX(created_at) FROM board_revisions WHERE board_id = ?1",
+        params![board_id],
+        |row| row.get(0),
+    )?;
+    Ok(latest_created_at.is_none_or(|created_at| now_ms - created_at >= REVISION_WINDOW_MS))
+}
+
+fn insert_revision(
+    conn: &Connection,
+    board_id: &str,
+    data: &str,
+    created_at_ms: i64,
+) -> AppResult<()> {
+    conn.execute(
+        "INSERT INTO board_revisions
+            (board_id, created_at, element_count, size_bytes, data, compression)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            board_id,
+            created_at_ms,
+            scene::live_element_count(data) as i64,
+            data.len() as i64,
+            encode_scene(data)?,
+            COMPRESSION_ZSTD
+        ],
+    )?;
+    Ok(())
+}
+
+fn prune_revisions(conn: &Connection, board_id: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM board_revisions WHERE board_id = ?1 AND id NOT IN (
+            SELECT id FROM board_revisions WHERE board_id = ?1
+            ORDER BY created_at DESC, id DESC LIMIT ?2
+        )",
+        params![board_id, MAX_REVISIONS_PER_BOARD],
+    )?;
+    Ok(())
+}
+
+fn load_revision_data(conn: &Connection, board_id: &str, revision_id: i64) -> AppResult<String> {
+    conn.query_row(
+        "SELECT data, compression FROM board_revisions WHERE id = ?1 AND board_id = ?2",
+        params![revision_id, board_id],
+        |row| StoredScene::from_row(row, 0, 1),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Revision not found").with_board_id(board_id))?
+    .decode()
+}
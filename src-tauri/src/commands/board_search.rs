@@ -0,0 +1,59 @@
+use rusqlite::params;
+use tauri::AppHandle;
+
+use crate::db::lock_db;
+use crate::error::AppResult;
+use crate::models::BoardSearchHit;
+
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+const SNIPPET_TOKENS: i64 = 12;
+
+/// Full-text search over board names and the text elements of every board scene. Hits on a
+/// board name have no `element_id`. Matched terms in `snippet` are wrapped in `<mark>` tags.
+#[tauri::command]
+pub(crate) fn search_boards(
+    app: AppHandle,
+    query: String,
+    limit: Option<u32>,
+) -> AppResult<Vec<BoardSearchHit>> {
+    let Some(match_expression) = build_match_expression(&query) else {
+        return Ok(Vec::new());
+    };
+
+    let conn = lock_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT board_search.board_id, boards.name, board_search.element_id,
+                    snippet(board_search, 2, '<mark>', '</mark>', '…', ?3)
+             FROM board_search
+             JOIN boards ON boards.id = board_search.board_id
+             WHERE board_search MATCH ?1 AND boards.deleted_at IS NULL
+             ORDER BY rank LIMIT ?2",
+    )?;
+    let mut rows = stmt.query(params![
+        match_expression,
+        limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        SNIPPET_TOKENS
+    ])?;
+
+    let mut hits = Vec::new();
+    while let Some(row) = rows.next()? {
+        hits.push(BoardSearchHit {
+            board_id: row.get(0)?,
+            board_name: row.get(1)?,
+            element_id: row.get(2)?,
+            snippet: row.get(3)?,
+        });
+    }
+
+    Ok(hits)
+}
+
+/// Turns free-form user input into an FTS5 query: every whitespace-separated term is quoted
+/// (so operators and punctuation are matched literally) and prefix-matched.
+fn build_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
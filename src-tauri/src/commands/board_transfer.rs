@@ -1,67 +1,77 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
 use serde_json::Value as JsonValue;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::Path;
 use tauri::AppHandle;
+use uuid::Uuid;
 
-use crate::commands::board_content::save_board_data;
-use crate::commands::boards::{create_board_record, load_resolved_boards_index};
+use crate::assets;
+use crate::commands::board_archive::{read_boards_export, ExportContents};
+use crate::commands::board_content::{merge_into_board, store_board_data};
+use crate::commands::boards::{
+    insert_folder_item_at, insert_index_item_at, insert_new_board, load_resolved_boards_index,
+};
 use crate::db::{
-    default_board_data, get_setting, load_board_data_value, load_boards_index_from_db, open_db,
-    set_setting,
+    datetime_from_millis, default_board_data, get_setting, is_safe_board_id, load_board_data_value,
+    load_boards_index_from_db, lock_db, set_setting,
 };
+use crate::error::AppResult;
 use crate::models::{
-    Board, BoardListItem, BoardsExportEntry, BoardsExportFile, BoardsImportResult,
+    Board, BoardListItem, BoardsExportEntry, BoardsExportFile, BoardsExportIndexItem,
+    BoardsImportResult, ImportAction, ImportConflictStrategy, ImportEntryDecision,
 };
+use crate::search_index;
 use crate::thumbnails;
 
 const ACTIVE_BOARD_SETTING_KEY: &str = "active_board_id";
+/// Version 2 added the folder tree (`index`) and version 3 lets folders nest; version 1 files
+/// import flat into the root.
+const EXPORT_FILE_VERSION: u8 = 3;
 
 #[tauri::command]
-pub(crate) fn export_boards(app: AppHandle, file_path: String) -> Result<(), String> {
-    let conn = open_db(&app)?;
-    let index = load_boards_index_from_db(&conn)?;
+pub(crate) fn export_boards(app: AppHandle, file_path: String) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    let export_file = collect_export(&app, &conn)?;
 
-    let mut boards = Vec::new();
-    let mut seen = HashSet::new();
-    let mut ctx = ExportContext {
-        app: &app,
-        conn: &conn,
-        seen: &mut seen,
-        export_entries: &mut boards,
-    };
+    let payload = serde_json::to_string_pretty(&export_file)?;
+    fs::write(file_path, payload)?;
+    Ok(())
+}
 
-    for item in &index.items {
-        export_item_boards(&mut ctx, item)?;
+/// Reads an export file (JSON bundle or `.esb` archive) for the import preview. Board scene
+/// data and thumbnails are omitted to keep the payload small.
+#[tauri::command]
+pub(crate) fn read_boards_import_file(file_path: String) -> AppResult<BoardsExportFile> {
+    let mut export_file = read_boards_export(Path::new(&file_path), ExportContents::Scenes)?;
+    for entry in &mut export_file.boards {
+        entry.data = None;
+        entry.thumbnail = None;
     }
-
-    let export_file = BoardsExportFile {
-        version: 1,
-        exported_at: Utc::now(),
-        boards,
-    };
-
-    let payload = serde_json::to_string_pretty(&export_file).map_err(|error| error.to_string())?;
-    fs::write(file_path, payload).map_err(|error| error.to_string())?;
-    Ok(())
+    Ok(export_file)
 }
 
+/// Imports the selected entries of an export file in one transaction, so a failure leaves the
+/// database as it was. `strategy` decides what happens to entries whose id matches an
+/// existing board; it defaults to keeping both.
 #[tauri::command]
 pub(crate) fn import_boards(
     app: AppHandle,
     file_path: String,
     selected_indices: Vec<usize>,
-) -> Result<BoardsImportResult, String> {
-    let payload = fs::read_to_string(file_path).map_err(|error| error.to_string())?;
-    let export_file: BoardsExportFile =
-        serde_json::from_str(&payload).map_err(|error| error.to_string())?;
+    strategy: Option<ImportConflictStrategy>,
+) -> AppResult<BoardsImportResult> {
+    let export_file = read_boards_export(Path::new(&file_path), ExportContents::Full)?;
 
-    let conn = open_db(&app)?;
-    let active_before = get_setting(&conn, ACTIVE_BOARD_SETTING_KEY)?;
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let active_before = get_setting(&tx, ACTIVE_BOARD_SETTING_KEY)?;
 
-    let (existing_ids, mut used_names) = load_existing_board_ids_and_names(&conn)?;
+    let mut planner = ImportPlanner::load(&tx, strategy.unwrap_or_default())?;
     let selected: HashSet<usize> = selected_indices.into_iter().collect();
-    let mut seen_ids = existing_ids;
+    let mut imported_ids = HashMap::new();
+    let mut thumbnails = Vec::new();
     let mut imported = 0;
     let mut skipped = 0;
 
@@ -70,15 +80,36 @@ pub(crate) fn import_boards(
             continue;
         }
 
-        let did_import = import_selected_entry(&app, entry, &mut seen_ids, &mut used_names)?;
-        if did_import {
+        let decision = planner.decide(index, entry);
+        let applied = match decision.action {
+            ImportAction::Skip => false,
+            ImportAction::Replace => {
+                replace_existing_board(&tx, entry, &decision.name, &mut thumbnails)?;
+                true
+            }
+            ImportAction::Merge => {
+                merge_existing_board(&tx, entry)?;
+                true
+            }
+            ImportAction::Create | ImportAction::CreateWithId | ImportAction::Copy => {
+                let created_id = import_selected_entry(&tx, entry, &decision, &mut thumbnails)?;
+                imported_ids.entry(entry.id.clone()).or_insert(created_id);
+                true
+            }
+        };
+
+        if applied {
             imported += 1;
+            planner.register(entry, &decision);
         } else {
             skipped += 1;
         }
     }
 
-    restore_active_board(&conn, active_before)?;
+    restore_imported_items(&tx, None, &export_file.index, &imported_ids)?;
+    restore_active_board(&tx, active_before)?;
+    tx.commit()?;
+    write_imported_thumbnails(&app, &conn, &thumbnails)?;
 
     let index = load_resolved_boards_index(&app, &conn)?;
     Ok(BoardsImportResult {
@@ -88,59 +119,286 @@ pub(crate) fn import_boards(
     })
 }
 
+/// Dry run of `import_boards`: returns the decision for every selected entry without writing
+/// anything.
+#[tauri::command]
+pub(crate) fn preview_boards_import(
+    app: AppHandle,
+    file_path: String,
+    selected_indices: Vec<usize>,
+    strategy: Option<ImportConflictStrategy>,
+) -> AppResult<Vec<ImportEntryDecision>> {
+    let export_file = read_boards_export(Path::new(&file_path), ExportContents::Scenes)?;
+    let conn = lock_db(&app)?;
+
+    let mut planner = ImportPlanner::load(&conn, strategy.unwrap_or_default())?;
+    let selected: HashSet<usize> = selected_indices.into_iter().collect();
+    let mut decisions = Vec::new();
+
+    for (index, entry) in export_file.boards.iter().enumerate() {
+        if !selected.contains(&index) {
+            continue;
+        }
+
+        let decision = planner.decide(index, entry);
+        planner.register(entry, &decision);
+        decisions.push(decision);
+    }
+
+    Ok(decisions)
+}
+
+/// Builds the full export for every board in index order, with scene data and thumbnails
+/// inlined and the folder tree recorded in `index`.
+pub(crate) fn collect_export(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+) -> AppResult<BoardsExportFile> {
+    let (boards, index) = collect_export_boards(conn)?;
+    let boards = boards
+        .iter()
+        .map(|board| build_export_entry(app, conn, board))
+        .collect::<AppResult<_>>()?;
+
+    Ok(BoardsExportFile {
+        version: EXPORT_FILE_VERSION,
+        exported_at: Utc::now(),
+        boards,
+        index,
+    })
+}
+
+/// Every exported board once, in index order, along with the exported folder tree. Exports
+/// that write their own entries start from this rather than from `collect_export`.
+pub(crate) fn collect_export_boards(
+    conn: &rusqlite::Connection,
+) -> AppResult<(Vec<Board>, Vec<BoardsExportIndexItem>)> {
+    let index = load_boards_index_from_db(conn)?;
+
+    let mut boards = Vec::new();
+    let mut export_index = Vec::new();
+    let mut seen = HashSet::new();
+    let mut ctx = ExportContext {
+        seen: &mut seen,
+        boards: &mut boards,
+    };
+
+    for item in &index.items {
+        export_index.extend(export_item_boards(&mut ctx, item));
+    }
+    Ok((boards, export_index))
+}
+
 /// Shared state threaded through the recursive export helpers.
 struct ExportContext<'a> {
-    app: &'a AppHandle,
-    conn: &'a rusqlite::Connection,
     seen: &'a mut HashSet<String>,
-    export_entries: &'a mut Vec<BoardsExportEntry>,
+    boards: &'a mut Vec<Board>,
 }
 
-fn export_item_boards(ctx: &mut ExportContext<'_>, item: &BoardListItem) -> Result<(), String> {
+/// Smart folders are not exported: their queries refer to tags by id, which mean nothing in
+/// another database.
+fn export_item_boards(
+    ctx: &mut ExportContext<'_>,
+    item: &BoardListItem,
+) -> Option<BoardsExportIndexItem> {
     match item {
-        BoardListItem::Board(board) => export_board_if_new(ctx, board),
-        BoardListItem::Folder(folder) => {
-            for board in &folder.items {
-                export_board_if_new(ctx, board)?;
+        BoardListItem::Board(board) => {
+            if ctx.seen.insert(board.id.clone()) {
+                ctx.boards.push(board.clone());
             }
+            Some(BoardsExportIndexItem::Board {
+                id: board.id.clone(),
+            })
+        }
+        BoardListItem::Folder(folder) => {
+            let items = folder
+                .items
+                .iter()
+                .filter_map(|item| export_item_boards(ctx, item))
+                .collect();
+            Some(BoardsExportIndexItem::Folder {
+                id: folder.id.clone(),
+                name: folder.name.clone(),
+                items,
+                board_ids: Vec::new(),
+            })
+        }
+        BoardListItem::SmartFolder(_) => None,
+    }
+}
+
+/// Imports one entry as a new board, returning the id of the created board.
+fn import_selected_entry(
+    conn: &rusqlite::Connection,
+    entry: &BoardsExportEntry,
+    decision: &ImportEntryDecision,
+    thumbnails: &mut Vec<thumbnails::StagedThumbnail>,
+) -> AppResult<String> {
+    let board_id = match decision.action {
+        ImportAction::CreateWithId => entry.id.clone(),
+        _ => Uuid::new_v4().to_string(),
+    };
+    let created = insert_new_board(conn, board_id, decision.name.clone())?;
+
+    persist_imported_board_data(conn, &created.id, entry)?;
+    persist_imported_board_thumbnail(conn, &created.id, entry, thumbnails)?;
+    conn.execute(
+        "UPDATE boards SET collaboration_link = ?1 WHERE id = ?2",
+        rusqlite::params![entry.collaboration_link, created.id],
+    )?;
+    Ok(created.id)
+}
+
+/// Overwrites an existing board with an export entry, including its collaboration link,
+/// keeping its place in the index. The entry's `updated_at` is kept so that importing the same
+/// file again is a no-op.
+fn replace_existing_board(
+    conn: &rusqlite::Connection,
+    entry: &BoardsExportEntry,
+    name: &str,
+    thumbnails: &mut Vec<thumbnails::StagedThumbnail>,
+) -> AppResult<()> {
+    persist_imported_board_data(conn, &entry.id, entry)?;
+    persist_imported_board_thumbnail(conn, &entry.id, entry, thumbnails)?;
+
+    conn.execute(
+        "UPDATE boards SET name = ?1, updated_at = ?2, collaboration_link = ?3 WHERE id = ?4",
+        rusqlite::params![
+            name,
+            entry.updated_at.timestamp_millis(),
+            entry.collaboration_link,
+            entry.id
+        ],
+    )?;
+    search_index::index_board_name(conn, &entry.id, name)
+}
+
+/// Merges an export entry's scene into the existing board with the same id. The board keeps
+/// its name and thumbnail.
+fn merge_existing_board(conn: &rusqlite::Connection, entry: &BoardsExportEntry) -> AppResult<()> {
+    match entry.data.as_ref() {
+        Some(data) if !data.is_null() => {
+            merge_into_board(conn, &entry.id, data)?;
             Ok(())
         }
+        _ => Ok(()),
     }
 }
 
-fn export_board_if_new(ctx: &mut ExportContext<'_>, board: &Board) -> Result<(), String> {
-    if !ctx.seen.insert(board.id.clone()) {
-        return Ok(());
+/// Recreates the exported folder tree for the boards that were just imported (which start
+/// out appended to the root). Boards are moved to the end of the root or into their folder
+/// in tree order. A folder is merged into an existing one with the same id or, failing that,
+/// the same name under the same parent; otherwise it is created where it appears in the tree.
+fn restore_imported_items(
+    conn: &rusqlite::Connection,
+    parent_folder_id: Option<&str>,
+    items: &[BoardsExportIndexItem],
+    imported_ids: &HashMap<String, String>,
+) -> AppResult<()> {
+    for item in items {
+        match item {
+            BoardsExportIndexItem::Board { id } => {
+                let Some(created_id) = imported_ids.get(id) else {
+                    continue;
+                };
+                detach_root_board(conn, created_id)?;
+                match parent_folder_id {
+                    Some(folder_id) => {
+                        insert_folder_item_at(conn, folder_id, i64::MAX, "board", created_id)?
+                    }
+                    None => insert_index_item_at(conn, i64::MAX, "board", created_id)?,
+                }
+            }
+            BoardsExportIndexItem::Folder {
+                id,
+                name,
+                items,
+                board_ids,
+            } => {
+                let legacy_items: Vec<BoardsExportIndexItem> = board_ids
+                    .iter()
+                    .map(|board_id| BoardsExportIndexItem::Board {
+                        id: board_id.clone(),
+                    })
+                    .collect();
+                let children = [items.as_slice(), legacy_items.as_slice()].concat();
+                if !contains_imported_board(&children, imported_ids) {
+                    continue;
+                }
+
+                let folder_id = resolve_import_folder(conn, parent_folder_id, id, name)?;
+                restore_imported_items(conn, Some(&folder_id), &children, imported_ids)?;
+            }
+        }
     }
+    Ok(())
+}
+
+fn contains_imported_board(
+    items: &[BoardsExportIndexItem],
+    imported_ids: &HashMap<String, String>,
+) -> bool {
+    items.iter().any(|item| match item {
+        BoardsExportIndexItem::Board { id } => imported_ids.contains_key(id),
+        BoardsExportIndexItem::Folder {
+            items, board_ids, ..
+        } => {
+            contains_imported_board(items, imported_ids)
+                || board_ids.iter().any(|id| imported_ids.contains_key(id))
+        }
+    })
+}
 
-    let entry = build_export_entry(ctx.app, ctx.conn, board)?;
-    ctx.export_entries.push(entry);
+fn detach_root_board(conn: &rusqlite::Connection, board_id: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM index_items WHERE item_type = 'board' AND item_id = ?1",
+        rusqlite::params![board_id],
+    )?;
     Ok(())
 }
 
-fn import_selected_entry(
-    app: &AppHandle,
-    entry: &BoardsExportEntry,
-    seen_ids: &mut HashSet<String>,
-    used_names: &mut HashSet<String>,
-) -> Result<bool, String> {
-    let final_name = resolve_import_name(entry, seen_ids, used_names);
-    let created = match create_board_record(app, final_name.clone()) {
-        Ok(board) => board,
-        Err(_) => return Ok(false),
-    };
+fn resolve_import_folder(
+    conn: &rusqlite::Connection,
+    parent_folder_id: Option<&str>,
+    folder_id: &str,
+    folder_name: &str,
+) -> AppResult<String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM folders WHERE id = ?1
+             UNION ALL
+             SELECT id FROM folders
+             WHERE lower(trim(name)) = lower(trim(?2)) AND parent_folder_id IS ?3
+             LIMIT 1",
+            rusqlite::params![folder_id, folder_name, parent_folder_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
 
-    persist_imported_board_data(app.clone(), &created.id, entry)?;
-    persist_imported_board_thumbnail(app, &created.id, entry)?;
-    register_imported_identity(entry, &final_name, seen_ids, used_names);
-    Ok(true)
+    let folder_id = if folder_id.trim().is_empty() {
+        Uuid::new_v4().to_string()
+    } else {
+        folder_id.to_string()
+    };
+    conn.execute(
+        "INSERT INTO folders (id, name, parent_folder_id) VALUES (?1, ?2, ?3)",
+        rusqlite::params![folder_id, folder_name, parent_folder_id],
+    )?;
+    match parent_folder_id {
+        Some(parent_id) => insert_folder_item_at(conn, parent_id, i64::MAX, "folder", &folder_id)?,
+        None => insert_index_item_at(conn, i64::MAX, "folder", &folder_id)?,
+    }
+    Ok(folder_id)
 }
 
 fn persist_imported_board_data(
-    app: AppHandle,
+    conn: &rusqlite::Connection,
     created_board_id: &str,
     entry: &BoardsExportEntry,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let Some(data_value) = entry.data.as_ref() else {
         return Ok(());
     };
@@ -150,54 +408,154 @@ fn persist_imported_board_data(
     }
 
     let data_str = data_value.to_string();
-    save_board_data(app, created_board_id.to_string(), data_str)
+    store_board_data(conn, created_board_id, &data_str, None)?;
+    Ok(())
 }
 
+/// Points the board at its imported thumbnail and stages the file, which is only written once
+/// the import is committed so a failed import leaves existing thumbnails untouched.
 fn persist_imported_board_thumbnail(
-    app: &AppHandle,
+    conn: &rusqlite::Connection,
     created_board_id: &str,
     entry: &BoardsExportEntry,
-) -> Result<(), String> {
+    thumbnails: &mut Vec<thumbnails::StagedThumbnail>,
+) -> AppResult<()> {
     let Some(thumbnail) = entry.thumbnail.as_deref() else {
         return Ok(());
     };
 
-    let relative_path = thumbnails::save_thumbnail(
-        app,
+    let staged = thumbnails::StagedThumbnail::decode(
         thumbnails::BoardId::from(created_board_id),
-        Some(thumbnail),
+        thumbnail,
     )?;
-    let conn = open_db(app)?;
     conn.execute(
         "UPDATE boards SET thumbnail = ?1 WHERE id = ?2",
-        rusqlite::params![relative_path, created_board_id],
-    )
-    .map_err(|error| error.to_string())?;
+        rusqlite::params![staged.relative_path(), created_board_id],
+    )?;
+    thumbnails.push(staged);
     Ok(())
 }
 
-fn load_existing_board_ids_and_names(
+/// Writes the thumbnails staged by a committed import. A thumbnail is only a cache the frontend
+/// regenerates, so one that cannot be written is dropped from its board instead of failing
+/// the import.
+fn write_imported_thumbnails(
+    app: &AppHandle,
     conn: &rusqlite::Connection,
-) -> Result<(HashSet<String>, HashSet<String>), String> {
-    let mut stmt = conn
-        .prepare("SELECT id, name FROM boards")
-        .map_err(|error| error.to_string())?;
-    let mut rows = stmt.query([]).map_err(|error| error.to_string())?;
-    let mut ids = HashSet::new();
-    let mut used_names = HashSet::new();
-
-    while let Some(row) = rows.next().map_err(|error| error.to_string())? {
-        let id: String = row.get(0).map_err(|error| error.to_string())?;
-        let name: String = row.get(1).map_err(|error| error.to_string())?;
-
-        ids.insert(id);
-        let name_key = name.trim().to_lowercase();
-        if !name_key.is_empty() {
-            used_names.insert(name_key);
+    thumbnails: &[thumbnails::StagedThumbnail],
+) -> AppResult<()> {
+    for thumbnail in thumbnails {
+        if thumbnail.write(app).is_err() {
+            conn.execute(
+                "UPDATE boards SET thumbnail = NULL WHERE id = ?1",
+                rusqlite::params![thumbnail.board_id()],
+            )?;
         }
     }
+    Ok(())
+}
 
-    Ok((ids, used_names))
+/// Decides what importing each entry does. Entries are registered as they are accepted, so
+/// duplicates within one export file resolve against each other the same way they resolve
+/// against the database. Shared by `import_boards` and its preview so the two always agree.
+struct ImportPlanner {
+    strategy: ImportConflictStrategy,
+    /// Live boards by id, with their `updated_at`.
+    existing: HashMap<String, DateTime<Utc>>,
+    /// Ids of trashed boards. These cannot be reused, but are not replaced or skipped either.
+    trashed_ids: HashSet<String>,
+    used_names: HashSet<String>,
+}
+
+impl ImportPlanner {
+    fn load(conn: &rusqlite::Connection, strategy: ImportConflictStrategy) -> AppResult<Self> {
+        let mut stmt = conn.prepare("SELECT id, name, updated_at, deleted_at FROM boards")?;
+        let mut rows = stmt.query([])?;
+        let mut planner = Self {
+            strategy,
+            existing: HashMap::new(),
+            trashed_ids: HashSet::new(),
+            used_names: HashSet::new(),
+        };
+
+        while let Some(row) = rows.next()? {
+            let id: String = row.get(0)?;
+            let name: String = row.get(1)?;
+            let updated_at_ms: i64 = row.get(2)?;
+            let deleted_at: Option<i64> = row.get(3)?;
+
+            if deleted_at.is_some() {
+                planner.trashed_ids.insert(id);
+            } else {
+                planner
+                    .existing
+                    .insert(id, datetime_from_millis(updated_at_ms)?);
+            }
+            let name_key = name.trim().to_lowercase();
+            if !name_key.is_empty() {
+                planner.used_names.insert(name_key);
+            }
+        }
+
+        Ok(planner)
+    }
+
+    fn decide(&self, index: usize, entry: &BoardsExportEntry) -> ImportEntryDecision {
+        let base_name = normalize_import_name(&entry.name);
+        let has_id = !entry.id.trim().is_empty();
+        let existing = if has_id {
+            self.existing.get(&entry.id)
+        } else {
+            None
+        };
+
+        let action = match (self.strategy, existing) {
+            (ImportConflictStrategy::Skip, Some(_)) => ImportAction::Skip,
+            (ImportConflictStrategy::ReplaceIfNewer, Some(updated_at)) => {
+                if entry.updated_at > *updated_at {
+                    ImportAction::Replace
+                } else {
+                    ImportAction::Skip
+                }
+            }
+            (ImportConflictStrategy::Merge, Some(_)) => ImportAction::Merge,
+            (ImportConflictStrategy::KeepBoth | ImportConflictStrategy::PreserveId, Some(_)) => {
+                ImportAction::Copy
+            }
+            (_, None) if has_id && self.trashed_ids.contains(&entry.id) => ImportAction::Copy,
+            (ImportConflictStrategy::KeepBoth, None) => ImportAction::Create,
+            // The id ends up in file names, so one that could escape a directory is dropped.
+            (_, None) if is_safe_board_id(&entry.id) => ImportAction::CreateWithId,
+            (_, None) => ImportAction::Create,
+        };
+
+        let name = if action == ImportAction::Copy {
+            make_copy_name(&base_name, &self.used_names)
+        } else {
+            base_name
+        };
+
+        ImportEntryDecision {
+            index,
+            id: entry.id.clone(),
+            name,
+            action,
+        }
+    }
+
+    fn register(&mut self, entry: &BoardsExportEntry, decision: &ImportEntryDecision) {
+        if decision.action == ImportAction::Skip {
+            return;
+        }
+
+        self.used_names.insert(decision.name.to_lowercase());
+        let has_id = !entry.id.trim().is_empty();
+        if has_id
+            && (decision.action == ImportAction::Replace || !self.existing.contains_key(&entry.id))
+        {
+            self.existing.insert(entry.id.clone(), entry.updated_at);
+        }
+    }
 }
 
 fn normalize_import_name(name: &str) -> String {
@@ -218,38 +576,10 @@ fn make_copy_name(base: &str, used_names: &HashSet<String>) -> String {
     candidate
 }
 
-fn resolve_import_name(
-    entry: &BoardsExportEntry,
-    seen_ids: &HashSet<String>,
-    used_names: &HashSet<String>,
-) -> String {
-    let base_name = normalize_import_name(&entry.name);
-    let has_id = !entry.id.trim().is_empty();
-    let is_duplicate = has_id && seen_ids.contains(&entry.id);
-    if is_duplicate {
-        make_copy_name(&base_name, used_names)
-    } else {
-        base_name
-    }
-}
-
-fn register_imported_identity(
-    entry: &BoardsExportEntry,
-    final_name: &str,
-    seen_ids: &mut HashSet<String>,
-    used_names: &mut HashSet<String>,
-) {
-    let has_id = !entry.id.trim().is_empty();
-    used_names.insert(final_name.to_lowercase());
-    if has_id {
-        seen_ids.insert(entry.id.clone());
-    }
-}
-
-fn restore_active_board(
+pub(crate) fn restore_active_board(
     conn: &rusqlite::Connection,
     active_before: Option<String>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if let Some(active_id) = active_before {
         set_setting(conn, ACTIVE_BOARD_SETTING_KEY, Some(&active_id))?;
     }
@@ -260,8 +590,11 @@ fn build_export_entry(
     app: &AppHandle,
     conn: &rusqlite::Connection,
     board: &Board,
-) -> Result<BoardsExportEntry, String> {
-    let data_str = load_board_data_value(conn, &board.id)?.unwrap_or_else(default_board_data);
+) -> AppResult<BoardsExportEntry> {
+    let data_str = match load_board_data_value(conn, &board.id)? {
+        Some(data) => assets::hydrate_assets(conn, data)?,
+        None => default_board_data(),
+    };
     let data_json: JsonValue = serde_json::from_str(&data_str).unwrap_or(JsonValue::Null);
     let thumbnail = thumbnails::load_thumbnail_data_url(
         app,
@@ -281,3 +614,32 @@ fn build_export_entry(
         data: Some(data_json),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn export_entry(id: &str) -> BoardsExportEntry {
+        BoardsExportEntry {
+            id: id.to_string(),
+            name: "Board".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            collaboration_link: None,
+            thumbnail: None,
+            data: None,
+        }
+    }
+
+    #[test]
+    fn imports_only_keep_ids_that_are_safe_in_file_names() {
+        let conn = open_test_db();
+        let planner = ImportPlanner::load(&conn, ImportConflictStrategy::PreserveId).unwrap();
+
+        let kept = planner.decide(0, &export_entry("3f2a9c1e-board_1"));
+        assert_eq!(kept.action, ImportAction::CreateWithId);
+        let dropped = planner.decide(1, &export_entry("../../../x"));
+        assert_eq!(dropped.action, ImportAction::Create);
+    }
+}
@@ -1,151 +1,208 @@
 use chrono::Utc;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
+use std::collections::HashSet;
 use tauri::AppHandle;
 use uuid::Uuid;
 
+use crate::assets;
+use crate::commands::trash::move_board_to_trash;
 use crate::db::{
-    board_exists, board_id_exists, default_board_data, first_board_id, first_board_id_from_db,
-    get_board_by_id, get_setting, load_board_data_value, load_boards_index_from_db,
-    normalize_active_board_id, open_db, set_setting,
+    board_exists, board_id_exists, default_board_data, find_board, first_board_id,
+    first_board_id_from_db, get_board_by_id, get_setting, load_board_data_value,
+    load_boards_index_from_db, load_boards_map, lock_db, normalize_active_board_id, pinned_boards,
+    set_setting, write_board_data,
 };
+use crate::error::{AppError, AppResult};
 use crate::models::{Board, BoardFolder, BoardListItem, BoardMutationResult, BoardsIndex};
+use crate::search_index;
 use crate::thumbnails;
 
 const ACTIVE_BOARD_SETTING_KEY: &str = "active_board_id";
+const DEFAULT_RECENT_BOARDS_LIMIT: u32 = 10;
 
 struct BoardDataPayload(String);
 
 #[tauri::command]
-pub(crate) fn get_boards(app: AppHandle) -> Result<BoardsIndex, String> {
-    let conn = open_db(&app)?;
+pub(crate) fn get_boards(app: AppHandle) -> AppResult<BoardsIndex> {
+    let conn = lock_db(&app)?;
     load_resolved_boards_index(&app, &conn)
 }
 
 #[tauri::command]
-pub(crate) fn create_board(app: AppHandle, name: String) -> Result<BoardMutationResult, String> {
-    let mut conn = open_db(&app)?;
-    let board = insert_new_board(&mut conn, name)?;
+pub(crate) fn create_board(app: AppHandle, name: String) -> AppResult<BoardMutationResult> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let board = insert_new_board(&tx, Uuid::new_v4().to_string(), name)?;
+    tx.commit()?;
     build_mutation_result(&app, &conn, &board.id)
 }
 
-pub(crate) fn create_board_record(app: &AppHandle, name: String) -> Result<Board, String> {
-    let mut conn = open_db(app)?;
-    insert_new_board(&mut conn, name)
-}
-
-fn insert_new_board(conn: &mut rusqlite::Connection, name: String) -> Result<Board, String> {
+/// Creates an empty board under `board_id` and makes it the active board. Fails if the id
+/// is taken.
+pub(crate) fn insert_new_board(
+    conn: &rusqlite::Connection,
+    board_id: String,
+    name: String,
+) -> AppResult<Board> {
     let now = Utc::now();
     let board = Board {
-        id: Uuid::new_v4().to_string(),
+        id: board_id,
         name,
         created_at: now,
         updated_at: now,
         collaboration_link: None,
         thumbnail: None,
+        tags: Vec::new(),
+        pinned: false,
+        last_opened_at: None,
     };
 
-    let tx = conn.transaction().map_err(|error| error.to_string())?;
     let board_data = BoardDataPayload(default_board_data());
-    insert_board_with_data(&tx, &board, &board_data)?;
+    insert_board_with_data(conn, &board, &board_data)?;
 
-    tx.execute(
+    conn.execute(
         "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
         params![ACTIVE_BOARD_SETTING_KEY, board.id],
-    )
-    .map_err(|error| error.to_string())?;
-
-    tx.commit().map_err(|error| error.to_string())?;
+    )?;
     Ok(board)
 }
 
 #[tauri::command]
-pub(crate) fn rename_board(
-    app: AppHandle,
-    board_id: String,
-    new_name: String,
-) -> Result<Board, String> {
-    let conn = open_db(&app)?;
+pub(crate) fn rename_board(app: AppHandle, board_id: String, new_name: String) -> AppResult<Board> {
+    let conn = lock_db(&app)?;
     let now = Utc::now().timestamp_millis();
-    let updated = conn
-        .execute(
-            "UPDATE boards SET name = ?1, updated_at = ?2 WHERE id = ?3",
-            params![new_name, now, board_id],
-        )
-        .map_err(|error| error.to_string())?;
+    let updated = conn.execute(
+        "UPDATE boards SET name = ?1, updated_at = ?2 WHERE id = ?3 AND deleted_at IS NULL",
+        params![new_name, now, board_id],
+    )?;
     if updated == 0 {
-        return Err("Board not found".to_string());
+        return Err(AppError::board_not_found(&board_id));
     }
+    search_index::index_board_name(&conn, &board_id, &new_name)?;
     let board = get_board_by_id(&conn, &board_id)?;
     resolve_board_thumbnail(&app, board)
 }
 
+/// Moves a board to the trash. Its data and thumbnail are kept until it is purged, either
+/// explicitly or once the trash retention period has passed.
 #[tauri::command]
-pub(crate) fn delete_board(app: AppHandle, board_id: String) -> Result<BoardsIndex, String> {
-    let mut conn = open_db(&app)?;
-    let tx = conn.transaction().map_err(|error| error.to_string())?;
+pub(crate) fn delete_board(app: AppHandle, board_id: String) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
 
-    tx.execute(
-        "DELETE FROM board_data WHERE board_id = ?1",
-        params![board_id],
-    )
-    .map_err(|error| error.to_string())?;
-    let deleted = tx
-        .execute("DELETE FROM boards WHERE id = ?1", params![board_id])
-        .map_err(|error| error.to_string())?;
-    if deleted == 0 {
-        return Err("Board not found".to_string());
-    }
-    thumbnails::delete_thumbnail(&app, thumbnails::BoardId::from(board_id.as_str()))?;
+    move_board_to_trash(&tx, &board_id, Utc::now().timestamp_millis())?;
+    remove_empty_folders(&tx)?;
+    reset_active_board_if_removed(&tx, std::slice::from_ref(&board_id))?;
 
-    tx.execute(
-        "DELETE FROM index_items WHERE item_type = 'board' AND item_id = ?1",
-        params![board_id],
-    )
-    .map_err(|error| error.to_string())?;
-    tx.execute(
-        "DELETE FROM folder_items WHERE board_id = ?1",
-        params![board_id],
-    )
-    .map_err(|error| error.to_string())?;
-    tx.execute(
-        "DELETE FROM folders WHERE id NOT IN (SELECT DISTINCT folder_id FROM folder_items)",
-        [],
-    )
-    .map_err(|error| error.to_string())?;
-    tx.execute(
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Drops folders that no longer contain anything, along with their index entries. Removing
+/// a folder can empty its parent, so this repeats until nothing else goes.
+pub(crate) fn remove_empty_folders(conn: &rusqlite::Connection) -> AppResult<()> {
+    loop {
+        conn.execute(
+            "DELETE FROM folder_items
+             WHERE item_type = 'folder' AND item_id NOT IN (SELECT id FROM folders)",
+            [],
+        )?;
+        let removed = conn.execute(
+            "DELETE FROM folders WHERE id NOT IN (SELECT DISTINCT folder_id FROM folder_items)",
+            [],
+        )?;
+        if removed == 0 {
+            break;
+        }
+    }
+    conn.execute(
         "DELETE FROM index_items WHERE item_type = 'folder' AND item_id NOT IN (SELECT id FROM folders)",
         [],
-    )
-    .map_err(|error| error.to_string())?;
+    )?;
+    Ok(())
+}
 
-    let active_id = get_setting(&tx, ACTIVE_BOARD_SETTING_KEY)?;
-    if active_id.as_deref() == Some(&board_id) {
-        let next_id = first_board_id_from_db(&tx)?;
-        set_setting(&tx, ACTIVE_BOARD_SETTING_KEY, next_id.as_deref())?;
+/// Moves the active board to the first remaining board if it was one of `removed_ids`.
+pub(crate) fn reset_active_board_if_removed(
+    conn: &rusqlite::Connection,
+    removed_ids: &[String],
+) -> AppResult<()> {
+    let active_id = get_setting(conn, ACTIVE_BOARD_SETTING_KEY)?;
+    if active_id.is_some_and(|active_id| removed_ids.contains(&active_id)) {
+        let next_id = first_board_id_from_db(conn)?;
+        set_setting(conn, ACTIVE_BOARD_SETTING_KEY, next_id.as_deref())?;
     }
-
-    tx.commit().map_err(|error| error.to_string())?;
-    load_resolved_boards_index(&app, &conn)
+    Ok(())
 }
 
 #[tauri::command]
-pub(crate) fn set_active_board(app: AppHandle, board_id: String) -> Result<(), String> {
-    let conn = open_db(&app)?;
+pub(crate) fn set_active_board(app: AppHandle, board_id: String) -> AppResult<()> {
+    let conn = lock_db(&app)?;
     if !board_id_exists(&conn, &board_id)? {
-        return Err("Board not found".to_string());
+        return Err(AppError::board_not_found(&board_id));
     }
     set_setting(&conn, ACTIVE_BOARD_SETTING_KEY, Some(&board_id))?;
+    conn.execute(
+        "UPDATE boards SET last_opened_at = ?1 WHERE id = ?2",
+        params![Utc::now().timestamp_millis(), board_id],
+    )?;
     Ok(())
 }
 
+#[tauri::command]
+pub(crate) fn set_board_pinned(
+    app: AppHandle,
+    board_id: String,
+    pinned: bool,
+) -> AppResult<BoardsIndex> {
+    let conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+    conn.execute(
+        "UPDATE boards SET pinned = ?1 WHERE id = ?2",
+        params![pinned, board_id],
+    )?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Live boards by when they were last opened, newest first. Boards never opened since
+/// opening was tracked are left out.
+#[tauri::command]
+pub(crate) fn get_recent_boards(app: AppHandle, limit: Option<u32>) -> AppResult<Vec<Board>> {
+    let conn = lock_db(&app)?;
+    let board_ids: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM boards
+             WHERE deleted_at IS NULL AND last_opened_at IS NOT NULL
+             ORDER BY last_opened_at DESC, id ASC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(
+            params![limit.unwrap_or(DEFAULT_RECENT_BOARDS_LIMIT)],
+            |row| row.get(0),
+        )?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut boards = load_boards_map(&conn)?;
+    board_ids
+        .iter()
+        .filter_map(|board_id| boards.remove(board_id))
+        .map(|board| resolve_board_thumbnail(&app, board))
+        .collect()
+}
+
 #[tauri::command]
 pub(crate) fn duplicate_board(
     app: AppHandle,
     board_id: String,
     new_name: String,
-) -> Result<BoardMutationResult, String> {
-    let mut conn = open_db(&app)?;
-    let _original = get_board_by_id(&conn, &board_id)?;
+) -> AppResult<BoardMutationResult> {
+    let mut conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+    let original = get_board_by_id(&conn, &board_id)?;
     let original_data = BoardDataPayload(
         load_board_data_value(&conn, &board_id)?.unwrap_or_else(default_board_data),
     );
@@ -164,12 +221,21 @@ pub(crate) fn duplicate_board(
         updated_at: now,
         collaboration_link: None,
         thumbnail: copied_thumbnail,
+        tags: original.tags,
+        pinned: false,
+        last_opened_at: None,
     };
 
-    let tx = conn.transaction().map_err(|error| error.to_string())?;
+    let tx = conn.transaction()?;
     insert_board_with_data(&tx, &new_board, &original_data)?;
+    assets::copy_board_assets(&tx, &board_id, &new_board.id)?;
+    tx.execute(
+        "INSERT INTO board_tags (board_id, tag_id)
+             SELECT ?2, tag_id FROM board_tags WHERE board_id = ?1",
+        params![board_id, new_board.id],
+    )?;
 
-    tx.commit().map_err(|error| error.to_string())?;
+    tx.commit()?;
     build_mutation_result(&app, &conn, &new_board.id)
 }
 
@@ -177,10 +243,11 @@ pub(crate) fn duplicate_board(
 pub(crate) fn set_boards_index(
     app: AppHandle,
     items: Vec<BoardListItem>,
-) -> Result<BoardsIndex, String> {
-    let mut conn = open_db(&app)?;
-    let tx = conn.transaction().map_err(|error| error.to_string())?;
+) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
 
+    ensure_lists_live_boards(&tx, &items)?;
     clear_index_tables(&tx)?;
 
     for (position, item) in items.iter().enumerate() {
@@ -192,6 +259,10 @@ pub(crate) fn set_boards_index(
     let index = BoardsIndex {
         items,
         active_board_id: normalized_active_board_id,
+        pinned: pinned_boards(&load_boards_map(&tx)?)
+            .into_iter()
+            .map(|board| resolve_board_thumbnail(&app, board))
+            .collect::<AppResult<_>>()?,
     };
 
     set_setting(
@@ -199,17 +270,64 @@ pub(crate) fn set_boards_index(
         ACTIVE_BOARD_SETTING_KEY,
         index.active_board_id.as_deref(),
     )?;
-    tx.commit().map_err(|error| error.to_string())?;
+    tx.commit()?;
     Ok(index)
 }
 
-fn clear_index_tables(tx: &rusqlite::Transaction<'_>) -> Result<(), String> {
-    tx.execute("DELETE FROM index_items", [])
-        .map_err(|error| error.to_string())?;
-    tx.execute("DELETE FROM folder_items", [])
-        .map_err(|error| error.to_string())?;
-    tx.execute("DELETE FROM folders", [])
-        .map_err(|error| error.to_string())?;
+/// Rejects a client tree that does not list exactly the live boards. A window holding a stale
+/// copy of the index would otherwise drop boards created elsewhere, or re-add trashed ones.
+fn ensure_lists_live_boards(conn: &rusqlite::Connection, items: &[BoardListItem]) -> AppResult<()> {
+    let mut listed = HashSet::new();
+    collect_board_ids(items, &mut listed);
+    let live: HashSet<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM boards WHERE deleted_at IS NULL")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    if listed != live {
+        return Err(AppError::conflict(
+            "The board list is out of date; reload it and try again",
+        ));
+    }
+    Ok(())
+}
+
+/// Fails unless every live board is reachable from the root of the index, so a change that
+/// would hide a board is rolled back instead of committed.
+pub(crate) fn ensure_boards_reachable(conn: &rusqlite::Connection) -> AppResult<()> {
+    let index = load_boards_index_from_db(conn)?;
+    let mut reachable = HashSet::new();
+    collect_board_ids(&index.items, &mut reachable);
+    let live: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM boards WHERE deleted_at IS NULL",
+        [],
+        |row| row.get(0),
+    )?;
+    if reachable.len() as i64 != live {
+        return Err(AppError::conflict(
+            "The change would leave boards outside the board list",
+        ));
+    }
+    Ok(())
+}
+
+fn collect_board_ids(items: &[BoardListItem], board_ids: &mut HashSet<String>) {
+    for item in items {
+        match item {
+            BoardListItem::Board(board) => {
+                board_ids.insert(board.id.clone());
+            }
+            BoardListItem::Folder(folder) => collect_board_ids(&folder.items, board_ids),
+            // A smart folder only shows boards placed elsewhere in the tree.
+            BoardListItem::SmartFolder(_) => {}
+        }
+    }
+}
+
+fn clear_index_tables(tx: &rusqlite::Transaction<'_>) -> AppResult<()> {
+    tx.execute("DELETE FROM index_items", [])?;
+    tx.execute("DELETE FROM folder_items", [])?;
+    tx.execute("DELETE FROM folders", [])?;
     Ok(())
 }
 
@@ -217,42 +335,48 @@ fn persist_index_item(
     tx: &rusqlite::Transaction<'_>,
     position: i64,
     item: &BoardListItem,
-) -> Result<(), String> {
-    match item {
-        BoardListItem::Board(board) => {
-            tx.execute(
-                "INSERT INTO index_items (position, item_type, item_id) VALUES (?1, 'board', ?2)",
-                params![position, &board.id],
-            )
-            .map_err(|error| error.to_string())?;
-            Ok(())
+) -> AppResult<()> {
+    let (item_type, item_id) = match item {
+        BoardListItem::Board(board) => ("board", &board.id),
+        BoardListItem::Folder(folder) => {
+            persist_folder_item(tx, None, folder)?;
+            ("folder", &folder.id)
         }
-        BoardListItem::Folder(folder) => persist_folder_item(tx, position, folder),
-    }
+        BoardListItem::SmartFolder(smart_folder) => ("smart_folder", &smart_folder.id),
+    };
+    tx.execute(
+        "INSERT INTO index_items (position, item_type, item_id) VALUES (?1, ?2, ?3)",
+        params![position, item_type, item_id],
+    )?;
+    Ok(())
 }
 
+/// Stores a folder and, recursively, everything inside it. The caller places the folder
+/// itself in its parent's items.
 fn persist_folder_item(
     tx: &rusqlite::Transaction<'_>,
-    position: i64,
-    folder: &crate::models::BoardFolder,
-) -> Result<(), String> {
+    parent_folder_id: Option<&str>,
+    folder: &BoardFolder,
+) -> AppResult<()> {
     tx.execute(
-        "INSERT OR REPLACE INTO folders (id, name) VALUES (?1, ?2)",
-        params![&folder.id, &folder.name],
-    )
-    .map_err(|error| error.to_string())?;
-    tx.execute(
-        "INSERT INTO index_items (position, item_type, item_id) VALUES (?1, 'folder', ?2)",
-        params![position, &folder.id],
-    )
-    .map_err(|error| error.to_string())?;
+        "INSERT INTO folders (id, name, parent_folder_id) VALUES (?1, ?2, ?3)",
+        params![&folder.id, &folder.name, parent_folder_id],
+    )?;
 
-    for (folder_position, board) in folder.items.iter().enumerate() {
+    for (folder_position, item) in folder.items.iter().enumerate() {
+        let (item_type, item_id) = match item {
+            BoardListItem::Board(board) => ("board", &board.id),
+            BoardListItem::Folder(child) => {
+                persist_folder_item(tx, Some(&folder.id), child)?;
+                ("folder", &child.id)
+            }
+            BoardListItem::SmartFolder(smart_folder) => ("smart_folder", &smart_folder.id),
+        };
         tx.execute(
-            "INSERT INTO folder_items (folder_id, board_id, position) VALUES (?1, ?2, ?3)",
-            params![&folder.id, &board.id, folder_position as i64],
-        )
-        .map_err(|error| error.to_string())?;
+            "INSERT INTO folder_items (folder_id, position, item_type, item_id)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![&folder.id, folder_position as i64, item_type, item_id],
+        )?;
     }
 
     Ok(())
@@ -269,11 +393,11 @@ fn resolve_active_board_id(
 }
 
 fn insert_board_with_data(
-    tx: &rusqlite::Transaction<'_>,
+    conn: &rusqlite::Connection,
     board: &Board,
     data: &BoardDataPayload,
-) -> Result<(), String> {
-    tx.execute(
+) -> AppResult<()> {
+    conn.execute(
         "INSERT INTO boards (id, name, created_at, updated_at, collaboration_link, thumbnail)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         params![
@@ -284,38 +408,190 @@ fn insert_board_with_data(
             &board.collaboration_link,
             &board.thumbnail
         ],
-    )
-    .map_err(|error| error.to_string())?;
+    )?;
 
-    tx.execute(
-        "INSERT INTO board_data (board_id, data) VALUES (?1, ?2)",
-        params![&board.id, &data.0],
-    )
-    .map_err(|error| error.to_string())?;
+    write_board_data(conn, &board.id, &data.0)?;
+    search_index::index_board_name(conn, &board.id, &board.name)?;
+    search_index::index_board_scene(conn, &board.id, &data.0)?;
 
-    let position = next_index_position(tx)?;
-    tx.execute(
+    let position = next_index_position(conn)?;
+    conn.execute(
         "INSERT INTO index_items (position, item_type, item_id) VALUES (?1, 'board', ?2)",
         params![position, &board.id],
-    )
-    .map_err(|error| error.to_string())?;
+    )?;
 
     Ok(())
 }
 
-fn next_index_position(tx: &rusqlite::Transaction<'_>) -> Result<i64, String> {
-    tx.query_row(
+fn next_index_position(conn: &rusqlite::Connection) -> AppResult<i64> {
+    conn.query_row(
         "SELECT COALESCE(MAX(position), -1) + 1 FROM index_items",
         [],
         |row| row.get(0),
     )
-    .map_err(|error| error.to_string())
+    .map_err(AppError::from)
+}
+
+/// Inserts a root index entry before the entry currently at index `position` (or at the end
+/// when there is none), shifting later entries down. Stored positions may have gaps, so
+/// `position` counts entries rather than matching the `position` column.
+pub(crate) fn insert_index_item_at(
+    conn: &rusqlite::Connection,
+    position: i64,
+    item_type: &str,
+    item_id: &str,
+) -> AppResult<()> {
+    let position: i64 = match conn
+        .query_row(
+            "SELECT position FROM index_items ORDER BY position LIMIT 1 OFFSET ?1",
+            params![position.max(0)],
+            |row| row.get(0),
+        )
+        .optional()?
+    {
+        Some(position) => position,
+        None => next_index_position(conn)?,
+    };
+    // Shift through negative positions so the primary key never collides mid-update.
+    conn.execute(
+        "UPDATE index_items SET position = -position - 1 WHERE position >= ?1",
+        params![position],
+    )?;
+    conn.execute(
+        "UPDATE index_items SET position = -position WHERE position < 0",
+        [],
+    )?;
+    conn.execute(
+        "INSERT INTO index_items (position, item_type, item_id) VALUES (?1, ?2, ?3)",
+        params![position, item_type, item_id],
+    )?;
+    Ok(())
+}
+
+/// Inserts a board or subfolder into a folder before the item currently at index `position`
+/// (or at the end), shifting later items down. Like `insert_index_item_at`, `position` counts
+/// items.
+pub(crate) fn insert_folder_item_at(
+    conn: &rusqlite::Connection,
+    folder_id: &str,
+    position: i64,
+    item_type: &str,
+    item_id: &str,
+) -> AppResult<()> {
+    let position: i64 = match conn
+        .query_row(
+            "SELECT position FROM folder_items WHERE folder_id = ?1
+             ORDER BY position LIMIT 1 OFFSET ?2",
+            params![folder_id, position.max(0)],
+            |row| row.get(0),
+        )
+        .optional()?
+    {
+        Some(position) => position,
+        None => conn.query_row(
+            "SELECT COALESCE(MAX(position), -1) + 1 FROM folder_items WHERE folder_id = ?1",
+            params![folder_id],
+            |row| row.get(0),
+        )?,
+    };
+    conn.execute(
+        "UPDATE folder_items SET position = -position - 1 WHERE folder_id = ?1 AND position >= ?2",
+        params![folder_id, position],
+    )?;
+    conn.execute(
+        "UPDATE folder_items SET position = -position WHERE folder_id = ?1 AND position < 0",
+        params![folder_id],
+    )?;
+    conn.execute(
+        "INSERT INTO folder_items (folder_id, position, item_type, item_id)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![folder_id, position, item_type, item_id],
+    )?;
+    Ok(())
+}
+
+/// Inserts an entry into `folder_id`, or into the root when it is `None`.
+pub(crate) fn insert_item_at(
+    conn: &rusqlite::Connection,
+    folder_id: Option<&str>,
+    position: i64,
+    item_type: &str,
+    item_id: &str,
+) -> AppResult<()> {
+    match folder_id {
+        Some(folder_id) => insert_folder_item_at(conn, folder_id, position, item_type, item_id),
+        None => insert_index_item_at(conn, position, item_type, item_id),
+    }
+}
+
+/// Where an index entry is placed: the folder holding it (`None` for the root) and its index
+/// among that folder's entries.
+pub(crate) struct ItemLocation {
+    pub(crate) folder_id: Option<String>,
+    pub(crate) index: i64,
+}
+
+pub(crate) fn locate_item(
+    conn: &rusqlite::Connection,
+    item_type: &str,
+    item_id: &str,
+) -> AppResult<Option<ItemLocation>> {
+    let in_folder = conn
+        .query_row(
+            "SELECT fi.folder_id, (
+                 SELECT COUNT(*) FROM folder_items s
+                 WHERE s.folder_id = fi.folder_id AND s.position < fi.position
+             )
+             FROM folder_items fi WHERE fi.item_type = ?1 AND fi.item_id = ?2",
+            params![item_type, item_id],
+            |row| {
+                Ok(ItemLocation {
+                    folder_id: Some(row.get(0)?),
+                    index: row.get(1)?,
+                })
+            },
+        )
+        .optional()?;
+    if in_folder.is_some() {
+        return Ok(in_folder);
+    }
+
+    conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM index_items s WHERE s.position < i.position)
+         FROM index_items i WHERE i.item_type = ?1 AND i.item_id = ?2",
+        params![item_type, item_id],
+        |row| {
+            Ok(ItemLocation {
+                folder_id: None,
+                index: row.get(0)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+/// Removes an entry from wherever it is placed, the root or a folder.
+pub(crate) fn detach_item(
+    conn: &rusqlite::Connection,
+    item_type: &str,
+    item_id: &str,
+) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM index_items WHERE item_type = ?1 AND item_id = ?2",
+        params![item_type, item_id],
+    )?;
+    conn.execute(
+        "DELETE FROM folder_items WHERE item_type = ?1 AND item_id = ?2",
+        params![item_type, item_id],
+    )?;
+    Ok(())
 }
 
 pub(crate) fn load_resolved_boards_index(
     app: &AppHandle,
     conn: &rusqlite::Connection,
-) -> Result<BoardsIndex, String> {
+) -> AppResult<BoardsIndex> {
     let index = load_boards_index_from_db(conn)?;
     let index = normalize_active_board_id(conn, index)?;
     resolve_index_thumbnails(app, index)
@@ -325,26 +601,17 @@ fn build_mutation_result(
     app: &AppHandle,
     conn: &rusqlite::Connection,
     board_id: &str,
-) -> Result<BoardMutationResult, String> {
+) -> AppResult<BoardMutationResult> {
     let index = load_resolved_boards_index(app, conn)?;
-    let board = find_board_in_index(&index.items, board_id)
+    let board = find_board(&index.items, board_id)
         .cloned()
-        .ok_or_else(|| "Created board missing from index".to_string())?;
+        .ok_or_else(|| AppError::board_not_found(board_id))?;
     Ok(BoardMutationResult { board, index })
 }
 
-fn find_board_in_index<'a>(items: &'a [BoardListItem], board_id: &str) -> Option<&'a Board> {
-    items.iter().find_map(|item| match item {
-        BoardListItem::Board(board) if board.id == board_id => Some(board),
-        BoardListItem::Folder(folder) => folder.items.iter().find(|board| board.id == board_id),
-        BoardListItem::Board(_) => None,
-    })
-}
-
-/// Converts a board's `thumbnail` field from a relative file path (as stored in the DB)
-/// into a data URL suitable for the frontend.
-fn resolve_board_thumbnail(app: &AppHandle, mut board: Board) -> Result<Board, String> {
-    board.thumbnail = thumbnails::load_thumbnail_data_url(
+/// Replaces the stored thumbnail path with the `thumb://` URL the frontend loads it from.
+pub(crate) fn resolve_board_thumbnail(app: &AppHandle, mut board: Board) -> AppResult<Board> {
+    board.thumbnail = thumbnails::thumbnail_url(
         app,
         board
             .thumbnail
@@ -354,34 +621,44 @@ fn resolve_board_thumbnail(app: &AppHandle, mut board: Board) -> Result<Board, S
     Ok(board)
 }
 
-fn resolve_index_thumbnails(
-    app: &AppHandle,
-    mut index: BoardsIndex,
-) -> Result<BoardsIndex, String> {
+fn resolve_index_thumbnails(app: &AppHandle, mut index: BoardsIndex) -> AppResult<BoardsIndex> {
     let mut resolved_items = Vec::with_capacity(index.items.len());
     for item in index.items.drain(..) {
         resolved_items.push(resolve_item_thumbnails(app, item)?);
     }
     index.items = resolved_items;
+    index.pinned = index
+        .pinned
+        .into_iter()
+        .map(|board| resolve_board_thumbnail(app, board))
+        .collect::<AppResult<_>>()?;
     Ok(index)
 }
 
-fn resolve_item_thumbnails(app: &AppHandle, item: BoardListItem) -> Result<BoardListItem, String> {
+fn resolve_item_thumbnails(app: &AppHandle, item: BoardListItem) -> AppResult<BoardListItem> {
     match item {
         BoardListItem::Board(board) => {
             Ok(BoardListItem::Board(resolve_board_thumbnail(app, board)?))
         }
         BoardListItem::Folder(folder) => {
             let BoardFolder { id, name, items } = folder;
-            let mut resolved_boards = Vec::with_capacity(items.len());
-            for board in items {
-                resolved_boards.push(resolve_board_thumbnail(app, board)?);
+            let mut resolved_items = Vec::with_capacity(items.len());
+            for item in items {
+                resolved_items.push(resolve_item_thumbnails(app, item)?);
             }
             Ok(BoardListItem::Folder(BoardFolder {
                 id,
                 name,
-                items: resolved_boards,
+                items: resolved_items,
             }))
         }
+        BoardListItem::SmartFolder(mut smart_folder) => {
+            smart_folder.boards = smart_folder
+                .boards
+                .into_iter()
+                .map(|board| resolve_board_thumbnail(app, board))
+                .collect::<AppResult<_>>()?;
+            Ok(BoardListItem::SmartFolder(smart_folder))
+        }
     }
 }
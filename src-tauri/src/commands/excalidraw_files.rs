@@ -0,0 +1,93 @@
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::assets;
+use crate::commands::board_content::store_board_data;
+use crate::commands::board_transfer::restore_active_board;
+use crate::commands::boards::{insert_new_board, load_resolved_boards_index};
+use crate::db::{board_id_exists, default_board_data, get_setting, load_board_data_value, lock_db};
+use crate::error::{AppError, AppResult};
+use crate::models::BoardsImportResult;
+use crate::scene;
+
+const ACTIVE_BOARD_SETTING_KEY: &str = "active_board_id";
+const DEFAULT_IMPORTED_NAME: &str = "Imported board";
+
+/// Writes a single board as a standard `.excalidraw` file that excalidraw.com can open.
+#[tauri::command]
+pub(crate) fn export_board_excalidraw(
+    app: AppHandle,
+    board_id: String,
+    file_path: String,
+) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+    let data_str = match load_board_data_value(&conn, &board_id)? {
+        Some(data) => assets::hydrate_assets(&conn, data)?,
+        None => default_board_data(),
+    };
+    let data: JsonValue = serde_json::from_str(&data_str)?;
+
+    let payload = serde_json::to_string_pretty(&scene::to_excalidraw_file(&data))?;
+    fs::write(file_path, payload)?;
+    Ok(())
+}
+
+/// Imports each `.excalidraw` file as a new board named after the file, in one transaction.
+/// Files that cannot be read or are not Excalidraw scenes are counted as skipped.
+#[tauri::command]
+pub(crate) fn import_excalidraw_files(
+    app: AppHandle,
+    file_paths: Vec<String>,
+) -> AppResult<BoardsImportResult> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let active_before = get_setting(&tx, ACTIVE_BOARD_SETTING_KEY)?;
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    for file_path in &file_paths {
+        let Ok(data) = read_excalidraw_file(Path::new(file_path)) else {
+            skipped += 1;
+            continue;
+        };
+
+        let board = insert_new_board(
+            &tx,
+            Uuid::new_v4().to_string(),
+            board_name_from_path(Path::new(file_path)),
+        )?;
+        store_board_data(&tx, &board.id, &data.to_string(), None)?;
+        imported += 1;
+    }
+
+    restore_active_board(&tx, active_before)?;
+    tx.commit()?;
+
+    let index = load_resolved_boards_index(&app, &conn)?;
+    Ok(BoardsImportResult {
+        imported,
+        skipped,
+        index,
+    })
+}
+
+fn read_excalidraw_file(path: &Path) -> AppResult<JsonValue> {
+    let payload = fs::read_to_string(path)?;
+    let file: JsonValue = serde_json::from_str(&payload)?;
+    scene::from_excalidraw_file(&file)
+}
+
+fn board_name_from_path(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(str::trim)
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(DEFAULT_IMPORTED_NAME)
+        .to_string()
+}
@@ -0,0 +1,402 @@
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::commands::boards::{
+    detach_item, ensure_boards_reachable, insert_item_at, load_resolved_boards_index, locate_item,
+    remove_empty_folders, reset_active_board_if_removed,
+};
+use crate::commands::trash::move_boards_to_trash;
+use crate::db::{board_id_exists, lock_db};
+use crate::error::{AppError, AppResult};
+use crate::models::{BoardsIndex, FolderDeleteMode};
+
+// Positions passed to these commands count entries in the destination (the root when the
+// folder id is `None`) after the moved item has left its old place. Anything past the end
+// appends.
+
+/// Creates a folder holding `board_ids`, in that order, at `position` in `parent_folder_id`.
+/// Empty folders are not kept, so at least one board is required.
+#[tauri::command]
+pub(crate) fn create_folder(
+    app: AppHandle,
+    name: String,
+    parent_folder_id: Option<String>,
+    position: Option<i64>,
+    board_ids: Vec<String>,
+) -> AppResult<BoardsIndex> {
+    let name = validate_folder_name(&name)?;
+    if board_ids.is_empty() {
+        return Err(AppError::validation("A folder needs at least one board"));
+    }
+
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    if let Some(parent_id) = &parent_folder_id {
+        ensure_folder_exists(&tx, parent_id)?;
+    }
+
+    let mut seen = HashSet::new();
+    for board_id in &board_ids {
+        if !seen.insert(board_id) {
+            return Err(AppError::validation(
+                "A board can only be added to a folder once",
+            ));
+        }
+        if !board_id_exists(&tx, board_id)? {
+            return Err(AppError::board_not_found(board_id));
+        }
+        detach_item(&tx, "board", board_id)?;
+    }
+
+    let folder_id = Uuid::new_v4().to_string();
+    tx.execute(
+        "INSERT INTO folders (id, name, parent_folder_id) VALUES (?1, ?2, ?3)",
+        params![folder_id, name, parent_folder_id],
+    )?;
+    insert_item_at(
+        &tx,
+        parent_folder_id.as_deref(),
+        position.unwrap_or(i64::MAX),
+        "folder",
+        &folder_id,
+    )?;
+    for (board_position, board_id) in board_ids.iter().enumerate() {
+        insert_item_at(
+            &tx,
+            Some(&folder_id),
+            board_position as i64,
+            "board",
+            board_id,
+        )?;
+    }
+
+    finish_index_change(&tx)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+#[tauri::command]
+pub(crate) fn rename_folder(
+    app: AppHandle,
+    folder_id: String,
+    name: String,
+) -> AppResult<BoardsIndex> {
+    let name = validate_folder_name(&name)?;
+    let conn = lock_db(&app)?;
+    let updated = conn.execute(
+        "UPDATE folders SET name = ?1 WHERE id = ?2",
+        params![name, folder_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::not_found("Folder not found"));
+    }
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Removes a folder. By default every board in it and its subfolders goes to the trash, and
+/// restoring any of them recreates its folders under their original ids and names; smart
+/// folders inside are deleted. With `KeepBoards` the folder's contents take its place in the
+/// parent instead.
+#[tauri::command]
+pub(crate) fn delete_folder(
+    app: AppHandle,
+    folder_id: String,
+    mode: Option<FolderDeleteMode>,
+) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    ensure_folder_exists(&tx, &folder_id)?;
+
+    match mode.unwrap_or_default() {
+        FolderDeleteMode::TrashBoards => trash_folder(&tx, &folder_id)?,
+        FolderDeleteMode::KeepBoards => ungroup_folder(&tx, &folder_id)?,
+    }
+
+    finish_index_change(&tx)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+#[tauri::command]
+pub(crate) fn move_board(
+    app: AppHandle,
+    board_id: String,
+    folder_id: Option<String>,
+    position: i64,
+) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    if !board_id_exists(&tx, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+    if let Some(folder_id) = &folder_id {
+        ensure_folder_exists(&tx, folder_id)?;
+    }
+
+    detach_item(&tx, "board", &board_id)?;
+    insert_item_at(&tx, folder_id.as_deref(), position, "board", &board_id)?;
+
+    finish_index_change(&tx)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Moves a folder with everything in it. A folder cannot be moved into itself or one of its
+/// own subfolders.
+#[tauri::command]
+pub(crate) fn move_folder(
+    app: AppHandle,
+    folder_id: String,
+    parent_folder_id: Option<String>,
+    position: i64,
+) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    ensure_folder_exists(&tx, &folder_id)?;
+    if let Some(parent_id) = &parent_folder_id {
+        ensure_folder_exists(&tx, parent_id)?;
+        if folder_subtree_ids(&tx, &folder_id)?.contains(parent_id) {
+            return Err(AppError::validation(
+                "A folder cannot be moved into itself or one of its subfolders",
+            ));
+        }
+    }
+
+    detach_item(&tx, "folder", &folder_id)?;
+    insert_item_at(
+        &tx,
+        parent_folder_id.as_deref(),
+        position,
+        "folder",
+        &folder_id,
+    )?;
+    tx.execute(
+        "UPDATE folders SET parent_folder_id = ?1 WHERE id = ?2",
+        params![parent_folder_id, folder_id],
+    )?;
+
+    finish_index_change(&tx)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+fn validate_folder_name(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::validation("Folder name cannot be empty"));
+    }
+    Ok(name)
+}
+
+pub(crate) fn ensure_folder_exists(conn: &Connection, folder_id: &str) -> AppResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM folders WHERE id = ?1)",
+        params![folder_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::not_found("Folder not found"));
+    }
+    Ok(())
+}
+
+/// Drops the folders a change emptied and refuses to go on if a board was lost on the way.
+pub(crate) fn finish_index_change(conn: &Connection) -> AppResult<()> {
+    remove_empty_folders(conn)?;
+    ensure_boards_reachable(conn)
+}
+
+/// Sends every board in a folder and its subfolders to the trash and deletes the folder, with
+/// the subfolders and smart folders in it.
+fn trash_folder(conn: &Connection, folder_id: &str) -> AppResult<()> {
+    let board_ids = folder_board_ids(conn, folder_id)?;
+    move_boards_to_trash(conn, &board_ids, Utc::now().timestamp_millis())?;
+    delete_nested_smart_folders(conn, folder_id)?;
+    conn.execute("DELETE FROM folders WHERE id = ?1", params![folder_id])?;
+    reset_active_board_if_removed(conn, &board_ids)
+}
+
+/// Replaces a folder with its contents, in order, where it sits in its parent.
+fn ungroup_folder(conn: &Connection, folder_id: &str) -> AppResult<()> {
+    let location = locate_item(conn, "folder", folder_id)?;
+    let (parent_id, index) = match location {
+        Some(location) => (location.folder_id, location.index),
+        None => (None, i64::MAX),
+    };
+    let children: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT item_type, item_id FROM folder_items WHERE folder_id = ?1 ORDER BY position",
+        )?;
+        let rows = stmt.query_map(params![folder_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    detach_item(conn, "folder", folder_id)?;
+    conn.execute(
+        "DELETE FROM folder_items WHERE folder_id = ?1",
+        params![folder_id],
+    )?;
+    // Re-parent subfolders before the delete, which would otherwise cascade to them.
+    conn.execute(
+        "UPDATE folders SET parent_folder_id = ?1 WHERE parent_folder_id = ?2",
+        params![parent_id, folder_id],
+    )?;
+    for (offset, (item_type, item_id)) in children.iter().enumerate() {
+        insert_item_at(
+            conn,
+            parent_id.as_deref(),
+            index.saturating_add(offset as i64),
+            item_type,
+            item_id,
+        )?;
+    }
+    conn.execute("DELETE FROM folders WHERE id = ?1", params![folder_id])?;
+    Ok(())
+}
+
+/// A folder and every folder nested in it.
+fn folder_subtree_ids(conn: &Connection, folder_id: &str) -> AppResult<HashSet<String>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id) AS (
+             SELECT ?1
+             UNION
+             SELECT fi.item_id FROM folder_items fi JOIN subtree s ON fi.folder_id = s.id
+             WHERE fi.item_type = 'folder'
+         )
+         SELECT id FROM subtree",
+    )?;
+    let rows = stmt.query_map(params![folder_id], |row| row.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Every board in a folder and its subfolders, shallowest first and in tree order within each
+/// depth. `sort_key` is the folder's path of zero-padded positions, so sibling folders sort by
+/// where they sit rather than by id. The depth cap only matters for a corrupt tree with a
+/// cycle in it.
+fn folder_board_ids(conn: &Connection, folder_id: &str) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(id, depth, sort_key) AS (
+             SELECT ?1, 0, ''
+             UNION
+             SELECT fi.item_id, s.depth + 1, s.sort_key || printf('%020d/', fi.position)
+             FROM folder_items fi JOIN subtree s ON fi.folder_id = s.id
+             WHERE fi.item_type = 'folder' AND s.depth < (SELECT COUNT(*) FROM folders)
+         )
+         SELECT fi.item_id FROM folder_items fi JOIN subtree s ON fi.folder_id = s.id
+         WHERE fi.item_type = 'board'
+         ORDER BY s.depth, s.sort_key, fi.position",
+    )?;
+    let rows = stmt.query_map(params![folder_id], |row| row.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Deletes the smart folders placed in a folder or its subfolders. Their placements go with
+/// the folders.
+fn delete_nested_smart_folders(conn: &Connection, folder_id: &str) -> AppResult<()> {
+    conn.execute(
+        "WITH RECURSIVE subtree(id) AS (
+             SELECT ?1
+             UNION
+             SELECT fi.item_id FROM folder_items fi JOIN subtree s ON fi.folder_id = s.id
+             WHERE fi.item_type = 'folder'
+         )
+         DELETE FROM smart_folders WHERE id IN (
+             SELECT fi.item_id FROM folder_items fi JOIN subtree s ON fi.folder_id = s.id
+             WHERE fi.item_type = 'smart_folder'
+         )",
+        params![folder_id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::boards::insert_new_board;
+    use crate::commands::trash::restore_trashed_folder;
+    use crate::db::open_test_db;
+
+    fn folder_contents(conn: &Connection, folder_id: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT item_id FROM folder_items WHERE folder_id = ?1 ORDER BY position")
+            .unwrap();
+        let rows = stmt
+            .query_map(params![folder_id], |row| row.get(0))
+            .unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    fn add_folder(conn: &Connection, folder_id: &str, parent_id: Option<&str>, items: &[&str]) {
+        conn.execute(
+            "INSERT INTO folders (id, name, parent_folder_id) VALUES (?1, ?1, ?2)",
+            params![folder_id, parent_id],
+        )
+        .unwrap();
+        insert_item_at(conn, parent_id, i64::MAX, "folder", folder_id).unwrap();
+        for (position, item_id) in items.iter().enumerate() {
+            let item_type = if item_id.starts_with("folder") {
+                "folder"
+            } else {
+                "board"
+            };
+            detach_item(conn, item_type, item_id).unwrap();
+            conn.execute(
+                "UPDATE folders SET parent_folder_id = ?1 WHERE id = ?2",
+                params![folder_id, item_id],
+            )
+            .unwrap();
+            insert_item_at(conn, Some(folder_id), position as i64, item_type, item_id).unwrap();
+        }
+    }
+
+    #[test]
+    fn restoring_a_trashed_folder_keeps_board_order() {
+        let conn = open_test_db();
+        for board_id in ["a", "b", "c"] {
+            insert_new_board(&conn, board_id.to_string(), board_id.to_string()).unwrap();
+        }
+        add_folder(&conn, "folder", None, &["a", "b", "c"]);
+
+        trash_folder(&conn, "folder").unwrap();
+        assert!(folder_board_ids(&conn, "folder").unwrap().is_empty());
+        restore_trashed_folder(&conn, "folder").unwrap();
+
+        assert_eq!(folder_contents(&conn, "folder"), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn restoring_a_trashed_folder_keeps_subfolders_in_place() {
+        let conn = open_test_db();
+        for board_id in ["a", "b", "c", "d"] {
+            insert_new_board(&conn, board_id.to_string(), board_id.to_string()).unwrap();
+        }
+        add_folder(&conn, "folder-inner", None, &["c", "d"]);
+        add_folder(&conn, "folder", None, &["a", "folder-inner", "b"]);
+
+        assert_eq!(
+            folder_board_ids(&conn, "folder").unwrap(),
+            ["a", "b", "c", "d"]
+        );
+        trash_folder(&conn, "folder").unwrap();
+        restore_trashed_folder(&conn, "folder").unwrap();
+
+        assert_eq!(folder_contents(&conn, "folder"), ["a", "folder-inner", "b"]);
+        assert_eq!(folder_contents(&conn, "folder-inner"), ["c", "d"]);
+    }
+
+    #[test]
+    fn folder_board_ids_follow_subfolder_positions() {
+        let conn = open_test_db();
+        for board_id in ["a", "b", "c"] {
+            insert_new_board(&conn, board_id.to_string(), board_id.to_string()).unwrap();
+        }
+        add_folder(&conn, "folder-z", None, &["b"]);
+        add_folder(&conn, "folder-a", None, &["c"]);
+        add_folder(&conn, "folder", None, &["folder-z", "folder-a", "a"]);
+
+        assert_eq!(folder_board_ids(&conn, "folder").unwrap(), ["a", "b", "c"]);
+    }
+}
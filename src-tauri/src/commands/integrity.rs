@@ -0,0 +1,350 @@
+use rusqlite::{params, Connection};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::AppHandle;
+
+use crate::commands::boards::{load_resolved_boards_index, remove_empty_folders};
+use crate::db::lock_db;
+use crate::error::{AppError, AppResult};
+use crate::models::{IntegrityRepairResult, IntegrityReport};
+use crate::thumbnails;
+
+/// An index entry as `repair_integrity` writes it back.
+enum LayoutItem {
+    Board(String),
+    Folder { id: String, items: Vec<LayoutItem> },
+    SmartFolder(String),
+}
+
+/// The report for the current database together with the index it should have: every live
+/// board placed exactly once, in the existing order where there is one.
+struct IndexAnalysis {
+    report: IntegrityReport,
+    layout: Vec<LayoutItem>,
+}
+
+#[tauri::command]
+pub(crate) fn check_integrity(app: AppHandle) -> AppResult<IntegrityReport> {
+    let conn = lock_db(&app)?;
+    Ok(analyze_index(&app, &conn)?.report)
+}
+
+/// Rewrites the board index from `analyze_index`: dangling entries and empty folders are
+/// dropped, orphan folders, smart folders and boards are appended to the root, folder parents
+/// are reset to match the tree, and positions are renumbered. Thumbnail paths whose file is
+/// missing are cleared.
+#[tauri::command]
+pub(crate) fn repair_integrity(app: AppHandle) -> AppResult<IntegrityRepairResult> {
+    let mut conn = lock_db(&app)?;
+    let analysis = analyze_index(&app, &conn)?;
+    if analysis.report.integrity_check != ["ok"] {
+        return Err(AppError::database(
+            "The database file is damaged and cannot be repaired in place; restore a backup",
+        ));
+    }
+
+    let tx = conn.transaction()?;
+    write_layout(&tx, &analysis.layout)?;
+    for board_id in &analysis.report.missing_thumbnail_board_ids {
+        tx.execute(
+            "UPDATE boards SET thumbnail = NULL WHERE id = ?1",
+            params![board_id],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(IntegrityRepairResult {
+        repaired: analysis.report,
+        index: load_resolved_boards_index(&app, &conn)?,
+    })
+}
+
+fn analyze_index(app: &AppHandle, conn: &Connection) -> AppResult<IndexAnalysis> {
+    let mut report = IntegrityReport {
+        integrity_check: query_strings(conn, "PRAGMA integrity_check")?,
+        ..IntegrityReport::default()
+    };
+
+    let live_board_ids = query_strings(
+        conn,
+        "SELECT id FROM boards WHERE deleted_at IS NULL ORDER BY created_at ASC, id ASC",
+    )?;
+    let live_boards: HashSet<&str> = live_board_ids.iter().map(String::as_str).collect();
+    let folder_ids = query_strings(conn, "SELECT id FROM folders ORDER BY name ASC, id ASC")?;
+    let smart_folder_ids = query_strings(
+        conn,
+        "SELECT id FROM smart_folders ORDER BY name ASC, id ASC",
+    )?;
+    let smart_folders: HashSet<&str> = smart_folder_ids.iter().map(String::as_str).collect();
+    let folder_parents: HashMap<String, Option<String>> = {
+        let mut stmt = conn.prepare("SELECT id, parent_folder_id FROM folders")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let root_rows: Vec<(i64, String, String)> = {
+        let mut stmt =
+            conn.prepare("SELECT position, item_type, item_id FROM index_items ORDER BY position")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut folder_rows: BTreeMap<String, Vec<(i64, String, String)>> = BTreeMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT folder_id, position, item_type, item_id FROM folder_items
+             ORDER BY folder_id, position",
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            folder_rows.entry(row.get(0)?).or_default().push((
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+            ));
+        }
+    }
+
+    report.root_position_gaps = has_position_gaps(root_rows.iter().map(|(position, ..)| *position));
+    for (folder_id, rows) in &folder_rows {
+        if has_position_gaps(rows.iter().map(|(position, ..)| *position)) {
+            report.folder_position_gap_ids.push(folder_id.clone());
+        }
+        if !folder_parents.contains_key(folder_id) {
+            report
+                .dangling_folder_items
+                .extend(rows.iter().map(|(.., item_id)| item_id.clone()));
+        }
+    }
+
+    let mut walk = TreeWalk {
+        live_boards: &live_boards,
+        smart_folders: &smart_folders,
+        folder_parents: &folder_parents,
+        folder_rows: &folder_rows,
+        placed_boards: HashSet::new(),
+        placed_folders: HashSet::new(),
+        placed_smart_folders: HashSet::new(),
+        report: &mut report,
+    };
+    let mut layout = Vec::new();
+    for (_, item_type, item_id) in &root_rows {
+        match item_type.as_str() {
+            "board" if live_boards.contains(item_id.as_str()) => {
+                if walk.placed_boards.insert(item_id.clone()) {
+                    layout.push(LayoutItem::Board(item_id.clone()));
+                } else {
+                    walk.report.duplicate_board_ids.push(item_id.clone());
+                }
+            }
+            "folder"
+                if folder_parents.contains_key(item_id)
+                    && walk.placed_folders.insert(item_id.clone()) =>
+            {
+                layout.extend(walk.place_folder(item_id, None));
+            }
+            "smart_folder"
+                if smart_folders.contains(item_id.as_str())
+                    && walk.placed_smart_folders.insert(item_id.clone()) =>
+            {
+                layout.push(LayoutItem::SmartFolder(item_id.clone()));
+            }
+            _ => walk.report.dangling_index_items.push(item_id.clone()),
+        }
+    }
+
+    // A folder missing from the tree is re-attached to the root together with its unplaced
+    // ancestors, so a lost subtree keeps its shape.
+    for folder_id in &folder_ids {
+        if walk.placed_folders.contains(folder_id) {
+            continue;
+        }
+        let mut top = folder_id;
+        let mut visited = HashSet::from([folder_id]);
+        while let Some(Some(parent_id)) = folder_parents.get(top) {
+            if !folder_parents.contains_key(parent_id)
+                || walk.placed_folders.contains(parent_id)
+                || !visited.insert(parent_id)
+            {
+                break;
+            }
+            top = parent_id;
+        }
+
+        walk.placed_folders.insert(top.clone());
+        if let Some(folder) = walk.place_folder(top, None) {
+            walk.report.orphan_folder_ids.push(top.clone());
+            layout.push(folder);
+        }
+    }
+
+    for smart_folder_id in &smart_folder_ids {
+        if !walk.placed_smart_folders.contains(smart_folder_id) {
+            walk.report
+                .orphan_smart_folder_ids
+                .push(smart_folder_id.clone());
+            layout.push(LayoutItem::SmartFolder(smart_folder_id.clone()));
+        }
+    }
+
+    for board_id in &live_board_ids {
+        if !walk.placed_boards.contains(board_id) {
+            walk.report.orphan_board_ids.push(board_id.clone());
+            layout.push(LayoutItem::Board(board_id.clone()));
+        }
+    }
+
+    report.missing_thumbnail_board_ids = missing_thumbnail_board_ids(app, conn)?;
+    report.healthy = report.integrity_check == ["ok"]
+        && report.orphan_board_ids.is_empty()
+        && report.orphan_folder_ids.is_empty()
+        && report.orphan_smart_folder_ids.is_empty()
+        && report.empty_folder_ids.is_empty()
+        && report.dangling_index_items.is_empty()
+        && report.dangling_folder_items.is_empty()
+        && report.duplicate_board_ids.is_empty()
+        && !report.root_position_gaps
+        && report.folder_position_gap_ids.is_empty()
+        && report.misparented_folder_ids.is_empty()
+        && report.missing_thumbnail_board_ids.is_empty();
+
+    Ok(IndexAnalysis { report, layout })
+}
+
+/// State for laying out the folder tree: every board, folder and smart folder is placed at
+/// most once.
+struct TreeWalk<'a> {
+    live_boards: &'a HashSet<&'a str>,
+    smart_folders: &'a HashSet<&'a str>,
+    folder_parents: &'a HashMap<String, Option<String>>,
+    folder_rows: &'a BTreeMap<String, Vec<(i64, String, String)>>,
+    placed_boards: HashSet<String>,
+    placed_folders: HashSet<String>,
+    placed_smart_folders: HashSet<String>,
+    report: &'a mut IntegrityReport,
+}
+
+impl TreeWalk<'_> {
+    /// Lays out a folder the caller has just marked as placed, with the live boards and
+    /// subfolders not already placed elsewhere, in folder order. Returns `None` for a folder
+    /// that ends up empty.
+    fn place_folder(&mut self, folder_id: &str, parent_id: Option<&str>) -> Option<LayoutItem> {
+        if self
+            .folder_parents
+            .get(folder_id)
+            .and_then(Option::as_deref)
+            != parent_id
+        {
+            self.report
+                .misparented_folder_ids
+                .push(folder_id.to_string());
+        }
+
+        let mut items = Vec::new();
+        for (_, item_type, item_id) in self.folder_rows.get(folder_id).into_iter().flatten() {
+            match item_type.as_str() {
+                "board" if self.live_boards.contains(item_id.as_str()) => {
+                    if self.placed_boards.insert(item_id.clone()) {
+                        items.push(LayoutItem::Board(item_id.clone()));
+                    } else {
+                        self.report.duplicate_board_ids.push(item_id.clone());
+                    }
+                }
+                "folder"
+                    if self.folder_parents.contains_key(item_id)
+                        && self.placed_folders.insert(item_id.clone()) =>
+                {
+                    items.extend(self.place_folder(item_id, Some(folder_id)));
+                }
+                "smart_folder"
+                    if self.smart_folders.contains(item_id.as_str())
+                        && self.placed_smart_folders.insert(item_id.clone()) =>
+                {
+                    items.push(LayoutItem::SmartFolder(item_id.clone()));
+                }
+                _ => self.report.dangling_folder_items.push(item_id.clone()),
+            }
+        }
+
+        if items.is_empty() {
+            self.report.empty_folder_ids.push(folder_id.to_string());
+            return None;
+        }
+        Some(LayoutItem::Folder {
+            id: folder_id.to_string(),
+            items,
+        })
+    }
+}
+
+fn has_position_gaps(positions: impl Iterator<Item = i64>) -> bool {
+    positions
+        .enumerate()
+        .any(|(expected, position)| position != expected as i64)
+}
+
+fn missing_thumbnail_board_ids(app: &AppHandle, conn: &Connection) -> AppResult<Vec<String>> {
+    let mut stmt =
+        conn.prepare("SELECT id, thumbnail FROM boards WHERE thumbnail IS NOT NULL ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+
+    let mut board_ids = Vec::new();
+    while let Some(row) = rows.next()? {
+        let board_id: String = row.get(0)?;
+        let thumbnail: String = row.get(1)?;
+        if !thumbnails::thumbnail_file_exists(
+            app,
+            thumbnails::RelativeThumbnailPath::from(thumbnail.as_str()),
+        )? {
+            board_ids.push(board_id);
+        }
+    }
+    Ok(board_ids)
+}
+
+fn write_layout(conn: &Connection, layout: &[LayoutItem]) -> AppResult<()> {
+    conn.execute("DELETE FROM folder_items", [])?;
+    conn.execute("DELETE FROM index_items", [])?;
+
+    for (position, item) in layout.iter().enumerate() {
+        let (item_type, item_id) = write_layout_item(conn, None, item)?;
+        conn.execute(
+            "INSERT INTO index_items (position, item_type, item_id) VALUES (?1, ?2, ?3)",
+            params![position as i64, item_type, item_id],
+        )?;
+    }
+
+    remove_empty_folders(conn)
+}
+
+/// Writes a folder's contents and parent, returning the entry that places `item` in its
+/// parent.
+fn write_layout_item<'a>(
+    conn: &Connection,
+    parent_id: Option<&str>,
+    item: &'a LayoutItem,
+) -> AppResult<(&'static str, &'a str)> {
+    match item {
+        LayoutItem::Board(board_id) => Ok(("board", board_id)),
+        LayoutItem::Folder { id, items } => {
+            conn.execute(
+                "UPDATE folders SET parent_folder_id = ?1 WHERE id = ?2",
+                params![parent_id, id],
+            )?;
+            for (position, child) in items.iter().enumerate() {
+                let (item_type, item_id) = write_layout_item(conn, Some(id), child)?;
+                conn.execute(
+                    "INSERT INTO folder_items (folder_id, position, item_type, item_id)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![id, position as i64, item_type, item_id],
+                )?;
+            }
+            Ok(("folder", id))
+        }
+        LayoutItem::SmartFolder(smart_folder_id) => Ok(("smart_folder", smart_folder_id)),
+    }
+}
+
+fn query_strings(conn: &Connection, sql: &str) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
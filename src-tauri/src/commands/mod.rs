@@ -1,5 +1,18 @@
+pub(crate) mod backups;
+pub(crate) mod board_archive;
 pub(crate) mod board_content;
+pub(crate) mod board_journal;
+pub(crate) mod board_revisions;
+pub(crate) mod board_search;
 pub(crate) mod board_transfer;
 pub(crate) mod boards;
+pub(crate) mod excalidraw_files;
+pub(crate) mod folders;
+pub(crate) mod integrity;
+pub(crate) mod smart_folders;
+pub(crate) mod startup;
 pub(crate) mod system_tests;
+pub(crate) mod tags;
+pub(crate) mod thumbnail_cache;
+pub(crate) mod trash;
 pub(crate) mod ui_preferences;
@@ -0,0 +1,164 @@
+use rusqlite::{params, Connection};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::commands::boards::{detach_item, insert_item_at, load_resolved_boards_index};
+use crate::commands::folders::{ensure_folder_exists, finish_index_change};
+use crate::db::lock_db;
+use crate::error::{AppError, AppResult};
+use crate::models::{BoardsIndex, SmartFolderCondition, SmartFolderQuery};
+
+/// Creates a smart folder at `position` in `parent_folder_id`, the root when `None`. Unlike
+/// a folder it is kept while no board matches.
+#[tauri::command]
+pub(crate) fn create_smart_folder(
+    app: AppHandle,
+    name: String,
+    query: SmartFolderQuery,
+    parent_folder_id: Option<String>,
+    position: Option<i64>,
+) -> AppResult<BoardsIndex> {
+    let name = validate_smart_folder_name(&name)?;
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    validate_query(&tx, &query)?;
+    if let Some(parent_id) = &parent_folder_id {
+        ensure_folder_exists(&tx, parent_id)?;
+    }
+
+    let smart_folder_id = Uuid::new_v4().to_string();
+    tx.execute(
+        "INSERT INTO smart_folders (id, name, query) VALUES (?1, ?2, ?3)",
+        params![smart_folder_id, name, serde_json::to_string(&query)?],
+    )?;
+    insert_item_at(
+        &tx,
+        parent_folder_id.as_deref(),
+        position.unwrap_or(i64::MAX),
+        "smart_folder",
+        &smart_folder_id,
+    )?;
+
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Replaces a smart folder's name and query.
+#[tauri::command]
+pub(crate) fn update_smart_folder(
+    app: AppHandle,
+    smart_folder_id: String,
+    name: String,
+    query: SmartFolderQuery,
+) -> AppResult<BoardsIndex> {
+    let name = validate_smart_folder_name(&name)?;
+    let conn = lock_db(&app)?;
+    validate_query(&conn, &query)?;
+    let updated = conn.execute(
+        "UPDATE smart_folders SET name = ?1, query = ?2 WHERE id = ?3",
+        params![name, serde_json::to_string(&query)?, smart_folder_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::not_found("Smart folder not found"));
+    }
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Deletes a smart folder. The boards it showed are not affected.
+#[tauri::command]
+pub(crate) fn delete_smart_folder(
+    app: AppHandle,
+    smart_folder_id: String,
+) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let deleted = tx.execute(
+        "DELETE FROM smart_folders WHERE id = ?1",
+        params![smart_folder_id],
+    )?;
+    if deleted == 0 {
+        return Err(AppError::not_found("Smart folder not found"));
+    }
+    detach_item(&tx, "smart_folder", &smart_folder_id)?;
+
+    finish_index_change(&tx)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Moves a smart folder; `position` counts entries the same way as for `move_board`.
+#[tauri::command]
+pub(crate) fn move_smart_folder(
+    app: AppHandle,
+    smart_folder_id: String,
+    folder_id: Option<String>,
+    position: i64,
+) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    let exists: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM smart_folders WHERE id = ?1)",
+        params![smart_folder_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::not_found("Smart folder not found"));
+    }
+    if let Some(folder_id) = &folder_id {
+        ensure_folder_exists(&tx, folder_id)?;
+    }
+
+    detach_item(&tx, "smart_folder", &smart_folder_id)?;
+    insert_item_at(
+        &tx,
+        folder_id.as_deref(),
+        position,
+        "smart_folder",
+        &smart_folder_id,
+    )?;
+
+    finish_index_change(&tx)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+fn validate_smart_folder_name(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::validation("Smart folder name cannot be empty"));
+    }
+    Ok(name)
+}
+
+fn validate_query(conn: &Connection, query: &SmartFolderQuery) -> AppResult<()> {
+    if query.conditions.is_empty() {
+        return Err(AppError::validation(
+            "A smart folder needs at least one condition",
+        ));
+    }
+
+    for condition in &query.conditions {
+        match condition {
+            SmartFolderCondition::Tag { tag_id } => {
+                let exists: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?1)",
+                    params![tag_id],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    return Err(AppError::not_found("Tag not found"));
+                }
+            }
+            SmartFolderCondition::UpdatedWithinDays { days } if *days == 0 => {
+                return Err(AppError::validation(
+                    "The number of days must be at least 1",
+                ));
+            }
+            SmartFolderCondition::NameContains { text } if text.trim().is_empty() => {
+                return Err(AppError::validation("The name filter cannot be empty"));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use std::sync::{Mutex, PoisonError};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// Failures of the housekeeping `setup` runs before any window exists, kept until the frontend
+/// asks for them. None of them stop the app from starting.
+pub(crate) struct StartupErrors(Mutex<Vec<AppError>>);
+
+impl StartupErrors {
+    pub(crate) fn new(errors: Vec<AppError>) -> Self {
+        Self(Mutex::new(errors))
+    }
+}
+
+/// Returns the startup housekeeping failures once; later calls return nothing.
+#[tauri::command]
+pub(crate) fn take_startup_errors(app: AppHandle) -> Vec<AppError> {
+    let Some(errors) = app.try_state::<StartupErrors>() else {
+        return Vec::new();
+    };
+    let mut errors = errors
+        .inner()
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    std::mem::take(&mut *errors)
+}
@@ -2,21 +2,19 @@ use std::fs;
 use tauri::AppHandle;
 
 use crate::db::get_boards_dir;
+use crate::error::AppResult;
 
 #[tauri::command]
-pub(crate) fn get_system_test_export_path(app: AppHandle) -> Result<Option<String>, String> {
+pub(crate) fn get_system_test_export_path(app: AppHandle) -> AppResult<Option<String>> {
     resolve_system_test_transfer_path(&app, "TAURI_TEST_EXPORT_PATH")
 }
 
 #[tauri::command]
-pub(crate) fn get_system_test_import_path(app: AppHandle) -> Result<Option<String>, String> {
+pub(crate) fn get_system_test_import_path(app: AppHandle) -> AppResult<Option<String>> {
     resolve_system_test_transfer_path(&app, "TAURI_TEST_IMPORT_PATH")
 }
 
-fn resolve_system_test_transfer_path(
-    app: &AppHandle,
-    env_key: &str,
-) -> Result<Option<String>, String> {
+fn resolve_system_test_transfer_path(app: &AppHandle, env_key: &str) -> AppResult<Option<String>> {
     if !is_system_test_mode() {
         return Ok(None);
     }
@@ -26,7 +24,7 @@ fn resolve_system_test_transfer_path(
     }
 
     let directory = get_boards_dir(app)?.join("system-tests");
-    fs::create_dir_all(&directory).map_err(|error| error.to_string())?;
+    fs::create_dir_all(&directory)?;
     let path = directory.join("boards-transfer.json");
     Ok(Some(path.to_string_lossy().into_owned()))
 }
@@ -0,0 +1,217 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeSet;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::commands::boards::{load_resolved_boards_index, resolve_board_thumbnail};
+use crate::db::{board_id_exists, load_boards_map, lock_db, tag_from_row};
+use crate::error::{AppError, AppResult};
+use crate::models::{Board, BoardsIndex, Tag, TagMatch};
+
+/// Every tag, sorted by name, whether or not a board carries it.
+#[tauri::command]
+pub(crate) fn list_tags(app: AppHandle) -> AppResult<Vec<Tag>> {
+    let conn = lock_db(&app)?;
+    let mut stmt =
+        conn.prepare("SELECT id, name, color FROM tags ORDER BY name COLLATE NOCASE, id")?;
+    let rows = stmt.query_map([], |row| tag_from_row(row, 0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+#[tauri::command]
+pub(crate) fn create_tag(app: AppHandle, name: String, color: Option<String>) -> AppResult<Tag> {
+    let name = validate_tag_name(&name)?;
+    let color = normalize_color(color)?;
+    let conn = lock_db(&app)?;
+    ensure_name_available(&conn, name, None)?;
+
+    let tag = Tag {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        color,
+    };
+    conn.execute(
+        "INSERT INTO tags (id, name, color, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![tag.id, tag.name, tag.color, Utc::now().timestamp_millis()],
+    )?;
+    Ok(tag)
+}
+
+#[tauri::command]
+pub(crate) fn rename_tag(app: AppHandle, tag_id: String, name: String) -> AppResult<BoardsIndex> {
+    let name = validate_tag_name(&name)?;
+    let conn = lock_db(&app)?;
+    ensure_name_available(&conn, name, Some(&tag_id))?;
+    let updated = conn.execute(
+        "UPDATE tags SET name = ?1 WHERE id = ?2",
+        params![name, tag_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::not_found("Tag not found"));
+    }
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Sets or, with `None`, clears a tag's color.
+#[tauri::command]
+pub(crate) fn set_tag_color(
+    app: AppHandle,
+    tag_id: String,
+    color: Option<String>,
+) -> AppResult<BoardsIndex> {
+    let color = normalize_color(color)?;
+    let conn = lock_db(&app)?;
+    let updated = conn.execute(
+        "UPDATE tags SET color = ?1 WHERE id = ?2",
+        params![color, tag_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::not_found("Tag not found"));
+    }
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Deletes a tag and takes it off every board, trashed ones included.
+#[tauri::command]
+pub(crate) fn delete_tag(app: AppHandle, tag_id: String) -> AppResult<BoardsIndex> {
+    let conn = lock_db(&app)?;
+    let deleted = conn.execute("DELETE FROM tags WHERE id = ?1", params![tag_id])?;
+    if deleted == 0 {
+        return Err(AppError::not_found("Tag not found"));
+    }
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Tags a board. Tagging it again with the same tag does nothing.
+#[tauri::command]
+pub(crate) fn add_board_tag(
+    app: AppHandle,
+    board_id: String,
+    tag_id: String,
+) -> AppResult<BoardsIndex> {
+    let conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+    ensure_tag_exists(&conn, &tag_id)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO board_tags (board_id, tag_id) VALUES (?1, ?2)",
+        params![board_id, tag_id],
+    )?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+#[tauri::command]
+pub(crate) fn remove_board_tag(
+    app: AppHandle,
+    board_id: String,
+    tag_id: String,
+) -> AppResult<BoardsIndex> {
+    let conn = lock_db(&app)?;
+    if !board_id_exists(&conn, &board_id)? {
+        return Err(AppError::board_not_found(&board_id));
+    }
+    conn.execute(
+        "DELETE FROM board_tags WHERE board_id = ?1 AND tag_id = ?2",
+        params![board_id, tag_id],
+    )?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Live boards carrying any (the default) or all of `tag_ids`, sorted by name. Unknown tag ids
+/// match no board, so asking for all of a set that includes one returns nothing.
+#[tauri::command]
+pub(crate) fn list_boards_by_tags(
+    app: AppHandle,
+    tag_ids: Vec<String>,
+    match_mode: Option<TagMatch>,
+) -> AppResult<Vec<Board>> {
+    let tag_ids: BTreeSet<String> = tag_ids.into_iter().collect();
+    if tag_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let required_matches = match match_mode.unwrap_or_default() {
+        TagMatch::Any => 1,
+        TagMatch::All => tag_ids.len() as i64,
+    };
+
+    let conn = lock_db(&app)?;
+    let board_ids: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT b.id FROM boards b
+                 JOIN board_tags bt ON bt.board_id = b.id
+                 WHERE b.deleted_at IS NULL
+                   AND bt.tag_id IN (SELECT value FROM json_each(?1))
+                 GROUP BY b.id
+                 HAVING COUNT(*) >= ?2
+                 ORDER BY b.name COLLATE NOCASE, b.id",
+        )?;
+        let rows = stmt.query_map(
+            params![serde_json::to_string(&tag_ids)?, required_matches],
+            |row| row.get(0),
+        )?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut boards = load_boards_map(&conn)?;
+    board_ids
+        .iter()
+        .filter_map(|board_id| boards.remove(board_id))
+        .map(|board| resolve_board_thumbnail(&app, board))
+        .collect()
+}
+
+fn validate_tag_name(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::validation("Tag name cannot be empty"));
+    }
+    Ok(name)
+}
+
+/// Accepts `#rrggbb` in either case and stores it lowercased.
+fn normalize_color(color: Option<String>) -> AppResult<Option<String>> {
+    let Some(color) = color else {
+        return Ok(None);
+    };
+    let color = color.trim();
+    let is_hex_color = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !is_hex_color {
+        return Err(AppError::validation(format!(
+            "Tag color must be a hex color like #3b82f6, got \"{color}\""
+        )));
+    }
+    Ok(Some(color.to_ascii_lowercase()))
+}
+
+fn ensure_tag_exists(conn: &Connection, tag_id: &str) -> AppResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?1)",
+        params![tag_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::not_found("Tag not found"));
+    }
+    Ok(())
+}
+
+/// Tag names are unique regardless of case; `tag_id` is the tag being renamed, if any.
+fn ensure_name_available(conn: &Connection, name: &str, tag_id: Option<&str>) -> AppResult<()> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match existing {
+        Some(existing_id) if Some(existing_id.as_str()) != tag_id => Err(AppError::conflict(
+            format!("A tag named \"{name}\" already exists"),
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,77 @@
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+use crate::db::{get_setting, lock_db, set_setting};
+use crate::error::{AppError, AppResult};
+use crate::models::ThumbnailGcReport;
+use crate::thumbnails;
+
+const THUMBNAIL_CACHE_LIMIT_SETTING_KEY: &str = "thumbnails.max_cache_bytes";
+const DEFAULT_THUMBNAIL_CACHE_LIMIT_BYTES: u64 = 64 * 1024 * 1024;
+
+#[tauri::command]
+pub(crate) fn collect_thumbnail_garbage(app: AppHandle) -> AppResult<ThumbnailGcReport> {
+    let conn = lock_db(&app)?;
+    run_thumbnail_gc(&app, &conn)
+}
+
+#[tauri::command]
+pub(crate) fn get_thumbnail_cache_limit(app: AppHandle) -> AppResult<u64> {
+    let conn = lock_db(&app)?;
+    thumbnail_cache_limit(&conn)
+}
+
+/// Sets the most disk space cached thumbnails may take. `0` removes the limit. The limit is
+/// applied on the next garbage collection pass.
+#[tauri::command]
+pub(crate) fn set_thumbnail_cache_limit(app: AppHandle, max_bytes: u64) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    set_setting(
+        &conn,
+        THUMBNAIL_CACHE_LIMIT_SETTING_KEY,
+        Some(&max_bytes.to_string()),
+    )
+}
+
+/// Deletes thumbnail files no board references, including those of trashed boards, and
+/// evicts the oldest thumbnails beyond the cache size limit, clearing their paths so the
+/// frontend regenerates them. Runs at startup and on demand; the caller holds the database
+/// lock so no thumbnail is written mid-pass.
+pub(crate) fn run_thumbnail_gc(app: &AppHandle, conn: &Connection) -> AppResult<ThumbnailGcReport> {
+    let referenced: HashSet<String> = {
+        let mut stmt = conn.prepare("SELECT thumbnail FROM boards WHERE thumbnail IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let garbage = thumbnails::collect_garbage(app, &referenced, thumbnail_cache_limit(conn)?)?;
+
+    let mut evicted_board_ids = Vec::new();
+    for relative_path in &garbage.evicted_paths {
+        let mut stmt =
+            conn.prepare("UPDATE boards SET thumbnail = NULL WHERE thumbnail = ?1 RETURNING id")?;
+        let rows = stmt.query_map(params![relative_path], |row| row.get::<_, String>(0))?;
+        for board_id in rows {
+            evicted_board_ids.push(board_id?);
+        }
+    }
+
+    Ok(ThumbnailGcReport {
+        removed_files: garbage.removed_files,
+        reclaimed_bytes: garbage.reclaimed_bytes,
+        remaining_bytes: garbage.remaining_bytes,
+        evicted_board_ids,
+    })
+}
+
+fn thumbnail_cache_limit(conn: &Connection) -> AppResult<u64> {
+    let Some(raw) = get_setting(conn, THUMBNAIL_CACHE_LIMIT_SETTING_KEY)? else {
+        return Ok(DEFAULT_THUMBNAIL_CACHE_LIMIT_BYTES);
+    };
+    raw.trim().parse().map_err(|_| {
+        AppError::validation(format!(
+            "Invalid value for setting '{THUMBNAIL_CACHE_LIMIT_SETTING_KEY}'"
+        ))
+    })
+}
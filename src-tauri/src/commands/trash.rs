@@ -0,0 +1,428 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+use crate::assets;
+use crate::commands::boards::{
+    detach_item, insert_item_at, load_resolved_boards_index, locate_item, resolve_board_thumbnail,
+};
+use crate::db::{datetime_from_millis, get_board_by_id, get_setting, lock_db, set_setting};
+use crate::error::{AppError, AppResult};
+use crate::models::{BoardsIndex, TrashedBoard};
+use crate::search_index;
+use crate::thumbnails;
+
+const TRASH_RETENTION_SETTING_KEY: &str = "trash.retention_days";
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Where a trashed board lived in the index, so it can be put back on restore. `folder_path`
+/// lists the enclosing folders from the root inwards; it is empty for root boards.
+struct TrashOrigin {
+    folder_path: Vec<TrashFolder>,
+    position: Option<i64>,
+}
+
+/// A folder on a trashed board's path and its index within its own parent.
+#[derive(Serialize, Deserialize)]
+struct TrashFolder {
+    id: String,
+    name: String,
+    position: Option<i64>,
+}
+
+#[tauri::command]
+pub(crate) fn list_trash(app: AppHandle) -> AppResult<Vec<TrashedBoard>> {
+    let mut conn = lock_db(&app)?;
+    purge_expired_trash(&app, &mut conn)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT b.id, b.deleted_at, t.folder_id, t.folder_name FROM boards b
+             LEFT JOIN board_trash t ON t.board_id = b.id
+             WHERE b.deleted_at IS NOT NULL ORDER BY b.deleted_at DESC",
+    )?;
+    let mut rows = stmt.query([])?;
+
+    let mut trashed = Vec::new();
+    while let Some(row) = rows.next()? {
+        let board_id: String = row.get(0)?;
+        let deleted_at_ms: i64 = row.get(1)?;
+        let board = resolve_board_thumbnail(&app, get_board_by_id(&conn, &board_id)?)?;
+        trashed.push(TrashedBoard {
+            board,
+            deleted_at: datetime_from_millis(deleted_at_ms)?,
+            folder_id: row.get(2)?,
+            folder_name: row.get(3)?,
+        });
+    }
+
+    Ok(trashed)
+}
+
+#[tauri::command]
+pub(crate) fn restore_board(app: AppHandle, board_id: String) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    restore_trashed_board(&tx, &board_id)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+/// Restores every trashed board that originally lived in `folder_id` or one of its subfolders,
+/// in their original order.
+#[tauri::command]
+pub(crate) fn restore_folder(app: AppHandle, folder_id: String) -> AppResult<BoardsIndex> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    restore_trashed_folder(&tx, &folder_id)?;
+    tx.commit()?;
+    load_resolved_boards_index(&app, &conn)
+}
+
+pub(crate) fn restore_trashed_folder(conn: &Connection, folder_id: &str) -> AppResult<()> {
+    let board_ids: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT t.board_id FROM board_trash t JOIN boards b ON b.id = t.board_id
+                 WHERE b.deleted_at IS NOT NULL
+                   AND (t.folder_id = ?1 OR EXISTS (
+                       SELECT 1 FROM json_each(t.folder_path)
+                       WHERE json_extract(json_each.value, '$.id') = ?1
+                   ))
+                 ORDER BY json_array_length(COALESCE(t.folder_path, '[]')) ASC, t.position ASC",
+        )?;
+        let rows = stmt.query_map(params![folder_id], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    if board_ids.is_empty() {
+        return Err(AppError::not_found("Folder not found in trash"));
+    }
+
+    for board_id in &board_ids {
+        restore_trashed_board(conn, board_id)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn purge_board(app: AppHandle, board_id: String) -> AppResult<()> {
+    let mut conn = lock_db(&app)?;
+    let tx = conn.transaction()?;
+    purge_trashed_board(&tx, &board_id)?;
+    tx.commit()?;
+
+    thumbnails::delete_thumbnail(&app, thumbnails::BoardId::from(board_id.as_str()))
+}
+
+/// Deletes a trashed board's rows, and the assets only it referenced. Runs in the caller's
+/// transaction; the thumbnail file is left to the caller.
+pub(crate) fn purge_trashed_board(conn: &Connection, board_id: &str) -> AppResult<()> {
+    let deleted = conn.execute(
+        "DELETE FROM boards WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![board_id],
+    )?;
+    if deleted == 0 {
+        return Err(AppError::not_found("Board not found in trash").with_board_id(board_id));
+    }
+    search_index::remove_board_from_index(conn, board_id)?;
+    assets::collect_garbage(conn)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn empty_trash(app: AppHandle) -> AppResult<usize> {
+    let mut conn = lock_db(&app)?;
+    purge_trashed_boards(&app, &mut conn, None)
+}
+
+#[tauri::command]
+pub(crate) fn get_trash_retention_days(app: AppHandle) -> AppResult<u32> {
+    let conn = lock_db(&app)?;
+    trash_retention_days(&conn)
+}
+
+/// Sets how long trashed boards are kept before being purged automatically. `0` keeps them
+/// until the trash is emptied manually.
+#[tauri::command]
+pub(crate) fn set_trash_retention_days(app: AppHandle, days: u32) -> AppResult<()> {
+    let conn = lock_db(&app)?;
+    set_setting(&conn, TRASH_RETENTION_SETTING_KEY, Some(&days.to_string()))
+}
+
+/// Purges boards that have been in the trash longer than the configured retention period.
+/// Runs at startup and whenever the trash is listed.
+pub(crate) fn purge_expired_trash(app: &AppHandle, conn: &mut Connection) -> AppResult<usize> {
+    let retention_days = trash_retention_days(conn)?;
+    if retention_days == 0 {
+        return Ok(0);
+    }
+
+    let cutoff = Utc::now().timestamp_millis() - i64::from(retention_days) * DAY_MS;
+    purge_trashed_boards(app, conn, Some(cutoff))
+}
+
+fn purge_trashed_boards(
+    app: &AppHandle,
+    conn: &mut Connection,
+    deleted_before_ms: Option<i64>,
+) -> AppResult<usize> {
+    let cutoff = deleted_before_ms.unwrap_or(i64::MAX);
+    let tx = conn.transaction()?;
+    let board_ids: Vec<String> = {
+        let mut stmt =
+            tx.prepare("SELECT id FROM boards WHERE deleted_at IS NOT NULL AND deleted_at < ?1")?;
+        let rows = stmt.query_map(params![cutoff], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    for board_id in &board_ids {
+        tx.execute("DELETE FROM boards WHERE id = ?1", params![board_id])?;
+        search_index::remove_board_from_index(&tx, board_id)?;
+    }
+    if !board_ids.is_empty() {
+        assets::collect_garbage(&tx)?;
+    }
+    tx.commit()?;
+
+    for board_id in &board_ids {
+        thumbnails::delete_thumbnail(app, thumbnails::BoardId::from(board_id.as_str()))?;
+    }
+    Ok(board_ids.len())
+}
+
+fn trash_retention_days(conn: &Connection) -> AppResult<u32> {
+    let Some(raw) = get_setting(conn, TRASH_RETENTION_SETTING_KEY)? else {
+        return Ok(DEFAULT_TRASH_RETENTION_DAYS);
+    };
+    raw.trim().parse().map_err(|_| {
+        AppError::validation(format!(
+            "Invalid value for setting '{TRASH_RETENTION_SETTING_KEY}'"
+        ))
+    })
+}
+
+/// Marks a board as deleted and detaches it from the index, remembering its folder and
+/// position. The board's data and thumbnail are kept until it is purged.
+pub(crate) fn move_board_to_trash(
+    conn: &Connection,
+    board_id: &str,
+    deleted_at_ms: i64,
+) -> AppResult<()> {
+    let origin = load_index_origin(conn, board_id)?;
+    trash_board(conn, board_id, origin, deleted_at_ms)
+}
+
+/// Trashes several boards at once. Every origin is recorded before any board is detached,
+/// since detaching one shifts the index of each entry after it in the same folder.
+pub(crate) fn move_boards_to_trash(
+    conn: &Connection,
+    board_ids: &[String],
+    deleted_at_ms: i64,
+) -> AppResult<()> {
+    let origins = board_ids
+        .iter()
+        .map(|board_id| load_index_origin(conn, board_id))
+        .collect::<AppResult<Vec<_>>>()?;
+    for (board_id, origin) in board_ids.iter().zip(origins) {
+        trash_board(conn, board_id, origin, deleted_at_ms)?;
+    }
+    Ok(())
+}
+
+fn trash_board(
+    conn: &Connection,
+    board_id: &str,
+    origin: TrashOrigin,
+    deleted_at_ms: i64,
+) -> AppResult<()> {
+    let updated = conn.execute(
+        "UPDATE boards SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![deleted_at_ms, board_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::board_not_found(board_id));
+    }
+
+    let parent = origin.folder_path.last();
+    conn.execute(
+        "INSERT OR REPLACE INTO board_trash
+             (board_id, folder_id, folder_name, folder_position, position, folder_path)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            board_id,
+            parent.map(|folder| &folder.id),
+            parent.map(|folder| &folder.name),
+            parent.and_then(|folder| folder.position),
+            origin.position,
+            serde_json::to_string(&origin.folder_path)?
+        ],
+    )?;
+    detach_item(conn, "board", board_id)
+}
+
+fn load_index_origin(conn: &Connection, board_id: &str) -> AppResult<TrashOrigin> {
+    let Some(location) = locate_item(conn, "board", board_id)? else {
+        return Ok(TrashOrigin {
+            folder_path: Vec::new(),
+            position: None,
+        });
+    };
+
+    let mut folder_path = Vec::new();
+    let mut visited = HashSet::new();
+    let mut next_folder_id = location.folder_id;
+    while let Some(folder_id) = next_folder_id.take() {
+        if !visited.insert(folder_id.clone()) {
+            break;
+        }
+        let Some(name) = conn
+            .query_row(
+                "SELECT name FROM folders WHERE id = ?1",
+                params![folder_id],
+                |row| row.get(0),
+            )
+            .optional()?
+        else {
+            break;
+        };
+        let folder_location = locate_item(conn, "folder", &folder_id)?;
+        folder_path.push(TrashFolder {
+            id: folder_id,
+            name,
+            position: folder_location.as_ref().map(|location| location.index),
+        });
+        next_folder_id = folder_location.and_then(|location| location.folder_id);
+    }
+    folder_path.reverse();
+
+    Ok(TrashOrigin {
+        folder_path,
+        position: Some(location.index),
+    })
+}
+
+fn restore_trashed_board(conn: &Connection, board_id: &str) -> AppResult<()> {
+    let origin = conn
+        .query_row(
+            "SELECT folder_id, folder_name, folder_position, position, folder_path
+             FROM board_trash WHERE board_id = ?1",
+            params![board_id],
+            |row| {
+                let folder_id: Option<String> = row.get(0)?;
+                let folder_name: Option<String> = row.get(1)?;
+                let folder_position: Option<i64> = row.get(2)?;
+                let position: Option<i64> = row.get(3)?;
+                let folder_path: Option<String> = row.get(4)?;
+                Ok((
+                    folder_id,
+                    folder_name,
+                    folder_position,
+                    position,
+                    folder_path,
+                ))
+            },
+        )
+        .optional()?
+        .map(
+            |(folder_id, folder_name, folder_position, position, folder_path)| {
+                // Boards trashed before folders could nest only record their direct folder.
+                let folder_path = match (folder_path, folder_id, folder_name) {
+                    (Some(folder_path), ..) => serde_json::from_str(&folder_path)?,
+                    (None, Some(id), Some(name)) => vec![TrashFolder {
+                        id,
+                        name,
+                        position: folder_position,
+                    }],
+                    _ => Vec::new(),
+                };
+                AppResult::Ok(TrashOrigin {
+                    folder_path,
+                    position,
+                })
+            },
+        )
+        .transpose()?
+        .unwrap_or(TrashOrigin {
+            folder_path: Vec::new(),
+            position: None,
+        });
+
+    let updated = conn.execute(
+        "UPDATE boards SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![board_id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::not_found("Board not found in trash").with_board_id(board_id));
+    }
+
+    let mut parent_id: Option<String> = None;
+    for folder in origin.folder_path {
+        let folder_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM folders WHERE id = ?1)",
+            params![folder.id],
+            |row| row.get(0),
+        )?;
+        if !folder_exists {
+            conn.execute(
+                "INSERT INTO folders (id, name, parent_folder_id) VALUES (?1, ?2, ?3)",
+                params![folder.id, folder.name, parent_id],
+            )?;
+            insert_item_at(
+                conn,
+                parent_id.as_deref(),
+                folder.position.unwrap_or(i64::MAX),
+                "folder",
+                &folder.id,
+            )?;
+        }
+        parent_id = Some(folder.id);
+    }
+
+    insert_item_at(
+        conn,
+        parent_id.as_deref(),
+        origin.position.unwrap_or(i64::MAX),
+        "board",
+        board_id,
+    )?;
+
+    conn.execute(
+        "DELETE FROM board_trash WHERE board_id = ?1",
+        params![board_id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::board_content::store_board_data;
+    use crate::commands::boards::insert_new_board;
+    use crate::db::open_test_db;
+
+    #[test]
+    fn purging_a_board_drops_the_assets_only_it_referenced() {
+        let conn = open_test_db();
+        insert_new_board(&conn, "board".to_string(), "Board".to_string()).unwrap();
+        let scene = serde_json::json!({
+            "elements": [],
+            "files": {
+                "image": {
+                    "id": "image",
+                    "mimeType": "image/png",
+                    "dataURL": "data:image/png;base64,AAAA"
+                }
+            }
+        });
+        store_board_data(&conn, "board", &scene.to_string(), None).unwrap();
+        move_board_to_trash(&conn, "board", 0).unwrap();
+
+        purge_trashed_board(&conn, "board").unwrap();
+
+        let assets: i64 = conn
+            .query_row("SELECT COUNT(*) FROM assets", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(assets, 0);
+    }
+}
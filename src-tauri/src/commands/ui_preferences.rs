@@ -1,7 +1,8 @@
 use serde::Serialize;
 use tauri::AppHandle;
 
-use crate::db::{get_setting, open_db, set_setting};
+use crate::db::{get_setting, lock_db, set_setting};
+use crate::error::{AppError, AppResult};
 
 const HIDE_EXPORT_ROW_SETTING_KEY: &str = "ui.hide_export_row";
 const SHOW_TIMESTAMPS_SETTING_KEY: &str = "ui.show_timestamps";
@@ -15,8 +16,8 @@ pub(crate) struct UiPreferences {
 }
 
 #[tauri::command]
-pub(crate) fn get_ui_preferences(app: AppHandle) -> Result<UiPreferences, String> {
-    let conn = open_db(&app)?;
+pub(crate) fn get_ui_preferences(app: AppHandle) -> AppResult<UiPreferences> {
+    let conn = lock_db(&app)?;
     let hide_export_row = parse_optional_boolean_setting(
         get_setting(&conn, HIDE_EXPORT_ROW_SETTING_KEY)?,
         HIDE_EXPORT_ROW_SETTING_KEY,
@@ -38,23 +39,20 @@ pub(crate) fn get_ui_preferences(app: AppHandle) -> Result<UiPreferences, String
 }
 
 #[tauri::command]
-pub(crate) fn set_ui_preference(app: AppHandle, key: String, value: bool) -> Result<(), String> {
+pub(crate) fn set_ui_preference(app: AppHandle, key: String, value: bool) -> AppResult<()> {
     let setting_key = match key.as_str() {
         "hide_export_row" => HIDE_EXPORT_ROW_SETTING_KEY,
         "show_timestamps" => SHOW_TIMESTAMPS_SETTING_KEY,
         "sidebar_collapsed" => SIDEBAR_COLLAPSED_SETTING_KEY,
-        _ => return Err("Invalid UI preference key".to_string()),
+        _ => return Err(AppError::validation("Invalid UI preference key")),
     };
 
-    let conn = open_db(&app)?;
+    let conn = lock_db(&app)?;
     let setting_value = if value { "1" } else { "0" };
     set_setting(&conn, setting_key, Some(setting_value))
 }
 
-fn parse_optional_boolean_setting(
-    value: Option<String>,
-    key: &str,
-) -> Result<Option<bool>, String> {
+fn parse_optional_boolean_setting(value: Option<String>, key: &str) -> AppResult<Option<bool>> {
     let Some(raw) = value else {
         return Ok(None);
     };
@@ -62,6 +60,8 @@ fn parse_optional_boolean_setting(
     match raw.trim().to_ascii_lowercase().as_str() {
         "1" | "true" => Ok(Some(true)),
         "0" | "false" => Ok(Some(false)),
-        _ => Err(format!("Invalid boolean setting value for key '{key}'")),
+        _ => Err(AppError::validation(format!(
+            "Invalid boolean setting value for key '{key}'"
+        ))),
     }
 }
@@ -0,0 +1,61 @@
+use rusqlite::types::ValueRef;
+use rusqlite::Row;
+
+use crate::error::{AppError, AppResult};
+
+/// Values of the `compression` column on `board_data` and `board_revisions`. Rows written
+/// before schema version 6 hold plain JSON text.
+pub(crate) const COMPRESSION_NONE: i64 = 0;
+pub(crate) const COMPRESSION_ZSTD: i64 = 1;
+
+/// Scenes are rewritten on every autosave, so favour speed over ratio. Excalidraw JSON still
+/// shrinks about tenfold at this level.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Compresses a scene for storage with `COMPRESSION_ZSTD`.
+pub(crate) fn encode_scene(data: &str) -> AppResult<Vec<u8>> {
+    Ok(zstd::encode_all(data.as_bytes(), ZSTD_LEVEL)?)
+}
+
+/// A scene as read from a `data`/`compression` column pair, not yet decompressed.
+pub(crate) struct StoredScene {
+    compression: i64,
+    bytes: Vec<u8>,
+}
+
+impl StoredScene {
+    pub(crate) fn from_row(
+        row: &Row<'_>,
+        data_index: usize,
+        compression_index: usize,
+    ) -> rusqlite::Result<Self> {
+        let bytes = match row.get_ref(data_index)? {
+            ValueRef::Text(bytes) | ValueRef::Blob(bytes) => bytes.to_vec(),
+            other => {
+                return Err(rusqlite::Error::InvalidColumnType(
+                    data_index,
+                    "data".to_string(),
+                    other.data_type(),
+                ))
+            }
+        };
+        Ok(Self {
+            compression: row.get(compression_index)?,
+            bytes,
+        })
+    }
+
+    pub(crate) fn decode(self) -> AppResult<String> {
+        let bytes = match self.compression {
+            COMPRESSION_NONE => self.bytes,
+            COMPRESSION_ZSTD => zstd::decode_all(self.bytes.as_slice())
+                .map_err(|error| AppError::database(format!("Corrupt scene data: {error}")))?,
+            other => {
+                return Err(AppError::database(format!(
+                    "Unknown scene compression {other}"
+                )))
+            }
+        };
+        String::from_utf8(bytes).map_err(|_| AppError::database("Scene data is not valid UTF-8"))
+    }
+}
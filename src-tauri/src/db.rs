@@ -1,14 +1,26 @@
 use chrono::{DateTime, TimeZone, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
 use tauri::{AppHandle, Manager};
 
-use crate::models::{Board, BoardFolder, BoardListItem, BoardsIndex};
+use crate::compression::{encode_scene, StoredScene, COMPRESSION_ZSTD};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Board, BoardDataPatch, BoardFolder, BoardListItem, BoardsIndex, SmartFolder,
+    SmartFolderCondition, SmartFolderQuery, Tag,
+};
+use crate::scene;
 
-pub(crate) fn get_boards_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+/// How long a statement waits on a lock held by another connection (e.g. an external tool
+/// reading the database) before failing with "database is locked".
+const BUSY_TIMEOUT_MS: u64 = 5_000;
+
+pub(crate) fn get_boards_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let app_data = app.path().app_data_dir()?;
     let mut boards_dir = app_data.join("boards");
 
     if is_system_test_mode() {
@@ -19,7 +31,7 @@ pub(crate) fn get_boards_dir(app: &AppHandle) -> Result<PathBuf, String> {
         }
     }
 
-    fs::create_dir_all(&boards_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&boards_dir)?;
     Ok(boards_dir)
 }
 
@@ -62,280 +74,380 @@ pub(crate) fn default_board_data() -> String {
     .to_string()
 }
 
-fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn get_db_path(app: &AppHandle) -> AppResult<PathBuf> {
     let boards_dir = get_boards_dir(app)?;
     Ok(boards_dir.join("boards.db"))
 }
 
-pub(crate) fn open_db(app: &AppHandle) -> Result<Connection, String> {
-    let db_path = get_db_path(app)?;
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
-    conn.execute_batch("PRAGMA foreign_keys = ON;")
-        .map_err(|e| e.to_string())?;
-    init_db(&conn, app)?;
-    Ok(conn)
-}
+/// The app's single SQLite connection, opened once in `setup` and managed as Tauri state.
+/// Commands hold the lock for their whole body, so helpers they call take a `&Connection`
+/// rather than locking again.
+pub(crate) struct Database(Mutex<Connection>);
 
-fn init_db(conn: &Connection, app: &AppHandle) -> Result<(), String> {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS boards (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            created_at INTEGER NOT NULL,
-            updated_at INTEGER NOT NULL,
-            collaboration_link TEXT,
-            thumbnail TEXT
-        );
-        CREATE TABLE IF NOT EXISTS folders (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL
-        );
-        CREATE TABLE IF NOT EXISTS index_items (
-            position INTEGER NOT NULL,
-            item_type TEXT NOT NULL,
-            item_id TEXT NOT NULL,
-            PRIMARY KEY(position)
-        );
-        CREATE TABLE IF NOT EXISTS folder_items (
-            folder_id TEXT NOT NULL,
-            board_id TEXT NOT NULL,
-            position INTEGER NOT NULL,
-            PRIMARY KEY(folder_id, position),
-            UNIQUE(folder_id, board_id),
-            FOREIGN KEY(folder_id) REFERENCES folders(id) ON DELETE CASCADE,
-            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
-        );
-        CREATE TABLE IF NOT EXISTS board_data (
-            board_id TEXT PRIMARY KEY,
-            data TEXT NOT NULL,
-            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
-        );
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        );",
-    )
-    .map_err(|e| e.to_string())?;
-
-    let version: i64 = conn
-        .query_row("PRAGMA user_version", [], |row| row.get(0))
-        .map_err(|e| e.to_string())?;
-    if version == 0 {
-        conn.execute("PRAGMA user_version = 1", [])
-            .map_err(|e| e.to_string())?;
+impl Database {
+    pub(crate) fn new(conn: Connection) -> Self {
+        Self(Mutex::new(conn))
     }
-    if version < 2 {
-        migrate_thumbnails_to_files(conn, app)?;
-        conn.execute("PRAGMA user_version = 2", [])
-            .map_err(|e| e.to_string())?;
-    }
-    Ok(())
 }
 
-/// Migration from schema version 1 to 2: thumbnails used to be stored as inline
-/// `data:` URLs in the `boards.thumbnail` TEXT column. This moves any such values to
-/// files under the app data directory and replaces the DB value with the relative path.
-fn migrate_thumbnails_to_files(conn: &Connection, app: &AppHandle) -> Result<(), String> {
-    let mut stmt = conn
-        .prepare("SELECT id, thumbnail FROM boards WHERE thumbnail LIKE 'data:%'")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-
-    let mut updates: Vec<(String, Option<String>)> = Vec::new();
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let id: String = row.get(0).map_err(|e| e.to_string())?;
-        let thumbnail: String = row.get(1).map_err(|e| e.to_string())?;
-        let relative_path = crate::thumbnails::save_thumbnail(
-            app,
-            crate::thumbnails::BoardId::from(id.as_str()),
-            Some(&thumbnail),
-        )?;
-        updates.push((id, relative_path));
-    }
-    drop(rows);
-    drop(stmt);
+/// Opens the database file and brings its schema up to date. Only called from `setup`;
+/// everything else goes through `lock_db`.
+pub(crate) fn open_db(app: &AppHandle) -> AppResult<Connection> {
+    let boards_dir = get_boards_dir(app)?;
+    let db_path = get_db_path(app)?;
+    let conn = Connection::open(&db_path)?;
+    conn.busy_timeout(Duration::from_millis(BUSY_TIMEOUT_MS))?;
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.execute_batch("PRAGMA synchronous = NORMAL; PRAGMA foreign_keys = ON;")?;
+    crate::migrations::run_migrations(&conn, &boards_dir, &db_path)?;
+    Ok(conn)
+}
 
-    for (id, relative_path) in updates {
-        conn.execute(
-            "UPDATE boards SET thumbnail = ?1 WHERE id = ?2",
-            params![relative_path, id],
-        )
-        .map_err(|e| e.to_string())?;
-    }
+/// An in-memory database migrated to the latest schema.
+#[cfg(test)]
+pub(crate) fn open_test_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+    let boards_dir = std::env::temp_dir();
+    crate::migrations::run_migrations(&conn, &boards_dir, &boards_dir.join("boards.db")).unwrap();
+    conn
+}
 
-    Ok(())
+/// Locks the shared connection. A panic in another command does not leave the connection
+/// unusable: any transaction it had open was rolled back when it unwound.
+pub(crate) fn lock_db(app: &AppHandle) -> AppResult<MutexGuard<'_, Connection>> {
+    let database = app
+        .try_state::<Database>()
+        .ok_or_else(|| AppError::database("Database is not initialized"))?;
+    Ok(database
+        .inner()
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner))
 }
 
-pub(crate) fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+pub(crate) fn get_setting(conn: &Connection, key: &str) -> AppResult<Option<String>> {
     conn.query_row(
         "SELECT value FROM settings WHERE key = ?1",
         params![key],
         |row| row.get(0),
     )
     .optional()
-    .map_err(|e| e.to_string())
+    .map_err(AppError::from)
 }
 
-pub(crate) fn set_setting(conn: &Connection, key: &str, value: Option<&str>) -> Result<(), String> {
+pub(crate) fn set_setting(conn: &Connection, key: &str, value: Option<&str>) -> AppResult<()> {
     if let Some(value) = value {
         conn.execute(
             "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
             params![key, value],
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
     } else {
-        conn.execute("DELETE FROM settings WHERE key = ?1", params![key])
-            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
     }
     Ok(())
 }
 
-fn datetime_from_millis(value: i64) -> Result<DateTime<Utc>, String> {
+pub(crate) fn datetime_from_millis(value: i64) -> AppResult<DateTime<Utc>> {
     Utc.timestamp_millis_opt(value)
         .single()
-        .ok_or_else(|| "Invalid timestamp in database".to_string())
+        .ok_or_else(|| AppError::database("Invalid timestamp in database"))
 }
 
-pub(crate) fn board_id_exists(conn: &Connection, board_id: &str) -> Result<bool, String> {
-    let exists: i64 = conn
-        .query_row(
-            "SELECT EXISTS(SELECT 1 FROM boards WHERE id = ?1)",
-            params![board_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-    Ok(exists != 0)
+fn optional_datetime_from_millis(value: Option<i64>) -> AppResult<Option<DateTime<Utc>>> {
+    value.map(datetime_from_millis).transpose()
 }
 
-pub(crate) fn first_board_id_from_db(conn: &Connection) -> Result<Option<String>, String> {
-    let mut stmt = conn
-        .prepare("SELECT item_type, item_id FROM index_items ORDER BY position ASC")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let item_type: String = row.get(0).map_err(|e| e.to_string())?;
-        let item_id: String = row.get(1).map_err(|e| e.to_string())?;
-        let board_id = board_id_from_index_item(conn, &item_type, &item_id)?;
-        if board_id.is_some() {
-            return Ok(board_id);
-        }
-    }
+pub(crate) fn board_id_exists(conn: &Connection, board_id: &str) -> AppResult<bool> {
+    let exists: i64 = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM boards WHERE id = ?1 AND deleted_at IS NULL)",
+        params![board_id],
+        |row| row.get(0),
+    )?;
+    Ok(exists != 0)
+}
 
-    Ok(None)
+/// Whether a board id is safe to use in file names: non-empty and only `[A-Za-z0-9_-]`.
+/// Generated ids are UUIDs; ids from import files are only kept if they pass this.
+pub(crate) fn is_safe_board_id(board_id: &str) -> bool {
+    !board_id.is_empty()
+        && board_id
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_".contains(character))
 }
 
-fn board_id_from_index_item(
-    conn: &Connection,
-    item_type: &str,
-    item_id: &str,
-) -> Result<Option<String>, String> {
-    match item_type {
-        "board" => Ok(Some(item_id.to_string())),
-        "folder" => conn
-            .query_row(
-                "SELECT board_id FROM folder_items WHERE folder_id = ?1 ORDER BY position ASC LIMIT 1",
-                params![item_id],
-                |row| row.get(0),
-            )
-            .optional()
-            .map_err(|e| e.to_string()),
-        _ => Ok(None),
-    }
+pub(crate) fn first_board_id_from_db(conn: &Connection) -> AppResult<Option<String>> {
+    let mut expanded = HashSet::new();
+    first_board_id_in_rows(
+        conn,
+        "SELECT item_type, item_id FROM index_items ORDER BY position ASC",
+        params![],
+        &mut expanded,
+    )
 }
 
-fn load_folder_boards(
+/// Depth-first search for the first board among `(item_type, item_id)` rows. `expanded` holds
+/// the folders already visited so a cyclic `folder_items` graph cannot recurse forever.
+fn first_board_id_in_rows(
     conn: &Connection,
-    folder_id: &str,
-    boards: &HashMap<String, Board>,
-) -> Result<Vec<Board>, String> {
-    let mut stmt = conn
-        .prepare("SELECT board_id FROM folder_items WHERE folder_id = ?1 ORDER BY position ASC")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(params![folder_id]).map_err(|e| e.to_string())?;
+    sql: &str,
+    query_params: impl rusqlite::Params,
+    expanded: &mut HashSet<String>,
+) -> AppResult<Option<String>> {
+    let rows: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(query_params, |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
 
-    let mut items = Vec::new();
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let board_id: String = row.get(0).map_err(|e| e.to_string())?;
-        if let Some(board) = boards.get(&board_id) {
-            items.push(board.clone());
+    for (item_type, item_id) in rows {
+        match item_type.as_str() {
+            "board" => return Ok(Some(item_id)),
+            "folder" if expanded.insert(item_id.clone()) => {
+                let board_id = first_board_id_in_rows(
+                    conn,
+                    "SELECT item_type, item_id FROM folder_items
+                     WHERE folder_id = ?1 ORDER BY position ASC",
+                    params![item_id],
+                    expanded,
+                )?;
+                if board_id.is_some() {
+                    return Ok(board_id);
+                }
+            }
+            _ => {}
         }
     }
 
-    Ok(items)
+    Ok(None)
 }
 
-pub(crate) fn load_boards_index_from_db(conn: &Connection) -> Result<BoardsIndex, String> {
+pub(crate) fn load_boards_index_from_db(conn: &Connection) -> AppResult<BoardsIndex> {
     let boards = load_boards_map(conn)?;
     let folder_names = load_folder_names_map(conn)?;
-    let items = load_index_items(conn, &boards, &folder_names)?;
+    let smart_folders = load_smart_folders_map(conn, &boards)?;
+    let items = load_index_items(conn, &boards, &folder_names, &smart_folders)?;
     let active_board_id = get_setting(conn, "active_board_id")?;
 
     Ok(BoardsIndex {
         items,
         active_board_id,
+        pinned: pinned_boards(&boards),
     })
 }
 
-fn load_boards_map(conn: &Connection) -> Result<HashMap<String, Board>, String> {
+/// The pinned boards among `boards`, sorted by name.
+pub(crate) fn pinned_boards(boards: &HashMap<String, Board>) -> Vec<Board> {
+    let mut pinned: Vec<Board> = boards
+        .values()
+        .filter(|board| board.pinned)
+        .cloned()
+        .collect();
+    pinned.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    pinned
+}
+
+/// The `boards` columns `board_from_row` reads, in order.
+const BOARD_COLUMNS: &str =
+    "id, name, created_at, updated_at, collaboration_link, thumbnail, pinned, last_opened_at";
+
+/// A board without its tags, from a row selecting `BOARD_COLUMNS`.
+fn board_from_row(row: &rusqlite::Row<'_>) -> AppResult<Board> {
+    Ok(Board {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: datetime_from_millis(row.get(2)?)?,
+        updated_at: datetime_from_millis(row.get(3)?)?,
+        collaboration_link: row.get(4)?,
+        thumbnail: row.get(5)?,
+        tags: Vec::new(),
+        pinned: row.get(6)?,
+        last_opened_at: optional_datetime_from_millis(row.get(7)?)?,
+    })
+}
+
+pub(crate) fn load_boards_map(conn: &Connection) -> AppResult<HashMap<String, Board>> {
     let mut boards = HashMap::new();
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, created_at, updated_at, collaboration_link, thumbnail FROM boards",
-        )
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let created_at_ms: i64 = row.get(2).map_err(|e| e.to_string())?;
-        let updated_at_ms: i64 = row.get(3).map_err(|e| e.to_string())?;
-        let board = Board {
-            id: row.get(0).map_err(|e| e.to_string())?,
-            name: row.get(1).map_err(|e| e.to_string())?,
-            created_at: datetime_from_millis(created_at_ms)?,
-            updated_at: datetime_from_millis(updated_at_ms)?,
-            collaboration_link: row.get(4).map_err(|e| e.to_string())?,
-            thumbnail: row.get(5).map_err(|e| e.to_string())?,
-        };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {BOARD_COLUMNS} FROM boards WHERE deleted_at IS NULL"
+    ))?;
+    let mut rows = stmt.query([])?;
+
+    while let Some(row) = rows.next()? {
+        let board = board_from_row(row)?;
         boards.insert(board.id.clone(), board);
     }
 
+    let mut stmt = conn.prepare(
+        "SELECT bt.board_id, t.id, t.name, t.color FROM board_tags bt
+             JOIN tags t ON t.id = bt.tag_id
+             ORDER BY t.name COLLATE NOCASE, t.id",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let board_id: String = row.get(0)?;
+        if let Some(board) = boards.get_mut(&board_id) {
+            board.tags.push(tag_from_row(row, 1)?);
+        }
+    }
+
     Ok(boards)
 }
 
-fn load_folder_names_map(conn: &Connection) -> Result<HashMap<String, String>, String> {
+/// Tags of one board, sorted by name.
+pub(crate) fn load_board_tags(conn: &Connection, board_id: &str) -> AppResult<Vec<Tag>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.color FROM board_tags bt
+             JOIN tags t ON t.id = bt.tag_id
+             WHERE bt.board_id = ?1
+             ORDER BY t.name COLLATE NOCASE, t.id",
+    )?;
+    let rows = stmt.query_map(params![board_id], |row| tag_from_row(row, 0))?;
+    rows.collect::<Result<_, _>>().map_err(AppError::from)
+}
+
+/// Reads `id, name, color` starting at column `first`.
+pub(crate) fn tag_from_row(row: &rusqlite::Row<'_>, first: usize) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(first)?,
+        name: row.get(first + 1)?,
+        color: row.get(first + 2)?,
+    })
+}
+
+fn load_folder_names_map(conn: &Connection) -> AppResult<HashMap<String, String>> {
     let mut folder_names = HashMap::new();
-    let mut stmt = conn
-        .prepare("SELECT id, name FROM folders")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let id: String = row.get(0).map_err(|e| e.to_string())?;
-        let name: String = row.get(1).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare("SELECT id, name FROM folders")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let id: String = row.get(0)?;
+        let name: String = row.get(1)?;
         folder_names.insert(id, name);
     }
 
     Ok(folder_names)
 }
 
+/// Smart folders with their boards already evaluated. A folder whose stored query no longer
+/// parses is left out rather than failing the whole index.
+fn load_smart_folders_map(
+    conn: &Connection,
+    boards: &HashMap<String, Board>,
+) -> AppResult<HashMap<String, SmartFolder>> {
+    let now = Utc::now();
+    let mut smart_folders = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, name, query FROM smart_folders")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let query_json: String = row.get(2)?;
+        let Ok(query) = serde_json::from_str::<SmartFolderQuery>(&query_json) else {
+            continue;
+        };
+        let smart_folder = SmartFolder {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            boards: evaluate_smart_folder_query(&query, boards, now),
+            query,
+        };
+        smart_folders.insert(smart_folder.id.clone(), smart_folder);
+    }
+
+    Ok(smart_folders)
+}
+
+/// The boards meeting every condition of `query`, most recently updated first.
+pub(crate) fn evaluate_smart_folder_query(
+    query: &SmartFolderQuery,
+    boards: &HashMap<String, Board>,
+    now: DateTime<Utc>,
+) -> Vec<Board> {
+    let mut matches: Vec<Board> = boards
+        .values()
+        .filter(|board| {
+            query
+                .conditions
+                .iter()
+                .all(|condition| board_meets_condition(board, condition, now))
+        })
+        .cloned()
+        .collect();
+    matches.sort_by(|a, b| {
+        b.updated_at
+            .cmp(&a.updated_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    matches
+}
+
+fn board_meets_condition(
+    board: &Board,
+    condition: &SmartFolderCondition,
+    now: DateTime<Utc>,
+) -> bool {
+    match condition {
+        SmartFolderCondition::Tag { tag_id } => board.tags.iter().any(|tag| &tag.id == tag_id),
+        SmartFolderCondition::UpdatedWithinDays { days } => {
+            board.updated_at >= now - chrono::Duration::days(i64::from(*days))
+        }
+        SmartFolderCondition::NameContains { text } => {
+            board.name.to_lowercase().contains(&text.to_lowercase())
+        }
+        SmartFolderCondition::HasCollaborationLink => board
+            .collaboration_link
+            .as_deref()
+            .is_some_and(|link| !link.trim().is_empty()),
+    }
+}
+
 fn load_index_items(
     conn: &Connection,
     boards: &HashMap<String, Board>,
     folder_names: &HashMap<String, String>,
-) -> Result<Vec<BoardListItem>, String> {
+    smart_folders: &HashMap<String, SmartFolder>,
+) -> AppResult<Vec<BoardListItem>> {
+    let mut expanded = HashSet::new();
+    load_items(
+        conn,
+        "SELECT item_type, item_id FROM index_items ORDER BY position ASC",
+        params![],
+        boards,
+        folder_names,
+        smart_folders,
+        &mut expanded,
+    )
+}
+
+fn load_items(
+    conn: &Connection,
+    sql: &str,
+    query_params: impl rusqlite::Params,
+    boards: &HashMap<String, Board>,
+    folder_names: &HashMap<String, String>,
+    smart_folders: &HashMap<String, SmartFolder>,
+    expanded: &mut HashSet<String>,
+) -> AppResult<Vec<BoardListItem>> {
+    let rows: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(query_params, |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
     let mut items = Vec::new();
-    let mut stmt = conn
-        .prepare("SELECT item_type, item_id FROM index_items ORDER BY position ASC")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-
-    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let item_type: String = row.get(0).map_err(|e| e.to_string())?;
-        let item_id: String = row.get(1).map_err(|e| e.to_string())?;
-
-        if let Some(item) =
-            board_list_item_from_index_row(conn, &item_type, &item_id, boards, folder_names)?
-        {
+    for (item_type, item_id) in rows {
+        if let Some(item) = board_list_item_from_index_row(
+            conn,
+            &item_type,
+            &item_id,
+            boards,
+            folder_names,
+            smart_folders,
+            expanded,
+        )? {
             items.push(item);
         }
     }
@@ -349,27 +461,49 @@ fn board_list_item_from_index_row(
     item_id: &str,
     boards: &HashMap<String, Board>,
     folder_names: &HashMap<String, String>,
-) -> Result<Option<BoardListItem>, String> {
+    smart_folders: &HashMap<String, SmartFolder>,
+    expanded: &mut HashSet<String>,
+) -> AppResult<Option<BoardListItem>> {
     match item_type {
         "board" => Ok(boards
             .get(item_id)
             .map(|board| BoardListItem::Board(board.clone()))),
-        "folder" => folder_item_from_index_row(conn, item_id, boards, folder_names),
+        "folder" => {
+            folder_item_from_index_row(conn, item_id, boards, folder_names, smart_folders, expanded)
+        }
+        "smart_folder" => Ok(smart_folders
+            .get(item_id)
+            .map(|smart_folder| BoardListItem::SmartFolder(smart_folder.clone()))),
         _ => Ok(None),
     }
 }
 
+/// Loads a folder and everything nested in it. A folder is listed at most once, so a row that
+/// would place it a second time (or inside itself) is ignored.
 fn folder_item_from_index_row(
     conn: &Connection,
     folder_id: &str,
     boards: &HashMap<String, Board>,
     folder_names: &HashMap<String, String>,
-) -> Result<Option<BoardListItem>, String> {
+    smart_folders: &HashMap<String, SmartFolder>,
+    expanded: &mut HashSet<String>,
+) -> AppResult<Option<BoardListItem>> {
     let Some(name) = folder_names.get(folder_id) else {
         return Ok(None);
     };
+    if !expanded.insert(folder_id.to_string()) {
+        return Ok(None);
+    }
 
-    let folder_items = load_folder_boards(conn, folder_id, boards)?;
+    let folder_items = load_items(
+        conn,
+        "SELECT item_type, item_id FROM folder_items WHERE folder_id = ?1 ORDER BY position ASC",
+        params![folder_id],
+        boards,
+        folder_names,
+        smart_folders,
+        expanded,
+    )?;
     if folder_items.is_empty() {
         return Ok(None);
     }
@@ -384,7 +518,7 @@ fn folder_item_from_index_row(
 pub(crate) fn normalize_active_board_id(
     conn: &Connection,
     mut index: BoardsIndex,
-) -> Result<BoardsIndex, String> {
+) -> AppResult<BoardsIndex> {
     let next_active = match index.active_board_id.clone() {
         Some(id) if board_exists(&index.items, &id) => Some(id),
         _ => first_board_id(&index.items),
@@ -398,71 +532,171 @@ pub(crate) fn normalize_active_board_id(
     Ok(index)
 }
 
-pub(crate) fn get_board_by_id(conn: &Connection, board_id: &str) -> Result<Board, String> {
-    let (
-        id,
-        name,
-        created_at_ms,
-        updated_at_ms,
-        collaboration_link,
-        thumbnail,
-    ): (String, String, i64, i64, Option<String>, Option<String>) = conn
+pub(crate) fn get_board_by_id(conn: &Connection, board_id: &str) -> AppResult<Board> {
+    let mut stmt = conn.prepare(&format!("SELECT {BOARD_COLUMNS} FROM boards WHERE id = ?1"))?;
+    let mut rows = stmt.query(params![board_id])?;
+    let row = rows
+        .next()?
+        .ok_or_else(|| AppError::board_not_found(board_id))?;
+
+    let mut board = board_from_row(row)?;
+    board.tags = load_board_tags(conn, board_id)?;
+    Ok(board)
+}
+
+/// Number of pending patches after which `append_board_data_patch` folds them into the
+/// stored scene. Each load replays the pending patches, so this bounds the replay cost.
+const PATCH_COMPACTION_THRESHOLD: i64 = 50;
+
+/// A board's current scene: the stored scene with any pending patches applied.
+pub(crate) fn load_board_data_value(
+    conn: &Connection,
+    board_id: &str,
+) -> AppResult<Option<String>> {
+    let Some(data) = conn
         .query_row(
-            "SELECT id, name, created_at, updated_at, collaboration_link, thumbnail FROM boards WHERE id = ?1",
+            "SELECT data, compression FROM board_data WHERE board_id = ?1",
             params![board_id],
-            |row| {
-                Ok((
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                ))
-            },
+            |row| StoredScene::from_row(row, 0, 1),
         )
-        .map_err(|e| e.to_string())?;
+        .optional()?
+        .map(StoredScene::decode)
+        .transpose()?
+    else {
+        return Ok(None);
+    };
 
-    Ok(Board {
-        id,
-        name,
-        created_at: datetime_from_millis(created_at_ms)?,
-        updated_at: datetime_from_millis(updated_at_ms)?,
-        collaboration_link,
-        thumbnail,
-    })
+    let patches = load_board_data_patches(conn, board_id)?;
+    if patches.is_empty() {
+        return Ok(Some(data));
+    }
+    let mut scene: serde_json::Value = serde_json::from_str(&data)?;
+    for patch in &patches {
+        scene = scene::apply_patch(&scene, patch);
+    }
+    Ok(Some(scene.to_string()))
 }
 
-pub(crate) fn load_board_data_value(
+fn load_board_data_patches(conn: &Connection, board_id: &str) -> AppResult<Vec<BoardDataPatch>> {
+    let mut stmt = conn.prepare(
+        "SELECT data, compression FROM board_data_patches WHERE board_id = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![board_id], |row| StoredScene::from_row(row, 0, 1))?;
+
+    let mut patches = Vec::new();
+    for stored in rows {
+        patches.push(serde_json::from_str(&stored?.decode()?)?);
+    }
+    Ok(patches)
+}
+
+/// Revision of a board's stored scene, bumped on every write. Boards without a stored scene
+/// are at revision 0.
+pub(crate) fn board_data_revision(conn: &Connection, board_id: &str) -> AppResult<i64> {
+    let revision = conn
+        .query_row(
+            "SELECT revision FROM board_data WHERE board_id = ?1",
+            params![board_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(revision.unwrap_or(0))
+}
+
+/// Stores a board's scene, compressed, and returns its new revision. Pending patches are
+/// superseded and dropped.
+pub(crate) fn write_board_data(conn: &Connection, board_id: &str, data: &str) -> AppResult<i64> {
+    conn.execute(
+        "DELETE FROM board_data_patches WHERE board_id = ?1",
+        params![board_id],
+    )?;
+    conn.query_row(
+        "INSERT INTO board_data (board_id, data, compression, revision) VALUES (?1, ?2, ?3, 1)
+         ON CONFLICT(board_id) DO UPDATE SET
+            data = excluded.data,
+            compression = excluded.compression,
+            revision = board_data.revision + 1
+         RETURNING revision",
+        params![board_id, encode_scene(data)?, COMPRESSION_ZSTD],
+        |row| row.get(0),
+    )
+    .map_err(AppError::from)
+}
+
+/// Records a patch against a board's stored scene and returns the new revision, or `None` if
+/// the board has no stored scene yet. Compacts once enough patches have piled up.
+pub(crate) fn append_board_data_patch(
     conn: &Connection,
     board_id: &str,
-) -> Result<Option<String>, String> {
-    conn.query_row(
-        "SELECT data FROM board_data WHERE board_id = ?1",
+    patch: &BoardDataPatch,
+    now_ms: i64,
+) -> AppResult<Option<i64>> {
+    let Some(revision) = conn
+        .query_row(
+            "UPDATE board_data SET revision = revision + 1 WHERE board_id = ?1
+             RETURNING revision",
+            params![board_id],
+            |row| row.get(0),
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    conn.execute(
+        "INSERT INTO board_data_patches (board_id, created_at, data, compression)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            board_id,
+            now_ms,
+            encode_scene(&serde_json::to_string(patch)?)?,
+            COMPRESSION_ZSTD
+        ],
+    )?;
+
+    let pending: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM board_data_patches WHERE board_id = ?1",
         params![board_id],
         |row| row.get(0),
-    )
-    .optional()
-    .map_err(|e| e.to_string())
+    )?;
+    if pending >= PATCH_COMPACTION_THRESHOLD {
+        compact_board_data(conn, board_id)?;
+    }
+    Ok(Some(revision))
 }
 
-pub(crate) fn board_exists(items: &[BoardListItem], board_id: &str) -> bool {
-    items.iter().any(|item| match item {
-        BoardListItem::Board(board) => board.id == board_id,
-        BoardListItem::Folder(folder) => folder.items.iter().any(|board| board.id == board_id),
+/// Folds a board's pending patches into its stored scene. The revision is unchanged.
+pub(crate) fn compact_board_data(conn: &Connection, board_id: &str) -> AppResult<()> {
+    let Some(data) = load_board_data_value(conn, board_id)? else {
+        return Ok(());
+    };
+    conn.execute(
+        "UPDATE board_data SET data = ?1, compression = ?2 WHERE board_id = ?3",
+        params![encode_scene(&data)?, COMPRESSION_ZSTD, board_id],
+    )?;
+    conn.execute(
+        "DELETE FROM board_data_patches WHERE board_id = ?1",
+        params![board_id],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn find_board<'a>(items: &'a [BoardListItem], board_id: &str) -> Option<&'a Board> {
+    items.iter().find_map(|item| match item {
+        BoardListItem::Board(board) => (board.id == board_id).then_some(board),
+        BoardListItem::Folder(folder) => find_board(&folder.items, board_id),
+        BoardListItem::SmartFolder(_) => None,
     })
 }
 
+pub(crate) fn board_exists(items: &[BoardListItem], board_id: &str) -> bool {
+    find_board(items, board_id).is_some()
+}
+
 pub(crate) fn first_board_id(items: &[BoardListItem]) -> Option<String> {
-    for item in items {
-        match item {
-            BoardListItem::Board(board) => return Some(board.id.clone()),
-            BoardListItem::Folder(folder) => {
-                if let Some(board) = folder.items.first() {
-                    return Some(board.id.clone());
-                }
-            }
-        }
-    }
-    None
+    items.iter().find_map(|item| match item {
+        BoardListItem::Board(board) => Some(board.id.clone()),
+        BoardListItem::Folder(folder) => first_board_id(&folder.items),
+        BoardListItem::SmartFolder(_) => None,
+    })
 }
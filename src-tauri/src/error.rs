@@ -0,0 +1,172 @@
+use rusqlite::ErrorCode;
+use serde::Serialize;
+use std::fmt;
+
+/// Error returned by every command. Serialized as `{ kind, message, board_id? }` so the
+/// frontend can branch on `kind` instead of matching message text.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum AppError {
+    /// The board, folder or revision does not exist, e.g. because another window deleted it.
+    NotFound {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        board_id: Option<String>,
+    },
+    /// The write was based on state that has since changed. For scene saves,
+    /// `current_revision` is the revision now stored.
+    Conflict {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        board_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current_revision: Option<i64>,
+    },
+    /// The request or an imported file is malformed.
+    Validation {
+        message: String,
+    },
+    /// Reading or writing files failed, including the disk being full.
+    Io {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        board_id: Option<String>,
+    },
+    Database {
+        message: String,
+    },
+    Migration {
+        message: String,
+    },
+}
+
+pub(crate) type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub(crate) fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound {
+            message: message.into(),
+            board_id: None,
+        }
+    }
+
+    pub(crate) fn board_not_found(board_id: &str) -> Self {
+        Self::not_found("Board not found").with_board_id(board_id)
+    }
+
+    pub(crate) fn conflict(message: impl Into<String>) -> Self {
+        Self::Conflict {
+            message: message.into(),
+            board_id: None,
+            current_revision: None,
+        }
+    }
+
+    pub(crate) fn stale_board_data(board_id: &str, current_revision: i64) -> Self {
+        Self::Conflict {
+            message: "Board was changed since it was loaded".to_string(),
+            board_id: Some(board_id.to_string()),
+            current_revision: Some(current_revision),
+        }
+    }
+
+    pub(crate) fn validation(message: impl Into<String>) -> Self {
+        Self::Validation {
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self::Io {
+            message: message.into(),
+            board_id: None,
+        }
+    }
+
+    pub(crate) fn database(message: impl Into<String>) -> Self {
+        Self::Database {
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn migration(message: impl Into<String>) -> Self {
+        Self::Migration {
+            message: message.into(),
+        }
+    }
+
+    /// Records which board the error concerns. Kinds that are never board-specific are
+    /// returned unchanged.
+    pub(crate) fn with_board_id(mut self, id: &str) -> Self {
+        if let Self::NotFound { board_id, .. }
+        | Self::Conflict { board_id, .. }
+        | Self::Io { board_id, .. } = &mut self
+        {
+            *board_id = Some(id.to_string());
+        }
+        self
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::NotFound { message, .. }
+            | Self::Conflict { message, .. }
+            | Self::Validation { message }
+            | Self::Io { message, .. }
+            | Self::Database { message }
+            | Self::Migration { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(error: rusqlite::Error) -> Self {
+        match error.sqlite_error_code() {
+            Some(ErrorCode::DiskFull) => Self::io(error.to_string()),
+            Some(ErrorCode::ConstraintViolation) => Self::conflict(error.to_string()),
+            _ if matches!(error, rusqlite::Error::QueryReturnedNoRows) => {
+                Self::not_found("Record not found")
+            }
+            _ => Self::database(error.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        Self::io(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            Self::io(error.to_string())
+        } else {
+            Self::validation(error.to_string())
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for AppError {
+    fn from(error: zip::result::ZipError) -> Self {
+        match error {
+            zip::result::ZipError::Io(error) => error.into(),
+            error => Self::validation(error.to_string()),
+        }
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        Self::io(error.to_string())
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::db::{get_boards_dir, is_safe_board_id};
+use crate::error::{AppError, AppResult};
+use crate::models::BoardDataPatch;
+
+const JOURNAL_SUBDIR: &str = "journal";
+const JOURNAL_EXTENSION: &str = "jsonl";
+/// Every entry supersedes the ones before it, so a journal that grows past this (because
+/// saves keep failing) is rewritten with just its newest entry.
+const JOURNAL_COMPACT_BYTES: u64 = 1024 * 1024;
+
+/// One line of a board's journal: every change made since the save that produced
+/// `base_revision`, as a single cumulative patch.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct JournalEntry {
+    pub(crate) written_at: i64,
+    pub(crate) base_revision: i64,
+    pub(crate) patch: BoardDataPatch,
+}
+
+fn journal_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    let dir = get_boards_dir(app)?.join(JOURNAL_SUBDIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn journal_path(app: &AppHandle, board_id: &str) -> AppResult<PathBuf> {
+    if !is_safe_board_id(board_id) {
+        return Err(AppError::validation("Invalid board id"));
+    }
+    Ok(journal_dir(app)?.join(format!("{board_id}.{JOURNAL_EXTENSION}")))
+}
+
+/// Appends an entry to a board's journal and waits for it to reach the disk.
+pub(crate) fn append(app: &AppHandle, board_id: &str, entry: &JournalEntry) -> AppResult<()> {
+    let path = journal_path(app, board_id)?;
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+
+    let oversized =
+        fs::metadata(&path).is_ok_and(|metadata| metadata.len() > JOURNAL_COMPACT_BYTES);
+    if oversized {
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, &line)?;
+        fs::File::open(&temp_path)?.sync_all()?;
+        fs::rename(&temp_path, &path)?;
+        return Ok(());
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(&line)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// The newest entry that still applies to the stored scene at `revision`. A torn last line
+/// from a crash mid-write is skipped, as are entries written against an older save.
+pub(crate) fn latest_entry(
+    app: &AppHandle,
+    board_id: &str,
+    revision: i64,
+) -> AppResult<Option<JournalEntry>> {
+    let path = journal_path(app, board_id)?;
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+
+    Ok(contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+        .find(|entry| entry.base_revision == revision))
+}
+
+/// Ids of the boards that have a journal on disk.
+pub(crate) fn journaled_board_ids(app: &AppHandle) -> AppResult<Vec<String>> {
+    let mut board_ids = Vec::new();
+    for entry in fs::read_dir(journal_dir(app)?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(JOURNAL_EXTENSION) {
+            continue;
+        }
+        if let Some(board_id) = path.file_stem().and_then(|stem| stem.to_str()) {
+            board_ids.push(board_id.to_string());
+        }
+    }
+    Ok(board_ids)
+}
+
+/// Deletes a board's journal, once its changes are saved or discarded. A board whose id is not
+/// safe in a file name cannot have a journal, so there is nothing to delete.
+pub(crate) fn clear(app: &AppHandle, board_id: &str) -> AppResult<()> {
+    if !is_safe_board_id(board_id) {
+        return Ok(());
+    }
+    let path = journal_path(app, board_id)?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
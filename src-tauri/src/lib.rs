@@ -1,17 +1,61 @@
+mod assets;
+mod backups;
 mod commands;
+mod compression;
 mod db;
+mod error;
+mod journal;
+mod migrations;
 mod models;
+mod scene;
+mod scene_merge;
+mod search_index;
 mod thumbnails;
 
+use crate::commands::backups::{
+    create_backup, get_backup_settings, list_backups, restore_backup, set_backup_settings,
+};
+use crate::commands::board_archive::export_boards_archive;
 use crate::commands::board_content::{
-    load_board_data, save_board_data, save_board_thumbnail, set_collaboration_link,
+    load_board_data, merge_board_data, patch_board_data, save_board_data, save_board_thumbnail,
+    set_collaboration_link,
+};
+use crate::commands::board_journal::{
+    append_board_journal, discard_board_journal, list_board_journals, recover_board_journal,
+};
+use crate::commands::board_revisions::{
+    list_board_revisions, load_board_revision, restore_board_revision,
+};
+use crate::commands::board_search::search_boards;
+use crate::commands::board_transfer::{
+    export_boards, import_boards, preview_boards_import, read_boards_import_file,
 };
-use crate::commands::board_transfer::{export_boards, import_boards};
 use crate::commands::boards::{
-    create_board, delete_board, duplicate_board, get_boards, rename_board, set_active_board,
-    set_boards_index,
+    create_board, delete_board, duplicate_board, get_boards, get_recent_boards, rename_board,
+    set_active_board, set_board_pinned, set_boards_index,
 };
+use crate::commands::excalidraw_files::{export_board_excalidraw, import_excalidraw_files};
+use crate::commands::folders::{
+    create_folder, delete_folder, move_board, move_folder, rename_folder,
+};
+use crate::commands::integrity::{check_integrity, repair_integrity};
+use crate::commands::smart_folders::{
+    create_smart_folder, delete_smart_folder, move_smart_folder, update_smart_folder,
+};
+use crate::commands::startup::{take_startup_errors, StartupErrors};
 use crate::commands::system_tests::{get_system_test_export_path, get_system_test_import_path};
+use crate::commands::tags::{
+    add_board_tag, create_tag, delete_tag, list_boards_by_tags, list_tags, remove_board_tag,
+    rename_tag, set_tag_color,
+};
+use crate::commands::thumbnail_cache::{
+    collect_thumbnail_garbage, get_thumbnail_cache_limit, run_thumbnail_gc,
+    set_thumbnail_cache_limit,
+};
+use crate::commands::trash::{
+    empty_trash, get_trash_retention_days, list_trash, purge_board, purge_expired_trash,
+    restore_board, restore_folder, set_trash_retention_days,
+};
 use crate::commands::ui_preferences::{get_ui_preferences, set_ui_preference};
 use tauri::{Emitter, Listener, Manager};
 
@@ -32,7 +76,22 @@ pub fn run() {
     }));
 
     builder
+        .register_uri_scheme_protocol(thumbnails::THUMBNAIL_SCHEME, |ctx, request| {
+            thumbnails::serve_thumbnail(ctx.app_handle(), &request)
+        })
         .setup(|app| {
+            let mut conn = db::open_db(app.handle())?;
+            let mut startup_errors = Vec::new();
+            if let Err(error) = purge_expired_trash(app.handle(), &mut conn) {
+                startup_errors.push(error);
+            }
+            if let Err(error) = run_thumbnail_gc(app.handle(), &conn) {
+                startup_errors.push(error);
+            }
+            app.manage(db::Database::new(conn));
+            app.manage(StartupErrors::new(startup_errors));
+            backups::start_scheduler(app.handle().clone());
+
             // Handle deep links - when the app is opened via a URL
             #[cfg(desktop)]
             {
@@ -48,18 +107,70 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             get_boards,
             get_ui_preferences,
+            take_startup_errors,
             create_board,
             rename_board,
             delete_board,
             set_active_board,
+            set_board_pinned,
+            get_recent_boards,
             save_board_data,
             load_board_data,
+            merge_board_data,
+            patch_board_data,
+            append_board_journal,
+            list_board_journals,
+            recover_board_journal,
+            discard_board_journal,
+            list_board_revisions,
+            load_board_revision,
+            restore_board_revision,
+            list_backups,
+            create_backup,
+            restore_backup,
+            get_backup_settings,
+            set_backup_settings,
+            check_integrity,
+            repair_integrity,
+            search_boards,
             set_collaboration_link,
             duplicate_board,
+            list_trash,
+            create_folder,
+            rename_folder,
+            delete_folder,
+            move_board,
+            move_folder,
+            create_smart_folder,
+            update_smart_folder,
+            delete_smart_folder,
+            move_smart_folder,
+            list_tags,
+            create_tag,
+            rename_tag,
+            set_tag_color,
+            delete_tag,
+            add_board_tag,
+            remove_board_tag,
+            list_boards_by_tags,
+            restore_board,
+            restore_folder,
+            purge_board,
+            empty_trash,
+            get_trash_retention_days,
+            set_trash_retention_days,
             set_boards_index,
             export_boards,
             import_boards,
+            preview_boards_import,
+            export_boards_archive,
+            read_boards_import_file,
+            export_board_excalidraw,
+            import_excalidraw_files,
             save_board_thumbnail,
+            collect_thumbnail_garbage,
+            get_thumbnail_cache_limit,
+            set_thumbnail_cache_limit,
             set_ui_preference,
             get_system_test_export_path,
             get_system_test_import_path
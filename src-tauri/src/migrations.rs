@@ -0,0 +1,590 @@
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use std::fs;
+use std::path::Path;
+
+use crate::compression::{encode_scene, COMPRESSION_NONE, COMPRESSION_ZSTD};
+use crate::error::{AppError, AppResult};
+
+/// One schema change. `up` runs inside a transaction together with the `user_version` bump, so
+/// a failed step leaves the database at the previous version. Steps must also cope with tables
+/// created by builds that predate the registry, hence the `IF NOT EXISTS` clauses and
+/// `add_column`.
+/// `up` gets the boards directory, for steps that move data out to files. `reclaims_space`
+/// asks for a `VACUUM` once all pending steps have run.
+struct Migration {
+    version: i64,
+    description: &'static str,
+    up: fn(&Connection, &Path) -> AppResult<()>,
+    reclaims_space: bool,
+}
+
+/// Ordered by version, starting at 1 with no gaps. Append new steps; never edit applied ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create boards, folders, index and settings tables",
+        up: create_base_tables,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 2,
+        description: "Move inline thumbnails to files",
+        up: migrate_thumbnails_to_files,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 3,
+        description: "Add board revisions and the trash",
+        up: add_revisions_and_trash,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 4,
+        description: "Add the full-text search index",
+        up: add_search_index,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 5,
+        description: "Move embedded images into the asset store",
+        up: add_asset_store,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 6,
+        description: "Compress stored scenes",
+        up: compress_stored_scenes,
+        reclaims_space: true,
+    },
+    Migration {
+        version: 7,
+        description: "Track scene revisions",
+        up: add_scene_revisions,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 8,
+        description: "Add the scene patch log",
+        up: add_scene_patches,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 9,
+        description: "Nest folders",
+        up: nest_folders,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 10,
+        description: "Add board tags",
+        up: add_board_tags,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 11,
+        description: "Add smart folders",
+        up: add_smart_folders,
+        reclaims_space: false,
+    },
+    Migration {
+        version: 12,
+        description: "Track pinned and recently opened boards",
+        up: add_pinned_and_last_opened,
+        reclaims_space: false,
+    },
+];
+
+pub(crate) const LATEST_SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Brings the database at `db_path` up to `LATEST_SCHEMA_VERSION`. Refuses databases written
+/// by a newer build, and copies an existing database next to itself before changing it.
+pub(crate) fn run_migrations(
+    conn: &Connection,
+    boards_dir: &Path,
+    db_path: &Path,
+) -> AppResult<()> {
+    let version = schema_version(conn)?;
+    if version > LATEST_SCHEMA_VERSION {
+        return Err(AppError::migration(format!(
+            "Database schema version {version} is newer than this app supports \
+             ({LATEST_SCHEMA_VERSION}). Please update ExcaStoneBoard."
+        )));
+    }
+    if version == LATEST_SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    if has_user_tables(conn)? {
+        backup_before_migration(conn, db_path, version)?;
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        );",
+    )?;
+
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > version).collect();
+    for migration in &pending {
+        apply_migration(conn, boards_dir, migration).map_err(|e| {
+            AppError::migration(format!(
+                "Migration to schema version {} failed: {e}",
+                migration.version
+            ))
+        })?;
+    }
+
+    if pending.iter().any(|migration| migration.reclaims_space) {
+        conn.execute_batch("VACUUM")?;
+    }
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> AppResult<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(AppError::from)
+}
+
+fn has_user_tables(conn: &Connection) -> AppResult<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'boards'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Writes a consistent copy of the database to `boards.db.v<version>.bak` before any
+/// migration touches it. A backup from an earlier attempt at the same version is replaced.
+fn backup_before_migration(conn: &Connection, db_path: &Path, version: i64) -> AppResult<()> {
+    let file_name = db_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("boards.db");
+    let backup_path = db_path.with_file_name(format!("{file_name}.v{version}.bak"));
+    if backup_path.exists() {
+        fs::remove_file(&backup_path)?;
+    }
+
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![backup_path.to_string_lossy().into_owned()],
+    )
+    .map_err(|e| AppError::migration(format!("Pre-migration backup failed: {e}")))?;
+    Ok(())
+}
+
+fn apply_migration(conn: &Connection, boards_dir: &Path, migration: &Migration) -> AppResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    (migration.up)(&tx, boards_dir)?;
+    tx.execute(
+        "INSERT OR REPLACE INTO schema_migrations (version, description, applied_at)
+         VALUES (?1, ?2, ?3)",
+        params![
+            migration.version,
+            migration.description,
+            Utc::now().timestamp_millis()
+        ],
+    )?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit().map_err(AppError::from)
+}
+
+fn create_base_tables(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS boards (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            collaboration_link TEXT,
+            thumbnail TEXT
+        );
+        CREATE TABLE IF NOT EXISTS folders (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS index_items (
+            position INTEGER NOT NULL,
+            item_type TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            PRIMARY KEY(position)
+        );
+        CREATE TABLE IF NOT EXISTS folder_items (
+            folder_id TEXT NOT NULL,
+            board_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY(folder_id, position),
+            UNIQUE(folder_id, board_id),
+            FOREIGN KEY(folder_id) REFERENCES folders(id) ON DELETE CASCADE,
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS board_data (
+            board_id TEXT PRIMARY KEY,
+            data TEXT NOT NULL,
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )
+    .map_err(AppError::from)
+}
+
+/// Thumbnails used to be stored as inline `data:` URLs in the `boards.thumbnail` TEXT column.
+/// This moves any such values to files under the app data directory and replaces the DB value
+/// with the relative path.
+fn migrate_thumbnails_to_files(conn: &Connection, boards_dir: &Path) -> AppResult<()> {
+    let mut stmt =
+        conn.prepare("SELECT id, thumbnail FROM boards WHERE thumbnail LIKE 'data:%'")?;
+    let mut rows = stmt.query([])?;
+
+    let mut updates: Vec<(String, Option<String>)> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let id: String = row.get(0)?;
+        let thumbnail: String = row.get(1)?;
+        let relative_path = crate::thumbnails::save_thumbnail_in(
+            boards_dir,
+            crate::thumbnails::BoardId::from(id.as_str()),
+            Some(&thumbnail),
+        )?;
+        updates.push((id, relative_path));
+    }
+    drop(rows);
+    drop(stmt);
+
+    for (id, relative_path) in updates {
+        conn.execute(
+            "UPDATE boards SET thumbnail = ?1 WHERE id = ?2",
+            params![relative_path, id],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn add_revisions_and_trash(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS board_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            board_id TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            element_count INTEGER NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_revisions_board
+            ON board_revisions(board_id, created_at);
+        CREATE TABLE IF NOT EXISTS board_trash (
+            board_id TEXT PRIMARY KEY,
+            folder_id TEXT,
+            folder_name TEXT,
+            folder_position INTEGER,
+            position INTEGER,
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );",
+    )?;
+    add_column(conn, "boards", "deleted_at", "INTEGER")
+}
+
+fn add_search_index(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS board_search USING fts5(
+            board_id UNINDEXED,
+            element_id UNINDEXED,
+            content,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );",
+    )?;
+
+    // Scenes are still plain text at this version; later migrations compress them.
+    let boards: Vec<(String, String, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT boards.id, boards.name, board_data.data FROM boards
+                 LEFT JOIN board_data ON board_data.board_id = boards.id",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for (board_id, name, data) in boards {
+        crate::search_index::index_board_name(conn, &board_id, &name)?;
+        if let Some(data) = data {
+            crate::search_index::index_board_scene(conn, &board_id, &data)?;
+        }
+    }
+    Ok(())
+}
+
+fn add_asset_store(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS assets (
+            hash TEXT PRIMARY KEY,
+            mime TEXT NOT NULL,
+            data BLOB NOT NULL,
+            size_bytes INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS board_assets (
+            board_id TEXT NOT NULL,
+            file_id TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            PRIMARY KEY(board_id, file_id),
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_assets_hash ON board_assets(hash);",
+    )?;
+
+    let scenes: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT board_id, data FROM board_data")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for (board_id, data) in scenes {
+        let stored = crate::assets::extract_assets(conn, &board_id, &data)?;
+        if stored != data {
+            conn.execute(
+                "UPDATE board_data SET data = ?1 WHERE board_id = ?2",
+                params![stored, board_id],
+            )?;
+        }
+    }
+
+    let revisions: Vec<(i64, String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, board_id, data FROM board_revisions")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for (revision_id, board_id, data) in revisions {
+        let stored = crate::assets::extract_assets(conn, &board_id, &data)?;
+        if stored != data {
+            conn.execute(
+                "UPDATE board_revisions SET data = ?1, size_bytes = ?2 WHERE id = ?3",
+                params![stored, stored.len() as i64, revision_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds the `compression` flag to both scene tables and compresses the rows already there.
+/// Existing rows default to `COMPRESSION_NONE`, so a half-finished run is never ambiguous.
+fn compress_stored_scenes(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    let compression = format!("INTEGER NOT NULL DEFAULT {COMPRESSION_NONE}");
+    add_column(conn, "board_data", "compression", &compression)?;
+    add_column(conn, "board_revisions", "compression", &compression)?;
+
+    let scenes: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT board_id, data FROM board_data")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for (board_id, data) in scenes {
+        conn.execute(
+            "UPDATE board_data SET data = ?1, compression = ?2 WHERE board_id = ?3",
+            params![encode_scene(&data)?, COMPRESSION_ZSTD, board_id],
+        )?;
+    }
+
+    let revisions: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, data FROM board_revisions")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    for (revision_id, data) in revisions {
+        conn.execute(
+            "UPDATE board_revisions SET data = ?1, compression = ?2 WHERE id = ?3",
+            params![encode_scene(&data)?, COMPRESSION_ZSTD, revision_id],
+        )?;
+    }
+    Ok(())
+}
+
+fn add_scene_revisions(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    add_column(conn, "board_data", "revision", "INTEGER NOT NULL DEFAULT 0")
+}
+
+fn add_scene_patches(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS board_data_patches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            board_id TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            compression INTEGER NOT NULL,
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_data_patches_board
+            ON board_data_patches(board_id, id);",
+    )
+    .map_err(AppError::from)
+}
+
+/// Folders get a parent, and `folder_items` is rebuilt to hold folders as well as boards, typed
+/// the same way as `index_items`. Trashed boards remember the whole folder path they came from
+/// as a JSON array of `{ id, name, position }`, outermost first.
+fn nest_folders(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    add_column(
+        conn,
+        "folders",
+        "parent_folder_id",
+        "TEXT REFERENCES folders(id) ON DELETE CASCADE",
+    )?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_folders_parent ON folders(parent_folder_id);
+        CREATE TABLE folder_items_nested (
+            folder_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            item_type TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            PRIMARY KEY(folder_id, position),
+            UNIQUE(folder_id, item_type, item_id),
+            FOREIGN KEY(folder_id) REFERENCES folders(id) ON DELETE CASCADE
+        );
+        INSERT INTO folder_items_nested (folder_id, position, item_type, item_id)
+            SELECT folder_id, position, 'board', board_id FROM folder_items;
+        DROP TABLE folder_items;
+        ALTER TABLE folder_items_nested RENAME TO folder_items;
+        CREATE INDEX IF NOT EXISTS idx_folder_items_item ON folder_items(item_type, item_id);",
+    )?;
+    add_column(conn, "board_trash", "folder_path", "TEXT")
+}
+
+/// Tag names are unique regardless of case.
+fn add_board_tags(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS board_tags (
+            board_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY(board_id, tag_id),
+            FOREIGN KEY(board_id) REFERENCES boards(id) ON DELETE CASCADE,
+            FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_board_tags_tag ON board_tags(tag_id, board_id);",
+    )
+    .map_err(AppError::from)
+}
+
+/// Smart folders are placed through `index_items` and `folder_items` with the item type
+/// `smart_folder`; `query` holds the `SmartFolderQuery` as JSON.
+fn add_smart_folders(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS smart_folders (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL
+        );",
+    )
+    .map_err(AppError::from)
+}
+
+fn add_pinned_and_last_opened(conn: &Connection, _boards_dir: &Path) -> AppResult<()> {
+    add_column(conn, "boards", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(conn, "boards", "last_opened_at", "INTEGER")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_boards_last_opened ON boards(last_opened_at);",
+    )
+    .map_err(AppError::from)
+}
+
+/// `ALTER TABLE ... ADD COLUMN` that skips columns already there, as `deleted_at` is on
+/// databases a pre-registry build moved to version 3.
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> AppResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn migrate(conn: &Connection, dir: &Path) -> AppResult<()> {
+        run_migrations(conn, dir, &dir.join("boards.db"))
+    }
+
+    #[test]
+    fn migrates_the_baseline_schema_and_keeps_its_boards() {
+        let dir = scratch_dir();
+        let conn = Connection::open_in_memory().unwrap();
+        create_base_tables(&conn, &dir).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO boards (id, name, created_at, updated_at)
+                VALUES ('board', 'Board', 1, 1);
+            INSERT INTO board_data (board_id, data) VALUES ('board', '{"elements":[]}');
+            INSERT INTO folders (id, name) VALUES ('folder', 'Folder');
+            INSERT INTO folder_items (folder_id, board_id, position) VALUES ('folder', 'board', 0);
+            INSERT INTO index_items (position, item_type, item_id)
+                VALUES (0, 'folder', 'folder');"#,
+        )
+        .unwrap();
+
+        migrate(&conn, &dir).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), LATEST_SCHEMA_VERSION);
+        assert_eq!(
+            crate::db::load_board_data_value(&conn, "board")
+                .unwrap()
+                .as_deref(),
+            Some(r#"{"elements":[]}"#)
+        );
+        let (deleted_at, pinned): (Option<i64>, bool) = conn
+            .query_row(
+                "SELECT deleted_at, pinned FROM boards WHERE id = 'board'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((deleted_at, pinned), (None, false));
+        let folder_item: (String, String) = conn
+            .query_row(
+                "SELECT item_type, item_id FROM folder_items WHERE folder_id = 'folder'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(folder_item, ("board".to_string(), "board".to_string()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrates_databases_that_already_have_the_trash_column() {
+        let dir = scratch_dir();
+        let conn = Connection::open_in_memory().unwrap();
+        create_base_tables(&conn, &dir).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE boards ADD COLUMN deleted_at INTEGER;
+             PRAGMA user_version = 2;",
+        )
+        .unwrap();
+
+        migrate(&conn, &dir).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), LATEST_SCHEMA_VERSION);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
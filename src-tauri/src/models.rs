@@ -9,13 +9,65 @@ pub struct Board {
     pub updated_at: DateTime<Utc>,
     pub collaboration_link: Option<String>,
     pub thumbnail: Option<String>,
+    /// Sorted by name.
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub pinned: bool,
+    /// When `set_active_board` last switched to the board.
+    #[serde(default)]
+    pub last_opened_at: Option<DateTime<Utc>>,
+}
+
+/// A label shared by any number of boards. `color` is a `#rrggbb` hex string.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BoardFolder {
     pub id: String,
     pub name: String,
-    pub items: Vec<Board>,
+    pub items: Vec<BoardListItem>,
+}
+
+/// A saved query placed in the index like a folder. Its `boards` are computed from the query
+/// each time the index loads and also appear in their own place in the tree.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartFolder {
+    pub id: String,
+    pub name: String,
+    pub query: SmartFolderQuery,
+    /// Most recently updated first.
+    #[serde(default)]
+    pub boards: Vec<Board>,
+}
+
+/// Matches the live boards that meet every condition.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SmartFolderQuery {
+    pub conditions: Vec<SmartFolderCondition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SmartFolderCondition {
+    /// The board carries the tag.
+    Tag {
+        tag_id: String,
+    },
+    /// The board was saved within the last `days` days.
+    UpdatedWithinDays {
+        days: u32,
+    },
+    /// The board name contains `text`, ignoring case.
+    NameContains {
+        text: String,
+    },
+    HasCollaborationLink,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -23,12 +75,17 @@ pub struct BoardFolder {
 pub enum BoardListItem {
     Board(Board),
     Folder(BoardFolder),
+    #[serde(rename = "smart_folder")]
+    SmartFolder(SmartFolder),
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct BoardsIndex {
     pub items: Vec<BoardListItem>,
     pub active_board_id: Option<String>,
+    /// Pinned boards sorted by name. They also appear in their place in `items`.
+    #[serde(default)]
+    pub pinned: Vec<Board>,
 }
 
 #[derive(Debug, Serialize)]
@@ -37,6 +94,173 @@ pub struct BoardMutationResult {
     pub index: BoardsIndex,
 }
 
+/// A board's scene together with the revision to pass back when saving it.
+#[derive(Debug, Serialize)]
+pub struct BoardContent {
+    pub data: String,
+    pub revision: i64,
+}
+
+/// Changes to a scene since its last save, as sent to `patch_board_data`. Elements are
+/// matched by id; `files` only carries files added since the last save.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BoardDataPatch {
+    #[serde(default)]
+    pub elements: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub deleted_element_ids: Vec<String>,
+    #[serde(default)]
+    pub files: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub app_state: Option<serde_json::Value>,
+}
+
+/// A board with journaled changes that were never saved.
+#[derive(Debug, Serialize)]
+pub struct JournalRecovery {
+    pub board_id: String,
+    pub board_name: String,
+    pub written_at: DateTime<Utc>,
+    pub changed_elements: usize,
+    pub deleted_elements: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneConflictKind {
+    /// Both copies reached the same `version` independently; `versionNonce` picked the winner.
+    ConcurrentEdit,
+    /// One copy deleted the element while the other kept it; the higher `version` won.
+    DeletedVsEdited,
+    /// Both copies hold different contents under the same file id.
+    FileMismatch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeSide {
+    Current,
+    Incoming,
+}
+
+/// A choice made while merging two copies of a scene. `id` is the element or file id.
+#[derive(Debug, Serialize)]
+pub struct SceneConflict {
+    pub kind: SceneConflictKind,
+    pub id: String,
+    pub kept: MergeSide,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardMergeResult {
+    pub data: String,
+    pub revision: i64,
+    pub conflicts: Vec<SceneConflict>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashedBoard {
+    pub board: Board,
+    pub deleted_at: DateTime<Utc>,
+    pub folder_id: Option<String>,
+    pub folder_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardSearchHit {
+    pub board_id: String,
+    pub board_name: String,
+    pub element_id: Option<String>,
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardRevision {
+    pub id: i64,
+    pub board_id: String,
+    pub created_at: DateTime<Utc>,
+    pub element_count: i64,
+    pub size_bytes: i64,
+}
+
+/// Problems found in the database file and in the board index. Every list holds ids, in the
+/// order the problems were found.
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Rows returned by `PRAGMA integrity_check`; `["ok"]` when the file itself is sound.
+    pub integrity_check: Vec<String>,
+    /// Boards that are not trashed but appear neither in the root index nor in a folder.
+    pub orphan_board_ids: Vec<String>,
+    /// Folders with boards that appear nowhere in the index tree.
+    pub orphan_folder_ids: Vec<String>,
+    /// Smart folders that appear nowhere in the index tree.
+    pub orphan_smart_folder_ids: Vec<String>,
+    /// Folders left without any boards.
+    pub empty_folder_ids: Vec<String>,
+    /// Root index entries pointing at a missing or trashed board, or a missing folder or smart
+    /// folder.
+    pub dangling_index_items: Vec<String>,
+    /// Folder entries pointing at a missing or trashed board or a missing folder or smart
+    /// folder, placing a folder or smart folder a second time, or belonging to a missing folder.
+    pub dangling_folder_items: Vec<String>,
+    /// Boards placed in the index more than once.
+    pub duplicate_board_ids: Vec<String>,
+    pub root_position_gaps: bool,
+    /// Folders whose item positions are not numbered 0, 1, 2, ...
+    pub folder_position_gap_ids: Vec<String>,
+    /// Folders whose `parent_folder_id` disagrees with where the index tree places them.
+    pub misparented_folder_ids: Vec<String>,
+    /// Boards whose thumbnail path points at a file that does not exist.
+    pub missing_thumbnail_board_ids: Vec<String>,
+    pub healthy: bool,
+}
+
+/// `repaired` lists what was found and fixed; SQLite-level corruption is not repairable in
+/// place, so `repair_integrity` refuses to run when `integrity_check` reports any.
+#[derive(Debug, Serialize)]
+pub struct IntegrityRepairResult {
+    pub repaired: IntegrityReport,
+    pub index: BoardsIndex,
+}
+
+/// What a thumbnail garbage collection pass deleted. `evicted_board_ids` are boards whose
+/// thumbnail was removed to fit the cache size limit rather than because it was orphaned.
+#[derive(Debug, Serialize)]
+pub struct ThumbnailGcReport {
+    pub removed_files: usize,
+    pub reclaimed_bytes: u64,
+    pub remaining_bytes: u64,
+    pub evicted_board_ids: Vec<String>,
+}
+
+/// How often the background backup runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupSchedule {
+    Off,
+    Hourly,
+    #[default]
+    Daily,
+}
+
+/// `directory` is `None` for the default location next to the database. Backups beyond
+/// `keep_count` or older than `max_age_days` (`0` for no limit) are deleted, but the newest
+/// one is always kept.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupSettings {
+    pub schedule: BackupSchedule,
+    pub directory: Option<String>,
+    pub keep_count: u32,
+    pub max_age_days: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BoardsExportEntry {
     pub id: String,
@@ -49,12 +273,52 @@ pub struct BoardsExportEntry {
     pub data: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BoardsExportIndexItem {
+    Board {
+        id: String,
+    },
+    Folder {
+        id: String,
+        name: String,
+        #[serde(default)]
+        items: Vec<BoardsExportIndexItem>,
+        /// Version 2 files list a folder's boards here instead of in `items`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        board_ids: Vec<String>,
+    },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BoardsExportFile {
     pub version: u8,
     #[serde(rename = "exported_at", alias = "exportedAt")]
     pub exported_at: DateTime<Utc>,
     pub boards: Vec<BoardsExportEntry>,
+    #[serde(default)]
+    pub index: Vec<BoardsExportIndexItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoardsArchiveEntry {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub collaboration_link: Option<String>,
+    pub scene_path: String,
+    pub thumbnail_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoardsArchiveManifest {
+    pub format: String,
+    pub version: u8,
+    pub exported_at: DateTime<Utc>,
+    pub boards: Vec<BoardsArchiveEntry>,
+    #[serde(default)]
+    pub index: Vec<BoardsExportIndexItem>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -63,3 +327,67 @@ pub struct BoardsImportResult {
     pub skipped: usize,
     pub index: BoardsIndex,
 }
+
+/// What `delete_folder` does with the boards inside the folder and its subfolders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderDeleteMode {
+    /// Move the boards to the trash; restoring one recreates its folders.
+    #[default]
+    TrashBoards,
+    /// Move the folder's boards and subfolders up into its parent, where the folder was.
+    KeepBoards,
+}
+
+/// Whether `list_boards_by_tags` wants boards carrying all of the given tags or any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// How `import_boards` treats an entry whose id matches a board that already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflictStrategy {
+    /// Leave the existing board untouched.
+    Skip,
+    /// Overwrite the existing board when the incoming `updated_at` is newer, otherwise skip.
+    ReplaceIfNewer,
+    /// Import under a fresh id with a "(Copy)" name. Non-conflicting entries also get fresh
+    /// ids, matching the behaviour before strategies existed.
+    #[default]
+    KeepBoth,
+    /// Keep the original id for non-conflicting entries and import conflicts as copies.
+    PreserveId,
+    /// Keep the original id for non-conflicting entries and merge conflicting scenes into the
+    /// existing boards element by element.
+    Merge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// Created under a new id.
+    Create,
+    /// Created under the id recorded in the export.
+    CreateWithId,
+    /// Created under a new id next to the existing board with the same id.
+    Copy,
+    Replace,
+    /// Merged into the existing board with the same id.
+    Merge,
+    Skip,
+}
+
+/// What importing one export entry does (or would do, for a preview). `name` is the name the
+/// board ends up with; for skipped and merged entries it is the name in the export.
+#[derive(Debug, Serialize)]
+pub struct ImportEntryDecision {
+    pub index: usize,
+    pub id: String,
+    pub name: String,
+    pub action: ImportAction,
+}
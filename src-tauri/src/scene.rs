@@ -0,0 +1,196 @@
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{AppError, AppResult};
+use crate::models::BoardDataPatch;
+
+const EXCALIDRAW_FILE_TYPE: &str = "excalidraw";
+const EXCALIDRAW_FILE_VERSION: u32 = 2;
+const EXCALIDRAW_FILE_SOURCE: &str = "https://github.com/clFaster/ExcaStoneBoard";
+
+/// Returns the scene's element array from a stored `board_data` payload. Boards are stored
+/// either as the serialized frontend scene (`{ elements, appState, files }`) or in the legacy
+/// layout from `default_board_data` (`{ "excalidraw": "<elements json>", ... }`).
+pub(crate) fn scene_elements(data: &JsonValue) -> Vec<JsonValue> {
+    data.get("elements")
+        .and_then(JsonValue::as_array)
+        .cloned()
+        .or_else(|| {
+            parse_legacy_field(data, "excalidraw").and_then(|value| value.as_array().cloned())
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn element_id(element: &JsonValue) -> Option<&str> {
+    element.get("id").and_then(JsonValue::as_str)
+}
+
+pub(crate) fn is_deleted_element(element: &JsonValue) -> bool {
+    element
+        .get("isDeleted")
+        .and_then(JsonValue::as_bool)
+        .unwrap_or(false)
+}
+
+/// Number of elements that are still visible (not tombstoned via `isDeleted`). Unparseable
+/// payloads count as empty.
+pub(crate) fn live_element_count(data: &str) -> usize {
+    let Ok(value) = serde_json::from_str::<JsonValue>(data) else {
+        return 0;
+    };
+
+    scene_elements(&value)
+        .iter()
+        .filter(|element| !is_deleted_element(element))
+        .count()
+}
+
+/// Visible text elements of a scene as `(element id, text)` pairs. Prefers `originalText`,
+/// which holds the text without the soft line breaks Excalidraw inserts when wrapping.
+pub(crate) fn text_elements(data: &JsonValue) -> Vec<(String, String)> {
+    scene_elements(data)
+        .iter()
+        .filter_map(element_text)
+        .collect()
+}
+
+/// `(element id, text)` for a visible text element, as collected by `text_elements`.
+pub(crate) fn element_text(element: &JsonValue) -> Option<(String, String)> {
+    if is_deleted_element(element)
+        || element.get("type").and_then(JsonValue::as_str) != Some("text")
+    {
+        return None;
+    }
+    let id = element_id(element)?;
+    let text = element
+        .get("originalText")
+        .or_else(|| element.get("text"))
+        .and_then(JsonValue::as_str)?;
+    (!text.trim().is_empty()).then(|| (id.to_string(), text.to_string()))
+}
+
+/// Orders elements by their fractional `index` (Excalidraw 0.17 and later) when every element
+/// has one. Scenes from older versions are left in array order.
+pub(crate) fn sort_by_fractional_index(elements: &mut Vec<JsonValue>) {
+    let indices: Option<Vec<String>> = elements
+        .iter()
+        .map(|element| {
+            element
+                .get("index")
+                .and_then(JsonValue::as_str)
+                .map(str::to_string)
+        })
+        .collect();
+    let Some(indices) = indices else {
+        return;
+    };
+
+    let mut keyed: Vec<(String, JsonValue)> = indices.into_iter().zip(elements.drain(..)).collect();
+    keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    elements.extend(keyed.into_iter().map(|(_, element)| element));
+}
+
+/// Applies a `patch_board_data` patch to a stored scene: changed elements replace the stored
+/// ones with the same id and new ones are appended, deleted ids are dropped, files are added
+/// and `appState` is replaced when the patch carries one.
+pub(crate) fn apply_patch(data: &JsonValue, patch: &BoardDataPatch) -> JsonValue {
+    let mut changed: HashMap<&str, &JsonValue> = patch
+        .elements
+        .iter()
+        .filter_map(|element| Some((element_id(element)?, element)))
+        .collect();
+    let deleted: HashSet<&str> = patch
+        .deleted_element_ids
+        .iter()
+        .map(String::as_str)
+        .collect();
+
+    let mut elements = Vec::new();
+    for element in scene_elements(data) {
+        let replacement = match element_id(&element) {
+            Some(id) if deleted.contains(id) => continue,
+            Some(id) => changed.remove(id),
+            None => None,
+        };
+        elements.push(replacement.cloned().unwrap_or(element));
+    }
+    for element in &patch.elements {
+        if element_id(element).is_some_and(|id| changed.remove(id).is_some()) {
+            elements.push(element.clone());
+        }
+    }
+    sort_by_fractional_index(&mut elements);
+
+    let mut files = scene_files(data);
+    if let Some(files) = files.as_object_mut() {
+        files.extend(patch.files.clone());
+    }
+    let app_state = patch
+        .app_state
+        .clone()
+        .filter(JsonValue::is_object)
+        .unwrap_or_else(|| scene_app_state(data));
+
+    json!({
+        "elements": elements,
+        "appState": app_state,
+        "files": files,
+    })
+}
+
+fn parse_legacy_field(data: &JsonValue, key: &str) -> Option<JsonValue> {
+    data.get(key)
+        .and_then(JsonValue::as_str)
+        .and_then(|raw| serde_json::from_str(raw).ok())
+}
+
+/// The scene's app state, falling back to the legacy `excalidraw-state` field.
+pub(crate) fn scene_app_state(data: &JsonValue) -> JsonValue {
+    data.get("appState")
+        .filter(|value| value.is_object())
+        .cloned()
+        .or_else(|| parse_legacy_field(data, "excalidraw-state").filter(JsonValue::is_object))
+        .unwrap_or_else(|| json!({}))
+}
+
+/// The scene's binary files map (`fileId -> { mimeType, id, dataURL, ... }`).
+pub(crate) fn scene_files(data: &JsonValue) -> JsonValue {
+    data.get("files")
+        .filter(|value| value.is_object())
+        .cloned()
+        .unwrap_or_else(|| json!({}))
+}
+
+/// Converts a stored scene into the standard `.excalidraw` file format. Deleted elements are
+/// dropped, the same way excalidraw.com does when saving to disk.
+pub(crate) fn to_excalidraw_file(data: &JsonValue) -> JsonValue {
+    let elements: Vec<JsonValue> = scene_elements(data)
+        .into_iter()
+        .filter(|element| !is_deleted_element(element))
+        .collect();
+
+    json!({
+        "type": EXCALIDRAW_FILE_TYPE,
+        "version": EXCALIDRAW_FILE_VERSION,
+        "source": EXCALIDRAW_FILE_SOURCE,
+        "elements": elements,
+        "appState": scene_app_state(data),
+        "files": scene_files(data),
+    })
+}
+
+/// Converts a parsed `.excalidraw` file into the stored scene layout.
+pub(crate) fn from_excalidraw_file(file: &JsonValue) -> AppResult<JsonValue> {
+    if file.get("type").and_then(JsonValue::as_str) != Some(EXCALIDRAW_FILE_TYPE) {
+        return Err(AppError::validation("Not an Excalidraw file"));
+    }
+    if !file.get("elements").is_some_and(JsonValue::is_array) {
+        return Err(AppError::validation("Excalidraw file has no elements"));
+    }
+
+    Ok(json!({
+        "elements": scene_elements(file),
+        "appState": scene_app_state(file),
+        "files": scene_files(file),
+    }))
+}
@@ -0,0 +1,169 @@
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{MergeSide, SceneConflict, SceneConflictKind};
+use crate::scene;
+
+/// Result of reconciling two copies of a scene.
+pub(crate) struct SceneMerge {
+    pub(crate) scene: JsonValue,
+    pub(crate) conflicts: Vec<SceneConflict>,
+}
+
+/// Merges `incoming` into `current` the way Excalidraw reconciles collaborators' scenes:
+/// elements are matched by id and the copy with the higher `version` wins, with ties broken
+/// in favour of the lower `versionNonce`. Deletions are tombstones (`isDeleted`) and merge
+/// like any other change. Files are unioned; where both sides hold different contents for
+/// the same file id the current one is kept. `appState` always comes from `current`.
+pub(crate) fn merge_scenes(current: &JsonValue, incoming: &JsonValue) -> SceneMerge {
+    let mut conflicts = Vec::new();
+    let elements = merge_elements(
+        scene::scene_elements(current),
+        scene::scene_elements(incoming),
+        &mut conflicts,
+    );
+    let files = merge_files(
+        scene::scene_files(current),
+        scene::scene_files(incoming),
+        &mut conflicts,
+    );
+
+    SceneMerge {
+        scene: json!({
+            "elements": elements,
+            "appState": scene::scene_app_state(current),
+            "files": files,
+        }),
+        conflicts,
+    }
+}
+
+fn element_i64(element: &JsonValue, key: &str) -> i64 {
+    element.get(key).and_then(JsonValue::as_i64).unwrap_or(0)
+}
+
+/// Keeps the order of `current`, replacing elements that `incoming` has a newer copy of, and
+/// appends elements only `incoming` has, then orders by fractional index where available.
+fn merge_elements(
+    current: Vec<JsonValue>,
+    incoming: Vec<JsonValue>,
+    conflicts: &mut Vec<SceneConflict>,
+) -> Vec<JsonValue> {
+    let mut incoming_by_id: HashMap<String, JsonValue> = HashMap::new();
+    let mut incoming_order = Vec::new();
+    for element in incoming {
+        let Some(id) = scene::element_id(&element).map(str::to_string) else {
+            continue;
+        };
+        if incoming_by_id.insert(id.clone(), element).is_none() {
+            incoming_order.push(id);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut merged = Vec::with_capacity(current.len() + incoming_order.len());
+    for element in current {
+        let Some(id) = scene::element_id(&element).map(str::to_string) else {
+            merged.push(element);
+            continue;
+        };
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        match incoming_by_id.remove(&id) {
+            Some(other) => merged.push(pick_element(id, element, other, conflicts)),
+            None => merged.push(element),
+        }
+    }
+    for id in incoming_order {
+        if let Some(element) = incoming_by_id.remove(&id) {
+            merged.push(element);
+        }
+    }
+
+    scene::sort_by_fractional_index(&mut merged);
+    merged
+}
+
+/// Chooses between two copies of the same element, recording a conflict when the choice
+/// discards a concurrent edit or a deletion.
+fn pick_element(
+    id: String,
+    current: JsonValue,
+    incoming: JsonValue,
+    conflicts: &mut Vec<SceneConflict>,
+) -> JsonValue {
+    let (current_version, incoming_version) = (
+        element_i64(&current, "version"),
+        element_i64(&incoming, "version"),
+    );
+    let (current_nonce, incoming_nonce) = (
+        element_i64(&current, "versionNonce"),
+        element_i64(&incoming, "versionNonce"),
+    );
+
+    let keep_current = current_version > incoming_version
+        || (current_version == incoming_version && current_nonce < incoming_nonce);
+    let kind = if scene::is_deleted_element(&current) != scene::is_deleted_element(&incoming) {
+        Some(SceneConflictKind::DeletedVsEdited)
+    } else if current_version == incoming_version && current_nonce != incoming_nonce {
+        Some(SceneConflictKind::ConcurrentEdit)
+    } else {
+        None
+    };
+
+    if let Some(kind) = kind {
+        conflicts.push(SceneConflict {
+            kind,
+            id,
+            kept: if keep_current {
+                MergeSide::Current
+            } else {
+                MergeSide::Incoming
+            },
+        });
+    }
+
+    if keep_current {
+        current
+    } else {
+        incoming
+    }
+}
+
+fn merge_files(
+    current: JsonValue,
+    incoming: JsonValue,
+    conflicts: &mut Vec<SceneConflict>,
+) -> JsonValue {
+    let mut merged = match current {
+        JsonValue::Object(files) => files,
+        _ => Map::new(),
+    };
+    let JsonValue::Object(incoming) = incoming else {
+        return JsonValue::Object(merged);
+    };
+
+    for (file_id, file) in incoming {
+        match merged.get(&file_id) {
+            Some(existing) if file_content(existing) != file_content(&file) => {
+                conflicts.push(SceneConflict {
+                    kind: SceneConflictKind::FileMismatch,
+                    id: file_id,
+                    kept: MergeSide::Current,
+                });
+            }
+            Some(_) => {}
+            None => {
+                merged.insert(file_id, file);
+            }
+        }
+    }
+    JsonValue::Object(merged)
+}
+
+/// The part of a file entry that identifies its bytes: the data URL, or the asset hash for
+/// scenes that have not been hydrated.
+fn file_content(file: &JsonValue) -> Option<&JsonValue> {
+    file.get("dataURL").or_else(|| file.get("assetHash"))
+}
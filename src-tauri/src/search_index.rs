@@ -0,0 +1,70 @@
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+
+use crate::error::AppResult;
+use crate::models::BoardDataPatch;
+use crate::scene;
+
+/// Replaces the indexed name row of a board.
+pub(crate) fn index_board_name(conn: &Connection, board_id: &str, name: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM board_search WHERE board_id = ?1 AND element_id IS NULL",
+        params![board_id],
+    )?;
+    conn.execute(
+        "INSERT INTO board_search (board_id, element_id, content) VALUES (?1, NULL, ?2)",
+        params![board_id, name],
+    )?;
+    Ok(())
+}
+
+/// Replaces the indexed text element rows of a board with those found in `data`.
+pub(crate) fn index_board_scene(conn: &Connection, board_id: &str, data: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM board_search WHERE board_id = ?1 AND element_id IS NOT NULL",
+        params![board_id],
+    )?;
+
+    let Ok(value) = serde_json::from_str::<JsonValue>(data) else {
+        return Ok(());
+    };
+    let mut stmt = conn
+        .prepare("INSERT INTO board_search (board_id, element_id, content) VALUES (?1, ?2, ?3)")?;
+    for (element_id, text) in scene::text_elements(&value) {
+        stmt.execute(params![board_id, element_id, text])?;
+    }
+    Ok(())
+}
+
+/// Updates the indexed text element rows of a board for the elements a patch touches.
+pub(crate) fn index_scene_patch(
+    conn: &Connection,
+    board_id: &str,
+    patch: &BoardDataPatch,
+) -> AppResult<()> {
+    let mut delete =
+        conn.prepare("DELETE FROM board_search WHERE board_id = ?1 AND element_id = ?2")?;
+    let touched_ids = patch
+        .elements
+        .iter()
+        .filter_map(scene::element_id)
+        .chain(patch.deleted_element_ids.iter().map(String::as_str));
+    for element_id in touched_ids {
+        delete.execute(params![board_id, element_id])?;
+    }
+
+    let mut insert = conn
+        .prepare("INSERT INTO board_search (board_id, element_id, content) VALUES (?1, ?2, ?3)")?;
+    for (element_id, text) in patch.elements.iter().filter_map(scene::element_text) {
+        insert.execute(params![board_id, element_id, text])?;
+    }
+    Ok(())
+}
+
+pub(crate) fn remove_board_from_index(conn: &Connection, board_id: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM board_search WHERE board_id = ?1",
+        params![board_id],
+    )?;
+    Ok(())
+}
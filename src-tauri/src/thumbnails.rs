@@ -1,14 +1,26 @@
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::http::{header, Request, Response, StatusCode};
 use tauri::AppHandle;
 
+use crate::assets::decode_data_url;
 use crate::db::get_boards_dir;
+use crate::error::{AppError, AppResult};
 
 const THUMBNAILS_SUBDIR: &str = "thumbnails";
 const DEFAULT_MIME: &str = "image/png";
 
+pub(crate) const THUMBNAIL_SCHEME: &str = "thumb";
+/// Webviews on Windows and Android reach custom schemes through `http://<scheme>.localhost`.
+#[cfg(any(windows, target_os = "android"))]
+const THUMBNAIL_URL_BASE: &str = "http://thumb.localhost/";
+#[cfg(not(any(windows, target_os = "android")))]
+const THUMBNAIL_URL_BASE: &str = "thumb://localhost/";
+
 #[derive(Clone, Copy)]
 pub(crate) struct BoardId<'a>(&'a str);
 
@@ -91,18 +103,18 @@ const KNOWN_FORMATS: [ThumbnailFormat; 4] = [
 ];
 
 /// Directory that holds all cached thumbnail files, created on demand.
-pub(crate) fn thumbnails_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let dir = get_boards_dir(app)?.join(THUMBNAILS_SUBDIR);
-    fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+pub(crate) fn thumbnails_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    thumbnails_dir_in(&get_boards_dir(app)?)
+}
+
+fn thumbnails_dir_in(boards_dir: &Path) -> AppResult<PathBuf> {
+    let dir = boards_dir.join(THUMBNAILS_SUBDIR);
+    fs::create_dir_all(&dir)?;
     Ok(dir)
 }
 
-fn file_path_for(
-    app: &AppHandle,
-    board_id: BoardId<'_>,
-    format: ThumbnailFormat,
-) -> Result<PathBuf, String> {
-    Ok(thumbnails_dir(app)?.join(format!("{}.{}", board_id.as_str(), format.extension())))
+fn file_path_for(thumbnails_dir: &Path, board_id: BoardId<'_>, format: ThumbnailFormat) -> PathBuf {
+    thumbnails_dir.join(format!("{}.{}", board_id.as_str(), format.extension()))
 }
 
 /// Relative path (as stored in the DB) for a given board id/extension pair.
@@ -114,37 +126,12 @@ fn relative_path_for(board_id: BoardId<'_>, format: ThumbnailFormat) -> String {
     )
 }
 
-struct DecodedDataUrl {
-    format: ThumbnailFormat,
-    bytes: Vec<u8>,
-}
-
-fn decode_data_url(data_url: &str) -> Result<DecodedDataUrl, String> {
-    let (header, payload) = data_url
-        .split_once(',')
-        .ok_or_else(|| "Invalid thumbnail data URL".to_string())?;
-
-    let mime = header
-        .strip_prefix("data:")
-        .and_then(|rest| rest.split(';').next())
-        .unwrap_or(DEFAULT_MIME);
-
-    let bytes = STANDARD
-        .decode(payload)
-        .map_err(|error| format!("Failed to decode thumbnail data: {error}"))?;
-
-    Ok(DecodedDataUrl {
-        format: ThumbnailFormat::from_mime_label(mime),
-        bytes,
-    })
-}
-
 /// Removes any previously cached thumbnail file(s) for a board, regardless of extension.
-fn remove_existing_files(app: &AppHandle, board_id: BoardId<'_>) -> Result<(), String> {
+fn remove_existing_files(thumbnails_dir: &Path, board_id: BoardId<'_>) -> AppResult<()> {
     for format in KNOWN_FORMATS {
-        let path = file_path_for(app, board_id, format)?;
+        let path = file_path_for(thumbnails_dir, board_id, format);
         if path.exists() {
-            fs::remove_file(&path).map_err(|error| error.to_string())?;
+            fs::remove_file(&path)?;
         }
     }
     Ok(())
@@ -157,45 +144,243 @@ pub(crate) fn save_thumbnail(
     app: &AppHandle,
     board_id: BoardId<'_>,
     data_url: Option<&str>,
-) -> Result<Option<String>, String> {
-    remove_existing_files(app, board_id)?;
+) -> AppResult<Option<String>> {
+    save_thumbnail_in(&get_boards_dir(app)?, board_id, data_url)
+}
 
+/// `save_thumbnail` for a given boards directory, for when there is no app handle yet.
+pub(crate) fn save_thumbnail_in(
+    boards_dir: &Path,
+    board_id: BoardId<'_>,
+    data_url: Option<&str>,
+) -> AppResult<Option<String>> {
     let Some(data_url) = data_url else {
+        remove_existing_files(&thumbnails_dir_in(boards_dir)?, board_id)?;
         return Ok(None);
     };
 
-    let decoded = decode_data_url(data_url)?;
-    let path = file_path_for(app, board_id, decoded.format)?;
-    fs::write(&path, &decoded.bytes).map_err(|error| error.to_string())?;
+    let staged = StagedThumbnail::decode(board_id, data_url)?;
+    staged.write_in(boards_dir)?;
+    Ok(Some(staged.relative_path()))
+}
 
-    Ok(Some(relative_path_for(board_id, decoded.format)))
+/// A decoded thumbnail that is not on disk yet, for callers that write it only once the rows
+/// referencing it are committed.
+pub(crate) struct StagedThumbnail {
+    board_id: String,
+    format: ThumbnailFormat,
+    bytes: Vec<u8>,
+}
+
+impl StagedThumbnail {
+    pub(crate) fn decode(board_id: BoardId<'_>, data_url: &str) -> AppResult<Self> {
+        let (mime, bytes) = decode_data_url(data_url)
+            .ok_or_else(|| AppError::validation("Invalid thumbnail data URL"))?;
+        Ok(Self {
+            board_id: board_id.as_str().to_string(),
+            format: ThumbnailFormat::from_mime_label(&mime),
+            bytes,
+        })
+    }
+
+    pub(crate) fn board_id(&self) -> &str {
+        &self.board_id
+    }
+
+    /// Relative path (as stored in the DB) the thumbnail is written to.
+    pub(crate) fn relative_path(&self) -> String {
+        relative_path_for(BoardId::from(self.board_id.as_str()), self.format)
+    }
+
+    /// Writes the file, replacing the board's previous thumbnail file.
+    pub(crate) fn write(&self, app: &AppHandle) -> AppResult<()> {
+        self.write_in(&get_boards_dir(app)?)
+    }
+
+    fn write_in(&self, boards_dir: &Path) -> AppResult<()> {
+        let board_id = BoardId::from(self.board_id.as_str());
+        let thumbnails_dir = thumbnails_dir_in(boards_dir)?;
+        remove_existing_files(&thumbnails_dir, board_id)?;
+        fs::write(
+            file_path_for(&thumbnails_dir, board_id, self.format),
+            &self.bytes,
+        )?;
+        Ok(())
+    }
 }
 
 /// Reads the thumbnail file referenced by `relative_path` (if any) and re-encodes it as a
-/// data URL for the frontend.
+/// data URL, for exports that have to be self-contained.
 pub(crate) fn load_thumbnail_data_url(
     app: &AppHandle,
     relative_path: Option<RelativeThumbnailPath<'_>>,
-) -> Result<Option<String>, String> {
+) -> AppResult<Option<String>> {
+    let Some(path) = thumbnail_file_path(app, relative_path)? else {
+        return Ok(None);
+    };
+
+    let bytes = fs::read(&path)?;
+    let mime = ThumbnailFormat::from_path(&path).mime();
+    let encoded = STANDARD.encode(bytes);
+
+    Ok(Some(format!("data:{mime};base64,{encoded}")))
+}
+
+/// Absolute path of the thumbnail file referenced by `relative_path`, or `None` if there is
+/// no such file on disk.
+pub(crate) fn thumbnail_file_path(
+    app: &AppHandle,
+    relative_path: Option<RelativeThumbnailPath<'_>>,
+) -> AppResult<Option<PathBuf>> {
     let Some(relative_path) = relative_path else {
         return Ok(None);
     };
 
     let path = get_boards_dir(app)?.join(relative_path.as_str());
-    if !path.exists() {
+    Ok(path.exists().then_some(path))
+}
+
+/// Whether the thumbnail file referenced by `relative_path` is present on disk.
+pub(crate) fn thumbnail_file_exists(
+    app: &AppHandle,
+    relative_path: RelativeThumbnailPath<'_>,
+) -> AppResult<bool> {
+    Ok(get_boards_dir(app)?.join(relative_path.as_str()).is_file())
+}
+
+/// URL under which the `thumb` protocol serves the thumbnail at `relative_path`, or `None`
+/// if there is no thumbnail file. The file's modification time is appended so that a
+/// regenerated thumbnail is not served from the webview's cache.
+pub(crate) fn thumbnail_url(
+    app: &AppHandle,
+    relative_path: Option<RelativeThumbnailPath<'_>>,
+) -> AppResult<Option<String>> {
+    let Some(relative_path) = relative_path else {
         return Ok(None);
+    };
+
+    let path = get_boards_dir(app)?.join(relative_path.as_str());
+    let Ok(metadata) = fs::metadata(&path) else {
+        return Ok(None);
+    };
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(None);
+    };
+
+    let version = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_millis());
+    Ok(Some(format!("{THUMBNAIL_URL_BASE}{file_name}?v={version}")))
+}
+
+/// Handler for the `thumb` URI scheme: serves `<board id>.<ext>` from the thumbnails directory.
+/// URLs carry a version parameter, so responses can be cached indefinitely.
+pub(crate) fn serve_thumbnail(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let file_name = request.uri().path().trim_start_matches('/');
+    let is_safe_name = !file_name.is_empty()
+        && !file_name.starts_with('.')
+        && file_name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character));
+    if !is_safe_name {
+        return empty_response(StatusCode::BAD_REQUEST);
     }
 
-    let bytes = fs::read(&path).map_err(|error| error.to_string())?;
-    let mime = ThumbnailFormat::from_path(&path).mime();
-    let encoded = STANDARD.encode(bytes);
+    let Ok(path) = thumbnails_dir(app).map(|dir| dir.join(file_name)) else {
+        return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let Ok(bytes) = fs::read(&path) else {
+        return empty_response(StatusCode::NOT_FOUND);
+    };
 
-    Ok(Some(format!("data:{mime};base64,{encoded}")))
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            ThumbnailFormat::from_path(&path).mime(),
+        )
+        .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+        .body(bytes)
+        .unwrap_or_else(|_| empty_response(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
 }
 
 /// Deletes the cached thumbnail file(s) for a board, if any.
-pub(crate) fn delete_thumbnail(app: &AppHandle, board_id: BoardId<'_>) -> Result<(), String> {
-    remove_existing_files(app, board_id)
+pub(crate) fn delete_thumbnail(app: &AppHandle, board_id: BoardId<'_>) -> AppResult<()> {
+    remove_existing_files(&thumbnails_dir(app)?, board_id)
+}
+
+/// Outcome of a thumbnail garbage collection pass. `evicted_paths` are the relative paths of
+/// still-referenced thumbnails removed to fit the size limit, whose rows must be cleared.
+pub(crate) struct ThumbnailGarbage {
+    pub(crate) removed_files: usize,
+    pub(crate) reclaimed_bytes: u64,
+    pub(crate) remaining_bytes: u64,
+    pub(crate) evicted_paths: Vec<String>,
+}
+
+/// Deletes every file in the thumbnails directory whose relative path is not in
+/// `referenced`, then, while the remaining files total more than `max_total_bytes` (`0` for
+/// no limit), the least recently written ones. Evicted thumbnails are regenerated the next
+/// time their board is opened.
+pub(crate) fn collect_garbage(
+    app: &AppHandle,
+    referenced: &HashSet<String>,
+    max_total_bytes: u64,
+) -> AppResult<ThumbnailGarbage> {
+    let mut garbage = ThumbnailGarbage {
+        removed_files: 0,
+        reclaimed_bytes: 0,
+        remaining_bytes: 0,
+        evicted_paths: Vec::new(),
+    };
+
+    let mut kept: Vec<(SystemTime, u64, PathBuf, String)> = Vec::new();
+    for entry in fs::read_dir(thumbnails_dir(app)?)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let relative_path = format!(
+            "{THUMBNAILS_SUBDIR}/{}",
+            entry.file_name().to_string_lossy()
+        );
+
+        if referenced.contains(&relative_path) {
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            kept.push((modified, metadata.len(), path, relative_path));
+        } else {
+            fs::remove_file(&path)?;
+            garbage.removed_files += 1;
+            garbage.reclaimed_bytes += metadata.len();
+        }
+    }
+
+    garbage.remaining_bytes = kept.iter().map(|(_, size, ..)| size).sum();
+    if max_total_bytes > 0 && garbage.remaining_bytes > max_total_bytes {
+        kept.sort_by_key(|(modified, ..)| *modified);
+        for (_, size, path, relative_path) in kept {
+            if garbage.remaining_bytes <= max_total_bytes {
+                break;
+            }
+            fs::remove_file(&path)?;
+            garbage.removed_files += 1;
+            garbage.reclaimed_bytes += size;
+            garbage.remaining_bytes -= size;
+            garbage.evicted_paths.push(relative_path);
+        }
+    }
+
+    Ok(garbage)
 }
 
 /// Copies the thumbnail file from `source_board_id` to `destination_board_id`, returning
@@ -204,12 +389,13 @@ pub(crate) fn copy_thumbnail(
     app: &AppHandle,
     source_board_id: BoardId<'_>,
     destination_board_id: BoardId<'_>,
-) -> Result<Option<String>, String> {
+) -> AppResult<Option<String>> {
+    let thumbnails_dir = thumbnails_dir(app)?;
     for format in KNOWN_FORMATS {
-        let source_path = file_path_for(app, source_board_id, format)?;
+        let source_path = file_path_for(&thumbnails_dir, source_board_id, format);
         if source_path.exists() {
-            let destination_path = file_path_for(app, destination_board_id, format)?;
-            fs::copy(&source_path, &destination_path).map_err(|error| error.to_string())?;
+            let destination_path = file_path_for(&thumbnails_dir, destination_board_id, format);
+            fs::copy(&source_path, &destination_path)?;
             return Ok(Some(relative_path_for(destination_board_id, format)));
         }
     }
@@ -4,7 +4,7 @@ import { BoardList } from './components/BoardList';
 import { CommandPalette, type CommandPaletteItem } from './components/CommandPalette';
 import type { ExcalidrawData, ExcalidrawFrameHandle } from './components/ExcalidrawFrame';
 import { useAppController } from './hooks/useAppController';
-import type { BoardListItem } from './types/board';
+import { flattenBoardTree, type BoardListItem } from './types/board';
 import './App.css';
 
 const ExcalidrawFrame = lazy(() => import('./components/ExcalidrawFrame'));
@@ -13,15 +13,11 @@ type AppController = ReturnType<typeof useAppController>;
 type PaletteBoardEntry = ReturnType<typeof flattenBoardsForPalette>[number];
 
 const flattenBoardsForPalette = (items: BoardListItem[]) =>
-  items.flatMap((item) =>
-    item.type === 'board'
-      ? [{ boardId: item.id, boardName: item.name, folderName: null as string | null }]
-      : item.items.map((board) => ({
-          boardId: board.id,
-          boardName: board.name,
-          folderName: item.name,
-        })),
-  );
+  flattenBoardTree(items).map(({ board, folderPath }) => ({
+    boardId: board.id,
+    boardName: board.name,
+    folderName: folderPath.length > 0 ? folderPath.map((folder) => folder.name).join(' / ') : null,
+  }));
 
 const getBoardLocationDescription = (entry: PaletteBoardEntry) =>
   entry.folderName ? `Folder: ${entry.folderName}` : 'Top-level board';
@@ -69,6 +65,7 @@ interface CommandPaletteCommandsConfig {
   duplicateBoard: AppController['duplicateBoard'];
   requestOpenSettings: () => void;
   requestOpenImportBoards: () => void;
+  requestOpenTrash: () => void;
   sidebarCollapsed: AppController['sidebarCollapsed'];
   showTimestamps: AppController['showTimestamps'];
   setShowTimestamps: AppController['setShowTimestamps'];
@@ -266,6 +263,7 @@ const createCommandPaletteCommands = ({
   duplicateBoard,
   requestOpenSettings,
   requestOpenImportBoards,
+  requestOpenTrash,
   sidebarCollapsed,
   showTimestamps,
   setShowTimestamps,
@@ -321,6 +319,13 @@ const createCommandPaletteCommands = ({
       disabled: boardsImportBusy,
       action: requestOpenImportBoards,
     },
+    {
+      id: 'open-trash',
+      label: 'Open trash',
+      description: 'Restore or permanently delete deleted boards',
+      keywords: 'trash deleted restore recover bin',
+      action: requestOpenTrash,
+    },
     {
       id: 'toggle-timestamps',
       label: showTimestamps ? 'Hide sidebar timestamps' : 'Show sidebar timestamps',
@@ -436,6 +441,10 @@ const useCommandPaletteController = ({
     window.dispatchEvent(new CustomEvent('boardlist:import-boards'));
   }, []);
 
+  const requestOpenTrash = useCallback(() => {
+    window.dispatchEvent(new CustomEvent('app:open-trash'));
+  }, []);
+
   const commandPaletteCommands = useMemo(
     () =>
       createCommandPaletteCommands({
@@ -450,6 +459,7 @@ const useCommandPaletteController = ({
         duplicateBoard,
         requestOpenSettings,
         requestOpenImportBoards,
+        requestOpenTrash,
         sidebarCollapsed,
         showTimestamps,
         setShowTimestamps,
@@ -479,6 +489,7 @@ const useCommandPaletteController = ({
       renameBoard,
       requestOpenImportBoards,
       requestOpenSettings,
+      requestOpenTrash,
       sidebarCollapsed,
       setShowTimestamps,
       showTimestamps,
@@ -521,11 +532,274 @@ function AppErrorToast({ message }: { message: string | null }) {
   );
 }
 
+interface StaleBoardToastProps {
+  visible: boolean;
+  onReload: () => void;
+  onMerge: () => Promise<void>;
+}
+
+function StaleBoardToast({ visible, onReload, onMerge }: StaleBoardToastProps) {
+  if (!visible) {
+    return null;
+  }
+
+  return (
+    <div className="error-toast stale-board-toast">
+      <p>This board was changed in another window. Your latest edits were not saved.</p>
+      <button type="button" onClick={() => void onMerge()}>
+        Merge
+      </button>
+      <button type="button" onClick={onReload}>
+        Reload
+      </button>
+    </div>
+  );
+}
+
+interface JournalRecoveryModalProps {
+  recoveries: AppController['journalRecoveries'];
+  onRecover: AppController['recoverJournal'];
+  onDiscard: AppController['discardJournal'];
+}
+
+const describeJournalRecovery = (recovery: JournalRecoveryModalProps['recoveries'][number]) => {
+  const changes = recovery.changed_elements + recovery.deleted_elements;
+  const savedAt = new Date(recovery.written_at).toLocaleString();
+  return `${changes} unsaved ${changes === 1 ? 'change' : 'changes'} from ${savedAt}`;
+};
+
+function JournalRecoveryModal({ recoveries, onRecover, onDiscard }: JournalRecoveryModalProps) {
+  if (recoveries.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className="modal-overlay">
+      <div className="recovery-modal" data-testid="journal-recovery-modal">
+        <h3>Recover unsaved changes</h3>
+        <p className="modal-hint">
+          ExcaStoneBoard closed before these changes were saved. Restore them or discard them.
+        </p>
+        <ul className="recovery-list">
+          {recoveries.map((recovery) => (
+            <li key={recovery.board_id} className="recovery-item">
+              <div className="recovery-item-text">
+                <span className="recovery-item-name">{recovery.board_name}</span>
+                <span className="recovery-item-detail">{describeJournalRecovery(recovery)}</span>
+              </div>
+              <button
+                type="button"
+                className="btn-secondary"
+                onClick={() => void onDiscard(recovery.board_id)}
+              >
+                Discard
+              </button>
+              <button
+                type="button"
+                className="btn-primary"
+                onClick={() => void onRecover(recovery.board_id)}
+              >
+                Restore
+              </button>
+            </li>
+          ))}
+        </ul>
+      </div>
+    </div>
+  );
+}
+
+interface TrashModalProps {
+  open: AppController['trashOpen'];
+  trashedBoards: AppController['trashedBoards'];
+  retentionDays: AppController['trashRetentionDays'];
+  error: AppController['trashError'];
+  onClose: AppController['closeTrash'];
+  onRestoreBoard: AppController['restoreTrashedBoard'];
+  onRestoreFolder: AppController['restoreTrashedFolder'];
+  onPurgeBoard: AppController['purgeTrashedBoard'];
+  onEmptyTrash: AppController['emptyTrash'];
+  onRetentionDaysChange: AppController['setTrashRetentionDays'];
+}
+
+type TrashedBoardEntry = TrashModalProps['trashedBoards'][number];
+
+interface TrashGroup {
+  folderId: string | null;
+  folderName: string | null;
+  boards: TrashedBoardEntry[];
+}
+
+const TRASH_RETENTION_OPTIONS = [7, 14, 30, 60, 90];
+
+// A retention set to something other than the usual choices is still offered as it is.
+const getTrashRetentionOptions = (retentionDays: number | null) =>
+  !retentionDays || TRASH_RETENTION_OPTIONS.includes(retentionDays)
+    ? TRASH_RETENTION_OPTIONS
+    : [...TRASH_RETENTION_OPTIONS, retentionDays].sort((a, b) => a - b);
+
+// Boards deleted from the same folder are listed together, so the folder can be restored as a
+// whole. Boards deleted from the top level come first.
+const groupTrashedBoards = (trashedBoards: TrashedBoardEntry[]) => {
+  const groups: TrashGroup[] = [];
+  for (const entry of trashedBoards) {
+    const group = groups.find((candidate) => candidate.folderId === entry.folder_id);
+    if (group) {
+      group.boards.push(entry);
+    } else {
+      groups.push({ folderId: entry.folder_id, folderName: entry.folder_name, boards: [entry] });
+    }
+  }
+  return groups.sort((a, b) => Number(a.folderId !== null) - Number(b.folderId !== null));
+};
+
+function TrashModal({
+  open,
+  trashedBoards,
+  retentionDays,
+  error,
+  onClose,
+  onRestoreBoard,
+  onRestoreFolder,
+  onPurgeBoard,
+  onEmptyTrash,
+  onRetentionDaysChange,
+}: TrashModalProps) {
+  const groups = useMemo(() => groupTrashedBoards(trashedBoards), [trashedBoards]);
+
+  useEffect(() => {
+    if (!open) {
+      return;
+    }
+
+    const handleKeyDown = (event: KeyboardEvent) => {
+      if (event.key === 'Escape') {
+        event.preventDefault();
+        onClose();
+      }
+    };
+
+    window.addEventListener('keydown', handleKeyDown);
+    return () => {
+      window.removeEventListener('keydown', handleKeyDown);
+    };
+  }, [onClose, open]);
+
+  if (!open) {
+    return null;
+  }
+
+  const handlePurgeBoard = (entry: TrashedBoardEntry) => {
+    if (window.confirm(`Permanently delete "${entry.board.name}"? This cannot be undone.`)) {
+      void onPurgeBoard(entry.board.id);
+    }
+  };
+
+  const handleEmptyTrash = () => {
+    if (window.confirm('Permanently delete every board in the trash? This cannot be undone.')) {
+      void onEmptyTrash();
+    }
+  };
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div
+        className="recovery-modal trash-modal"
+        data-testid="trash-modal"
+        onClick={(event) => event.stopPropagation()}
+      >
+        <h3>Trash</h3>
+        <p className="modal-hint">
+          Deleted boards stay here until you restore them or delete them permanently.
+        </p>
+        {error ? <p className="trash-error">{error}</p> : null}
+        {groups.length === 0 ? (
+          <p className="trash-empty">The trash is empty.</p>
+        ) : (
+          <div className="trash-groups">
+            {groups.map(({ folderId, folderName, boards }) => (
+              <section key={folderId ?? 'top-level'} className="trash-group">
+                <div className="trash-group-header">
+                  <span className="trash-group-name">{folderName ?? 'Top-level boards'}</span>
+                  {folderId !== null ? (
+                    <button
+                      type="button"
+                      className="btn-secondary"
+                      onClick={() => void onRestoreFolder(folderId)}
+                    >
+                      Restore folder
+                    </button>
+                  ) : null}
+                </div>
+                <ul className="recovery-list">
+                  {boards.map((entry) => (
+                    <li key={entry.board.id} className="recovery-item">
+                      <div className="recovery-item-text">
+                        <span className="recovery-item-name">{entry.board.name}</span>
+                        <span className="recovery-item-detail">
+                          Deleted {new Date(entry.deleted_at).toLocaleString()}
+                        </span>
+                      </div>
+                      <button
+                        type="button"
+                        className="btn-secondary"
+                        onClick={() => handlePurgeBoard(entry)}
+                      >
+                        Delete
+                      </button>
+                      <button
+                        type="button"
+                        className="btn-primary"
+                        onClick={() => void onRestoreBoard(entry.board.id)}
+                      >
+                        Restore
+                      </button>
+                    </li>
+                  ))}
+                </ul>
+              </section>
+            ))}
+          </div>
+        )}
+        <label className="trash-retention">
+          Delete boards from the trash
+          <select
+            value={retentionDays ?? ''}
+            disabled={retentionDays === null}
+            onChange={(event) => void onRetentionDaysChange(Number(event.target.value))}
+          >
+            {getTrashRetentionOptions(retentionDays).map((days) => (
+              <option key={days} value={days}>
+                after {days} days
+              </option>
+            ))}
+            <option value={0}>never</option>
+          </select>
+        </label>
+        <div className="modal-actions">
+          <button
+            type="button"
+            className="btn-secondary"
+            onClick={handleEmptyTrash}
+            disabled={trashedBoards.length === 0}
+          >
+            Empty trash
+          </button>
+          <button type="button" className="btn-primary" onClick={onClose}>
+            Close
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
+
 interface EditorPanelProps {
   activeBoardId: string | null;
   boardDataLoading: boolean;
   activeBoardName: string | null;
-  handleDataChange: (boardId: string, data: ExcalidrawData) => Promise<void>;
+  handleDataChange: AppController['handleDataChange'];
+  journalBoardChanges: AppController['journalBoardChanges'];
   handleThumbnailGenerated: (boardId: string, dataUrl: string) => void;
   currentBoardData: ExcalidrawData | null;
   excalidrawRef: RefObject<ExcalidrawFrameHandle | null>;
@@ -536,6 +810,7 @@ function EditorPanel({
   boardDataLoading,
   activeBoardName,
   handleDataChange,
+  journalBoardChanges,
   handleThumbnailGenerated,
   currentBoardData,
   excalidrawRef,
@@ -551,6 +826,7 @@ function EditorPanel({
         boardId={activeBoardId}
         boardName={activeBoardName}
         onDataChange={handleDataChange}
+        onJournalChange={journalBoardChanges}
         onThumbnailGenerated={handleThumbnailGenerated}
         initialData={currentBoardData}
         ref={excalidrawRef}
@@ -561,6 +837,7 @@ function EditorPanel({
 
 interface AppLayoutProps {
   items: AppController['items'];
+  pinnedBoards: AppController['pinnedBoards'];
   activeBoardId: AppController['activeBoardId'];
   thumbnails: AppController['thumbnails'];
   handleSelectBoard: AppController['handleSelectBoard'];
@@ -568,7 +845,8 @@ interface AppLayoutProps {
   renameBoard: AppController['renameBoard'];
   deleteBoard: AppController['deleteBoard'];
   duplicateBoard: AppController['duplicateBoard'];
-  setBoardsIndex: AppController['setBoardsIndex'];
+  applyBoardListChange: AppController['applyBoardListChange'];
+  setBoardPinned: AppController['setBoardPinned'];
   handleExportPng: AppController['handleExportPng'];
   handleCopyPng: AppController['handleCopyPng'];
   handleExportSvg: AppController['handleExportSvg'];
@@ -586,6 +864,7 @@ interface AppLayoutProps {
   activeBoardName: AppController['activeBoardName'];
   boardDataLoading: AppController['boardDataLoading'];
   handleDataChange: AppController['handleDataChange'];
+  journalBoardChanges: AppController['journalBoardChanges'];
   handleThumbnailGenerated: AppController['handleThumbnailGenerated'];
   currentBoardData: AppController['currentBoardData'];
   excalidrawRef: AppController['excalidrawRef'];
@@ -593,10 +872,27 @@ interface AppLayoutProps {
   closeCommandPalette: () => void;
   commandPaletteCommands: CommandPaletteItem[];
   errorMessage: string | null;
+  activeBoardStale: boolean;
+  reloadBoardData: AppController['reloadBoardData'];
+  handleMergeStaleBoard: AppController['handleMergeStaleBoard'];
+  journalRecoveries: AppController['journalRecoveries'];
+  recoverJournal: AppController['recoverJournal'];
+  discardJournal: AppController['discardJournal'];
+  trashOpen: AppController['trashOpen'];
+  trashedBoards: AppController['trashedBoards'];
+  trashRetentionDays: AppController['trashRetentionDays'];
+  trashError: AppController['trashError'];
+  closeTrash: AppController['closeTrash'];
+  restoreTrashedBoard: AppController['restoreTrashedBoard'];
+  restoreTrashedFolder: AppController['restoreTrashedFolder'];
+  purgeTrashedBoard: AppController['purgeTrashedBoard'];
+  emptyTrash: AppController['emptyTrash'];
+  setTrashRetentionDays: AppController['setTrashRetentionDays'];
 }
 
 function AppLayout({
   items,
+  pinnedBoards,
   activeBoardId,
   thumbnails,
   handleSelectBoard,
@@ -604,7 +900,8 @@ function AppLayout({
   renameBoard,
   deleteBoard,
   duplicateBoard,
-  setBoardsIndex,
+  applyBoardListChange,
+  setBoardPinned,
   handleExportPng,
   handleCopyPng,
   handleExportSvg,
@@ -629,11 +926,28 @@ function AppLayout({
   closeCommandPalette,
   commandPaletteCommands,
   errorMessage,
+  activeBoardStale,
+  reloadBoardData,
+  handleMergeStaleBoard,
+  journalRecoveries,
+  recoverJournal,
+  discardJournal,
+  trashOpen,
+  trashedBoards,
+  trashRetentionDays,
+  trashError,
+  closeTrash,
+  restoreTrashedBoard,
+  restoreTrashedFolder,
+  purgeTrashedBoard,
+  emptyTrash,
+  setTrashRetentionDays,
 }: AppLayoutProps) {
   return (
     <div className="app">
       <BoardList
         items={items}
+        pinnedBoards={pinnedBoards}
         activeBoardId={activeBoardId}
         thumbnails={thumbnails}
         onSelectBoard={handleSelectBoard}
@@ -641,7 +955,8 @@ function AppLayout({
         onRenameBoard={renameBoard}
         onDeleteBoard={deleteBoard}
         onDuplicateBoard={duplicateBoard}
-        onUpdateItems={setBoardsIndex}
+        onApplyChange={applyBoardListChange}
+        onSetBoardPinned={setBoardPinned}
         onExportPng={handleExportPng}
         onCopyPng={handleCopyPng}
         onExportSvg={handleExportSvg}
@@ -662,6 +977,7 @@ function AppLayout({
         boardDataLoading={boardDataLoading}
         activeBoardName={activeBoardName}
         handleDataChange={handleDataChange}
+        journalBoardChanges={journalBoardChanges}
         handleThumbnailGenerated={handleThumbnailGenerated}
         currentBoardData={currentBoardData}
         excalidrawRef={excalidrawRef}
@@ -670,6 +986,28 @@ function AppLayout({
         <CommandPalette onClose={closeCommandPalette} commands={commandPaletteCommands} />
       ) : null}
       <AppErrorToast message={errorMessage} />
+      <StaleBoardToast
+        visible={activeBoardStale}
+        onReload={reloadBoardData}
+        onMerge={handleMergeStaleBoard}
+      />
+      <JournalRecoveryModal
+        recoveries={journalRecoveries}
+        onRecover={recoverJournal}
+        onDiscard={discardJournal}
+      />
+      <TrashModal
+        open={trashOpen}
+        trashedBoards={trashedBoards}
+        retentionDays={trashRetentionDays}
+        error={trashError}
+        onClose={closeTrash}
+        onRestoreBoard={restoreTrashedBoard}
+        onRestoreFolder={restoreTrashedFolder}
+        onPurgeBoard={purgeTrashedBoard}
+        onEmptyTrash={emptyTrash}
+        onRetentionDaysChange={setTrashRetentionDays}
+      />
     </div>
   );
 }
@@ -677,6 +1015,7 @@ function AppLayout({
 function App() {
   const {
     items,
+    pinnedBoards,
     activeBoardId,
     loading,
     error,
@@ -684,14 +1023,19 @@ function App() {
     renameBoard,
     deleteBoard,
     duplicateBoard,
-    setBoardsIndex,
+    applyBoardListChange,
+    setBoardPinned,
     currentBoardData,
     boardDataLoading,
+    staleBoardId,
+    reloadBoardData,
     exportBusy,
     exportError,
     boardsExportBusy,
     boardsImportBusy,
     settingsError,
+    backupError,
+    startupError,
     hideExportRow,
     showTimestamps,
     setHideExportRow,
@@ -701,12 +1045,27 @@ function App() {
     activeBoardName,
     handleThumbnailGenerated,
     handleDataChange,
+    journalBoardChanges,
     handleExportPng,
     handleCopyPng,
     handleExportSvg,
     handleExportBoards,
     handleImportBoards,
     handleSelectBoard,
+    handleMergeStaleBoard,
+    journalRecoveries,
+    recoverJournal,
+    discardJournal,
+    trashOpen,
+    trashedBoards,
+    trashRetentionDays,
+    trashError,
+    closeTrash,
+    restoreTrashedBoard,
+    restoreTrashedFolder,
+    purgeTrashedBoard,
+    emptyTrash,
+    setTrashRetentionDays,
     sidebarCollapsed,
     toggleSidebar,
   } = useAppController();
@@ -743,11 +1102,13 @@ function App() {
     return <FullScreenLoading message="Loading boards..." />;
   }
 
-  const errorMessage = error ?? exportError ?? settingsError;
+  const errorMessage = error ?? exportError ?? settingsError ?? backupError ?? startupError;
+  const activeBoardStale = staleBoardId !== null && staleBoardId === activeBoardId;
 
   return (
     <AppLayout
       items={items}
+      pinnedBoards={pinnedBoards}
       activeBoardId={activeBoardId}
       thumbnails={thumbnails}
       handleSelectBoard={handleSelectBoard}
@@ -755,7 +1116,8 @@ function App() {
       renameBoard={renameBoard}
       deleteBoard={deleteBoard}
       duplicateBoard={duplicateBoard}
-      setBoardsIndex={setBoardsIndex}
+      applyBoardListChange={applyBoardListChange}
+      setBoardPinned={setBoardPinned}
       handleExportPng={handleExportPng}
       handleCopyPng={handleCopyPng}
       handleExportSvg={handleExportSvg}
@@ -773,6 +1135,7 @@ function App() {
       activeBoardName={activeBoardName}
       boardDataLoading={boardDataLoading}
       handleDataChange={handleDataChange}
+      journalBoardChanges={journalBoardChanges}
       handleThumbnailGenerated={handleThumbnailGenerated}
       currentBoardData={currentBoardData}
       excalidrawRef={excalidrawRef}
@@ -780,6 +1143,22 @@ function App() {
       closeCommandPalette={closeCommandPalette}
       commandPaletteCommands={commandPaletteCommands}
       errorMessage={errorMessage}
+      activeBoardStale={activeBoardStale}
+      reloadBoardData={reloadBoardData}
+      handleMergeStaleBoard={handleMergeStaleBoard}
+      journalRecoveries={journalRecoveries}
+      recoverJournal={recoverJournal}
+      discardJournal={discardJournal}
+      trashOpen={trashOpen}
+      trashedBoards={trashedBoards}
+      trashRetentionDays={trashRetentionDays}
+      trashError={trashError}
+      closeTrash={closeTrash}
+      restoreTrashedBoard={restoreTrashedBoard}
+      restoreTrashedFolder={restoreTrashedFolder}
+      purgeTrashedBoard={purgeTrashedBoard}
+      emptyTrash={emptyTrash}
+      setTrashRetentionDays={setTrashRetentionDays}
     />
   );
 }
@@ -3,7 +3,6 @@ import { createPortal } from 'react-dom';
 import { invoke } from '@tauri-apps/api/core';
 import { getVersion } from '@tauri-apps/api/app';
 import { confirm, open as openDialog } from '@tauri-apps/plugin-dialog';
-import { readTextFile } from '@tauri-apps/plugin-fs';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { FontAwesomeIcon } from '@fortawesome/react-fontawesome';
 import {
@@ -18,11 +17,14 @@ import {
   faEllipsisVertical,
   faFileCode,
   faFileImage,
+  faFilter,
+  faFolderMinus,
   faGear,
   faGripVertical,
   faPen,
   faPlus,
   faStar,
+  faThumbtack,
   faTrash,
   faUpload,
 } from '@fortawesome/free-solid-svg-icons';
@@ -44,18 +46,24 @@ import { restrictToVerticalAxis, restrictToWindowEdges } from '@dnd-kit/modifier
 import type {
   Board,
   BoardFolder,
+  BoardListChange,
   BoardListItem,
+  BoardSmartFolder,
   BoardsExportEntry,
   BoardsExportFile,
   BoardsImportResult,
+  ImportAction,
+  ImportConflictStrategy,
+  ImportEntryDecision,
 } from '../types/board';
+import { flattenBoardTree } from '../types/board';
 import {
   applyBoardDrop,
   applyFolderDrop,
   calculateDropPosition,
-  cleanupFolders,
   findBoardById,
   findFolderById,
+  findSmartFolderById,
   makeDragId,
   parseDragId,
   type DropPosition,
@@ -68,6 +76,7 @@ import './BoardList.css';
 
 interface BoardListProps {
   items: BoardListItem[];
+  pinnedBoards: Board[];
   activeBoardId: string | null;
   thumbnails: Record<string, string>;
   onSelectBoard: (boardId: string) => void;
@@ -75,12 +84,17 @@ interface BoardListProps {
   onRenameBoard: (boardId: string, newName: string) => void;
   onDeleteBoard: (boardId: string) => void;
   onDuplicateBoard: (boardId: string, newName: string) => void;
-  onUpdateItems: (items: BoardListItem[]) => void;
+  onApplyChange: (change: BoardListChange) => Promise<boolean>;
+  onSetBoardPinned: (boardId: string, pinned: boolean) => Promise<boolean>;
   onExportPng: () => void;
   onCopyPng: () => void;
   onExportSvg: () => void;
   onExportBoards: () => Promise<void>;
-  onImportBoards: (filePath: string, selectedIndices: number[]) => Promise<BoardsImportResult>;
+  onImportBoards: (
+    filePath: string,
+    selectedIndices: number[],
+    strategy: ImportConflictStrategy,
+  ) => Promise<BoardsImportResult>;
   exportDisabled: boolean;
   boardsExporting: boolean;
   boardsImporting: boolean;
@@ -105,15 +119,40 @@ interface ImportBoardEntry extends BoardsExportEntry {
   index: number;
 }
 
+const IMPORT_STRATEGY_OPTIONS: { value: ImportConflictStrategy; label: string }[] = [
+  { value: 'keep_both', label: 'Keep both (import duplicates as copies)' },
+  { value: 'skip', label: 'Skip boards that already exist' },
+  { value: 'replace_if_newer', label: 'Replace existing boards with newer versions' },
+  { value: 'preserve_id', label: 'Keep original ids, import duplicates as copies' },
+  { value: 'merge', label: 'Merge changes into existing boards' },
+];
+
+const IMPORT_ACTION_LABELS: Partial<Record<ImportAction, string>> = {
+  copy: 'Copy',
+  replace: 'Replace',
+  merge: 'Merge',
+  skip: 'Skip',
+};
+
 // =============================================================================
 // Utility Functions
 // =============================================================================
 
+// Smart folders share the collapsed state with folders.
+const collectFolderIds = (items: BoardListItem[]): string[] =>
+  items.flatMap((item) => {
+    if (item.type === 'folder') return [item.id, ...collectFolderIds(item.items)];
+    if (item.type === 'smart_folder') return [item.id];
+    return [];
+  });
+
+const countFolderBoards = (folder: BoardFolder): number => flattenBoardTree(folder.items).length;
+
 const pruneCollapsedFolderState = (
   collapsed: Record<string, boolean>,
   items: BoardListItem[],
 ): Record<string, boolean> => {
-  const folderIds = new Set(items.filter((item) => item.type === 'folder').map((item) => item.id));
+  const folderIds = new Set(collectFolderIds(items));
   const next: Record<string, boolean> = {};
   for (const [id, value] of Object.entries(collapsed)) {
     if (folderIds.has(id)) {
@@ -204,8 +243,6 @@ interface DraggableBoardItemProps {
   formatDate: (date: string) => string;
   showTimestamps: boolean;
   disabled?: boolean;
-  inFolder?: boolean;
-  parentFolderId?: string;
   dropPosition?: DropPosition | null;
   isDragSource?: boolean;
   onMouseEnter?: (e: React.MouseEvent<HTMLDivElement>) => void;
@@ -225,8 +262,6 @@ function DraggableBoardItem({
   formatDate,
   showTimestamps,
   disabled,
-  inFolder,
-  parentFolderId,
   dropPosition,
   isDragSource,
   onMouseEnter,
@@ -240,12 +275,12 @@ function DraggableBoardItem({
   } = useDraggable({
     id: board.id,
     disabled,
-    data: { type: 'board', inFolder, parentFolderId },
+    data: { type: 'board' },
   });
 
   const { setNodeRef: setDroppableRef } = useDroppable({
     id: board.id,
-    data: { type: 'board', inFolder, parentFolderId },
+    data: { type: 'board' },
   });
 
   const setNodeRef = (node: HTMLElement | null) => {
@@ -419,7 +454,7 @@ function DraggableFolderItem({
             <FontAwesomeIcon icon={faGripVertical} />
           </span>
           <span className="folder-name">{folder.name}</span>
-          <span className="folder-count">{folder.items.length}</span>
+          <span className="folder-count">{countFolderBoards(folder)}</span>
           <button
             className="menu-btn"
             onPointerDown={(e) => e.stopPropagation()}
@@ -434,6 +469,79 @@ function DraggableFolderItem({
   );
 }
 
+interface BoardShortcutItemProps {
+  board: Board;
+  isActive: boolean;
+  onSelect: () => void;
+}
+
+/** A board listed outside its place in the tree, which only selects it. */
+function BoardShortcutItem({ board, isActive, onSelect }: BoardShortcutItemProps) {
+  return (
+    <div className={`board-item ${isActive ? 'active' : ''}`} onClick={onSelect}>
+      <div className="board-info">
+        <div className="board-text">
+          <span className="board-name">{board.name}</span>
+        </div>
+      </div>
+    </div>
+  );
+}
+
+interface SmartFolderItemProps {
+  smartFolder: BoardSmartFolder;
+  activeBoardId: string | null;
+  isCollapsed: boolean;
+  onToggleCollapse: () => void;
+  onSelectBoard: (boardId: string) => void;
+  onOpenMenu: (e: React.MouseEvent<HTMLButtonElement>) => void;
+}
+
+// Its boards are also listed in their own place, so nothing here is draggable.
+function SmartFolderItem({
+  smartFolder,
+  activeBoardId,
+  isCollapsed,
+  onToggleCollapse,
+  onSelectBoard,
+  onOpenMenu,
+}: SmartFolderItemProps) {
+  return (
+    <div className="board-folder smart-folder" data-testid={`smart-folder-${smartFolder.id}`}>
+      <div className="board-folder-header">
+        <button
+          type="button"
+          className={`folder-toggle ${isCollapsed ? 'collapsed' : ''}`}
+          onClick={onToggleCollapse}
+          aria-label={isCollapsed ? 'Expand smart folder' : 'Collapse smart folder'}
+        >
+          <FontAwesomeIcon icon={isCollapsed ? faChevronRight : faChevronDown} />
+        </button>
+        <span className="smart-folder-icon" aria-hidden="true">
+          <FontAwesomeIcon icon={faFilter} />
+        </span>
+        <span className="folder-name">{smartFolder.name}</span>
+        <span className="folder-count">{smartFolder.boards.length}</span>
+        <button className="menu-btn" onClick={onOpenMenu}>
+          <FontAwesomeIcon icon={faEllipsisVertical} />
+        </button>
+      </div>
+      {!isCollapsed && (
+        <div className="folder-items">
+          {smartFolder.boards.map((board) => (
+            <BoardShortcutItem
+              key={board.id}
+              board={board}
+              isActive={board.id === activeBoardId}
+              onSelect={() => onSelectBoard(board.id)}
+            />
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
+
 // =============================================================================
 // Drag Overlay Components (Ghost preview while dragging)
 // =============================================================================
@@ -475,7 +583,7 @@ function FolderOverlay({ folder }: FolderOverlayProps) {
           <FontAwesomeIcon icon={faGripVertical} />
         </span>
         <span className="folder-name">{folder.name}</span>
-        <span className="folder-count">{folder.items.length}</span>
+        <span className="folder-count">{countFolderBoards(folder)}</span>
       </div>
     </div>
   );
@@ -487,6 +595,7 @@ function FolderOverlay({ folder }: FolderOverlayProps) {
 
 export function BoardList({
   items,
+  pinnedBoards,
   activeBoardId,
   thumbnails,
   onSelectBoard,
@@ -494,7 +603,8 @@ export function BoardList({
   onRenameBoard,
   onDeleteBoard,
   onDuplicateBoard,
-  onUpdateItems,
+  onApplyChange,
+  onSetBoardPinned,
   onExportPng,
   onCopyPng,
   onExportSvg,
@@ -519,7 +629,7 @@ export function BoardList({
   const [editingFolderId, setEditingFolderId] = useState<string | null>(null);
   const [editFolderName, setEditFolderName] = useState('');
   const [activeMenu, setActiveMenu] = useState<{
-    type: 'board' | 'folder';
+    type: 'board' | 'folder' | 'smart_folder';
     id: string;
     anchorRect: DOMRect;
   } | null>(null);
@@ -538,6 +648,8 @@ export function BoardList({
   const [importError, setImportError] = useState<string | null>(null);
   const [importSourceName, setImportSourceName] = useState<string | null>(null);
   const [importFilePath, setImportFilePath] = useState<string | null>(null);
+  const [importStrategy, setImportStrategy] = useState<ImportConflictStrategy>('keep_both');
+  const [importDecisions, setImportDecisions] = useState<Record<number, ImportAction>>({});
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [appVersion, setAppVersion] = useState<string | null>(null);
   const releasesUrl = 'https://github.com/clFaster/ExcaStoneBoard/releases';
@@ -556,17 +668,7 @@ export function BoardList({
   // ---------------------------------------------------------------------------
   // Memoized Data
   // ---------------------------------------------------------------------------
-  type FlattenedBoard = { board: Board; folderId?: string };
-
-  const flattenedBoards = useMemo<FlattenedBoard[]>(
-    () =>
-      items.flatMap<FlattenedBoard>((item) =>
-        item.type === 'folder'
-          ? item.items.map((board) => ({ board, folderId: item.id }))
-          : [{ board: item }],
-      ),
-    [items],
-  );
+  const flattenedBoards = useMemo(() => flattenBoardTree(items), [items]);
 
   const existingBoardIds = useMemo(
     () => new Set(flattenedBoards.map((entry) => entry.board.id)),
@@ -712,14 +814,6 @@ export function BoardList({
     };
   }, [boardsExporting, boardsImporting, settingsOpen]);
 
-  // Cleanup folders (convert single-item folders to boards, remove empty ones)
-  useEffect(() => {
-    const normalized = cleanupFolders(items);
-    if (JSON.stringify(normalized) !== JSON.stringify(items)) {
-      onUpdateItems(normalized);
-    }
-  }, [items, onUpdateItems]);
-
   // ---------------------------------------------------------------------------
   // Handlers
   // ---------------------------------------------------------------------------
@@ -753,24 +847,50 @@ export function BoardList({
 
   const handleSaveFolderEdit = (folderId: string) => {
     if (editFolderName.trim()) {
-      const nextItems = items.map((item) =>
-        item.type === 'folder' && item.id === folderId
-          ? { ...item, name: editFolderName.trim() }
-          : item,
-      );
-      onUpdateItems(nextItems);
+      void onApplyChange({ kind: 'rename_folder', folderId, name: editFolderName.trim() });
     }
     setEditingFolderId(null);
     setEditFolderName('');
   };
 
+  const handleUngroupFolder = (folder: BoardFolder) => {
+    void onApplyChange({ kind: 'delete_folder', folderId: folder.id, mode: 'keep_boards' });
+    setActiveMenu(null);
+  };
+
+  const handleDeleteFolder = async (folder: BoardFolder) => {
+    const message = `Move "${folder.name}" and all boards in it to the trash?`;
+    const shouldDelete = await (async () => {
+      try {
+        return await confirm(message, { title: 'Delete folder', kind: 'warning' });
+      } catch {
+        return window.confirm(message);
+      }
+    })();
+
+    if (shouldDelete) {
+      await onApplyChange({ kind: 'delete_folder', folderId: folder.id, mode: 'trash_boards' });
+    }
+    setActiveMenu(null);
+  };
+
+  const handleDeleteSmartFolder = (smartFolderId: string) => {
+    void onApplyChange({ kind: 'delete_smart_folder', smartFolderId });
+    setActiveMenu(null);
+  };
+
+  const handleTogglePinned = (board: Board) => {
+    void onSetBoardPinned(board.id, !board.pinned);
+    setActiveMenu(null);
+  };
+
   const handleDuplicate = (board: Board) => {
     onDuplicateBoard(board.id, `${board.name} (Copy)`);
     setActiveMenu(null);
   };
 
   const handleDelete = async (board: Board) => {
-    const message = `Delete "${board.name}"?`;
+    const message = `Move "${board.name}" to the trash?`;
     const shouldDelete = await (async () => {
       try {
         return await confirm(message, { title: 'Delete board', kind: 'warning' });
@@ -810,9 +930,15 @@ export function BoardList({
     setImportError(null);
     setImportSourceName(null);
     setImportFilePath(null);
+    setImportStrategy('keep_both');
+    setImportDecisions({});
   };
 
   const openSettings = () => setSettingsOpen(true);
+  const openTrash = () => {
+    setSettingsOpen(false);
+    window.dispatchEvent(new CustomEvent('app:open-trash'));
+  };
   const closeSettings = () => {
     if (boardsExporting || boardsImporting) return;
     setSettingsOpen(false);
@@ -839,7 +965,7 @@ export function BoardList({
               title: 'Import boards',
               multiple: false,
               directory: false,
-              filters: [{ name: 'Boards export', extensions: ['json'] }],
+              filters: [{ name: 'Boards export', extensions: ['esb', 'json'] }],
             }),
           );
 
@@ -847,8 +973,9 @@ export function BoardList({
         return;
       }
 
-      const content = await readTextFile(filePath);
-      const parsed = JSON.parse(content) as Partial<BoardsExportFile>;
+      const parsed = await invoke<Partial<BoardsExportFile>>('read_boards_import_file', {
+        filePath,
+      });
       const entries = buildImportBoards(parsed);
 
       if (entries.length === 0) {
@@ -882,6 +1009,32 @@ export function BoardList({
     };
   }, [handleOpenImport]);
 
+  useEffect(() => {
+    if (!importDialogOpen || !importFilePath) return;
+    let cancelled = false;
+
+    const selectedIndices = selectedImportBoards.map((entry) => entry.index);
+    invoke<ImportEntryDecision[]>('preview_boards_import', {
+      filePath: importFilePath,
+      selectedIndices,
+      strategy: importStrategy,
+    })
+      .then((decisions) => {
+        if (cancelled) return;
+        setImportDecisions(
+          Object.fromEntries(decisions.map((decision) => [decision.index, decision.action])),
+        );
+      })
+      .catch((e) => {
+        console.warn('Failed to preview import:', e);
+        if (!cancelled) setImportDecisions({});
+      });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [importDialogOpen, importFilePath, importStrategy, selectedImportBoards]);
+
   const handleToggleImportSelection = (key: string) => {
     setImportSelection((prev) => ({ ...prev, [key]: !prev[key] }));
   };
@@ -904,7 +1057,7 @@ export function BoardList({
         return;
       }
       const selectedIndices = selectedImportBoards.map((entry) => entry.index);
-      await onImportBoards(importFilePath, selectedIndices);
+      await onImportBoards(importFilePath, selectedIndices, importStrategy);
       closeImportDialog();
     } catch (e) {
       console.error('Import failed:', e);
@@ -914,7 +1067,7 @@ export function BoardList({
 
   const openMenu = (
     event: React.MouseEvent<HTMLButtonElement>,
-    type: 'board' | 'folder',
+    type: 'board' | 'folder' | 'smart_folder',
     id: string,
   ) => {
     event.stopPropagation();
@@ -1049,16 +1202,12 @@ export function BoardList({
 
     const activeParsed = parseDragId(String(active.id));
     const overParsed = parseDragId(String(over.id));
-    const overData = over.data.current as
-      { type?: string; inFolder?: boolean; parentFolderId?: string } | undefined;
-    const isOverInFolder = overData?.inFolder ?? false;
 
     const dropPosition = calculateDropPosition(
       pointerY,
       over.rect,
       activeParsed.type,
       overParsed.type,
-      isOverInFolder,
     );
 
     setDragState((prev) => ({
@@ -1086,33 +1235,26 @@ export function BoardList({
 
     const activeParsed = parseDragId(String(active.id));
     const overParsed = parseDragId(String(over.id));
-    const overData = over.data.current as
-      { type?: string; inFolder?: boolean; parentFolderId?: string } | undefined;
-    const isOverInFolder = overData?.inFolder ?? false;
-    const parentFolderId = overData?.parentFolderId;
 
     const dropPosition = currentDragState.dropPosition ?? 'after';
 
-    const nextItems =
+    const change =
       activeParsed.type === 'folder'
         ? applyFolderDrop({
             folderId: activeParsed.id,
             over: overParsed,
-            isOverInFolder,
-            parentFolderId,
             dropPosition,
             items,
           })
         : applyBoardDrop({
             boardId: activeParsed.id,
             over: overParsed,
-            isOverInFolder,
             dropPosition,
             items,
           });
 
-    if (nextItems) {
-      onUpdateItems(nextItems);
+    if (change) {
+      void onApplyChange(change);
     }
   };
 
@@ -1130,6 +1272,10 @@ export function BoardList({
             <FontAwesomeIcon icon={faPen} />
             Rename
           </button>
+          <button data-testid="board-action-pin" onClick={() => handleTogglePinned(board)}>
+            <FontAwesomeIcon icon={faThumbtack} />
+            {board.pinned ? 'Unpin' : 'Pin'}
+          </button>
           <button data-testid="board-action-duplicate" onClick={() => handleDuplicate(board)}>
             <FontAwesomeIcon icon={faClone} />
             Duplicate
@@ -1146,6 +1292,17 @@ export function BoardList({
       );
     }
 
+    if (activeMenu.type === 'smart_folder') {
+      const smartFolder = findSmartFolderById(items, activeMenu.id);
+      if (!smartFolder) return null;
+      return (
+        <button className="danger" onClick={() => handleDeleteSmartFolder(smartFolder.id)}>
+          <FontAwesomeIcon icon={faTrash} />
+          Delete Smart Folder
+        </button>
+      );
+    }
+
     const folder = getFolderById(activeMenu.id);
     if (!folder) return null;
     return (
@@ -1154,6 +1311,14 @@ export function BoardList({
           <FontAwesomeIcon icon={faPen} />
           Rename Folder
         </button>
+        <button onClick={() => handleUngroupFolder(folder)}>
+          <FontAwesomeIcon icon={faFolderMinus} />
+          Ungroup
+        </button>
+        <button className="danger" onClick={() => handleDeleteFolder(folder)}>
+          <FontAwesomeIcon icon={faTrash} />
+          Delete Folder
+        </button>
       </>
     );
   })();
@@ -1170,6 +1335,75 @@ export function BoardList({
     return getBoardById(parsed.id);
   }, [dragState.activeId, getBoardById, getFolderById]);
 
+  const renderListItem = (item: BoardListItem): React.ReactNode => {
+    if (item.type === 'smart_folder') {
+      return (
+        <SmartFolderItem
+          key={item.id}
+          smartFolder={item}
+          activeBoardId={activeBoardId}
+          isCollapsed={isFolderCollapsed(item.id)}
+          onToggleCollapse={() => toggleFolderCollapsed(item.id)}
+          onSelectBoard={onSelectBoard}
+          onOpenMenu={(e) => openMenu(e, 'smart_folder', item.id)}
+        />
+      );
+    }
+
+    if (item.type === 'folder') {
+      const folderId = makeDragId('folder', item.id);
+      const isOverFolder = dragState.overId === folderId;
+      const folderDropPosition = isOverFolder ? dragState.dropPosition : null;
+      const isFolderDragSource = dragState.activeId === folderId;
+
+      return (
+        <DraggableFolderItem
+          key={item.id}
+          folder={item}
+          isCollapsed={isFolderCollapsed(item.id)}
+          isEditing={editingFolderId === item.id}
+          editName={editFolderName}
+          onEditNameChange={setEditFolderName}
+          onSaveEdit={() => handleSaveFolderEdit(item.id)}
+          onCancelEdit={() => setEditingFolderId(null)}
+          onToggleCollapse={() => toggleFolderCollapsed(item.id)}
+          onOpenMenu={(e) => openMenu(e, 'folder', item.id)}
+          disabled={dragDisabled || editingFolderId === item.id}
+          dropPosition={folderDropPosition}
+          isDragSource={isFolderDragSource}
+        >
+          {item.items.map(renderListItem)}
+        </DraggableFolderItem>
+      );
+    }
+
+    const isOverBoard = dragState.overId === item.id;
+    const boardDropPosition = isOverBoard ? dragState.dropPosition : null;
+    const isBoardDragSource = dragState.activeId === item.id;
+
+    return (
+      <DraggableBoardItem
+        key={item.id}
+        board={item}
+        isActive={item.id === activeBoardId}
+        isEditing={editingId === item.id}
+        editName={editName}
+        onEditNameChange={setEditName}
+        onSaveEdit={() => handleSaveEdit(item.id)}
+        onCancelEdit={() => setEditingId(null)}
+        onSelect={() => onSelectBoard(item.id)}
+        onOpenMenu={(e) => openMenu(e, 'board', item.id)}
+        formatDate={formatDate}
+        showTimestamps={showTimestamps}
+        disabled={dragDisabled || editingId === item.id}
+        dropPosition={boardDropPosition}
+        isDragSource={isBoardDragSource}
+        onMouseEnter={(e) => handleBoardMouseEnter(item.id, e)}
+        onMouseLeave={handleBoardMouseLeave}
+      />
+    );
+  };
+
   // ---------------------------------------------------------------------------
   // Render collapsed view
   // ---------------------------------------------------------------------------
@@ -1282,94 +1516,26 @@ export function BoardList({
         </form>
 
         <div className="boards-scroll" ref={boardsScrollRef}>
+          {pinnedBoards.length > 0 && (
+            <div className="pinned-boards" data-testid="pinned-boards">
+              <div className="pinned-boards-title">Pinned</div>
+              {pinnedBoards.map((board) => (
+                <BoardShortcutItem
+                  key={board.id}
+                  board={board}
+                  isActive={board.id === activeBoardId}
+                  onSelect={() => onSelectBoard(board.id)}
+                />
+              ))}
+            </div>
+          )}
           {items.length === 0 ? (
             <div className="no-boards">
               <p>No boards yet</p>
               <p className="hint">Create a new board to get started</p>
             </div>
           ) : (
-            items.map((item) => {
-              if (item.type === 'folder') {
-                const folderId = makeDragId('folder', item.id);
-                const isOverFolder = dragState.overId === folderId;
-                const folderDropPosition = isOverFolder ? dragState.dropPosition : null;
-                const isFolderDragSource = dragState.activeId === folderId;
-
-                return (
-                  <DraggableFolderItem
-                    key={item.id}
-                    folder={item}
-                    isCollapsed={isFolderCollapsed(item.id)}
-                    isEditing={editingFolderId === item.id}
-                    editName={editFolderName}
-                    onEditNameChange={setEditFolderName}
-                    onSaveEdit={() => handleSaveFolderEdit(item.id)}
-                    onCancelEdit={() => setEditingFolderId(null)}
-                    onToggleCollapse={() => toggleFolderCollapsed(item.id)}
-                    onOpenMenu={(e) => openMenu(e, 'folder', item.id)}
-                    disabled={dragDisabled || editingFolderId === item.id}
-                    dropPosition={folderDropPosition}
-                    isDragSource={isFolderDragSource}
-                  >
-                    {item.items.map((board) => {
-                      const isOverBoard = dragState.overId === board.id;
-                      const boardDropPosition = isOverBoard ? dragState.dropPosition : null;
-                      const isBoardDragSource = dragState.activeId === board.id;
-
-                      return (
-                        <DraggableBoardItem
-                          key={board.id}
-                          board={board}
-                          isActive={board.id === activeBoardId}
-                          isEditing={editingId === board.id}
-                          editName={editName}
-                          onEditNameChange={setEditName}
-                          onSaveEdit={() => handleSaveEdit(board.id)}
-                          onCancelEdit={() => setEditingId(null)}
-                          onSelect={() => onSelectBoard(board.id)}
-                          onOpenMenu={(e) => openMenu(e, 'board', board.id)}
-                          formatDate={formatDate}
-                          showTimestamps={showTimestamps}
-                          disabled={dragDisabled || editingId === board.id}
-                          inFolder
-                          parentFolderId={item.id}
-                          dropPosition={boardDropPosition}
-                          isDragSource={isBoardDragSource}
-                          onMouseEnter={(e) => handleBoardMouseEnter(board.id, e)}
-                          onMouseLeave={handleBoardMouseLeave}
-                        />
-                      );
-                    })}
-                  </DraggableFolderItem>
-                );
-              }
-
-              const isOverBoard = dragState.overId === item.id;
-              const boardDropPosition = isOverBoard ? dragState.dropPosition : null;
-              const isBoardDragSource = dragState.activeId === item.id;
-
-              return (
-                <DraggableBoardItem
-                  key={item.id}
-                  board={item}
-                  isActive={item.id === activeBoardId}
-                  isEditing={editingId === item.id}
-                  editName={editName}
-                  onEditNameChange={setEditName}
-                  onSaveEdit={() => handleSaveEdit(item.id)}
-                  onCancelEdit={() => setEditingId(null)}
-                  onSelect={() => onSelectBoard(item.id)}
-                  onOpenMenu={(e) => openMenu(e, 'board', item.id)}
-                  formatDate={formatDate}
-                  showTimestamps={showTimestamps}
-                  disabled={dragDisabled || editingId === item.id}
-                  dropPosition={boardDropPosition}
-                  isDragSource={isBoardDragSource}
-                  onMouseEnter={(e) => handleBoardMouseEnter(item.id, e)}
-                  onMouseLeave={handleBoardMouseLeave}
-                />
-              );
-            })
+            items.map(renderListItem)
           )}
         </div>
 
@@ -1430,6 +1596,10 @@ export function BoardList({
                       <FontAwesomeIcon icon={faUpload} />
                       {boardsImporting ? 'Importing...' : 'Import boards'}
                     </button>
+                    <button type="button" className="settings-action-btn" onClick={openTrash}>
+                      <FontAwesomeIcon icon={faTrash} />
+                      Open trash
+                    </button>
                   </div>
                 </div>
                 <div className="settings-section">
@@ -1520,12 +1690,34 @@ export function BoardList({
                     Clear
                   </button>
                 </div>
+                <label className="import-strategy">
+                  <span>When a board already exists</span>
+                  <select
+                    value={importStrategy}
+                    onChange={(event) =>
+                      setImportStrategy(event.target.value as ImportConflictStrategy)
+                    }
+                    disabled={boardsImporting}
+                  >
+                    {IMPORT_STRATEGY_OPTIONS.map((option) => (
+                      <option key={option.value} value={option.value}>
+                        {option.label}
+                      </option>
+                    ))}
+                  </select>
+                </label>
                 <div className="import-list">
                   {importBoards.map((entry) => {
                     const isSelected = Boolean(importSelection[entry.key]);
                     const hasId = Boolean(entry.id);
                     const isDuplicate =
                       hasId && (existingBoardIds.has(entry.id) || duplicateImportIds.has(entry.id));
+                    const decision = isSelected ? importDecisions[entry.index] : undefined;
+                    const badgeLabel = decision
+                      ? IMPORT_ACTION_LABELS[decision]
+                      : isDuplicate
+                        ? 'Duplicate'
+                        : undefined;
                     return (
                       <label
                         key={entry.key}
@@ -1539,7 +1731,7 @@ export function BoardList({
                         />
                         <span className="import-checkmark" aria-hidden="true"></span>
                         <span className="import-item-name">{entry.name}</span>
-                        {isDuplicate && <span className="import-item-duplicate">Duplicate</span>}
+                        {badgeLabel && <span className="import-item-duplicate">{badgeLabel}</span>}
                       </label>
                     );
                   })}
@@ -15,9 +15,11 @@ import type {
 } from '@excalidraw/excalidraw/types';
 import type { ExcalidrawElement } from '@excalidraw/excalidraw/element/types';
 import type { AppState } from '@excalidraw/excalidraw/types';
+import type { BoardDataPatch } from '../types/board';
 import './ExcalidrawFrame.css';
 
 const SAVE_DEBOUNCE_MS = 1000;
+const JOURNAL_THROTTLE_MS = 200;
 const THUMBNAIL_DEBOUNCE_MS = 5000;
 const THUMBNAIL_MAX_DIM = 320;
 
@@ -36,7 +38,14 @@ export interface ExcalidrawData {
 interface ExcalidrawFrameProps {
   boardId: string | null;
   boardName: string | null;
-  onDataChange: (boardId: string, data: ExcalidrawData) => Promise<void>;
+  /** `patch` holds the changes since the last successful save, or is null for a full save. */
+  onDataChange: (
+    boardId: string,
+    data: ExcalidrawData,
+    patch: BoardDataPatch | null,
+  ) => Promise<boolean>;
+  /** Receives every change since the last successful save, well before the next save. */
+  onJournalChange: (boardId: string, patch: BoardDataPatch) => void;
   onThumbnailGenerated: (boardId: string, dataUrl: string) => void;
   initialData: ExcalidrawData | null;
 }
@@ -46,6 +55,8 @@ export interface ExcalidrawFrameHandle {
   copyPng: () => Promise<void>;
   exportSvg: () => Promise<void>;
   flushSave: () => Promise<void>;
+  /** The scene for merging, including elements deleted in this window as `isDeleted`. */
+  getSceneData: () => ExcalidrawData | null;
 }
 
 const clearTimer = (timerRef: React.MutableRefObject<number | null>) => {
@@ -113,6 +124,13 @@ const getSceneSnapshot = (api: ExcalidrawImperativeAPI): SceneSnapshot => ({
   files: api.getFiles(),
 });
 
+// Merges need the `isDeleted` tombstones; without them an element deleted here is taken from the
+// stored scene and comes back.
+const getSceneSnapshotIncludingDeleted = (api: ExcalidrawImperativeAPI): SceneSnapshot => ({
+  ...getSceneSnapshot(api),
+  elements: api.getSceneElementsIncludingDeleted() as ExcalidrawElement[],
+});
+
 const createExportPngBlob = async (snapshot: SceneSnapshot): Promise<Blob> =>
   exportToBlob({
     elements: snapshot.elements,
@@ -246,6 +264,51 @@ const getInitialDataState = (initialData: ExcalidrawData | null): ExcalidrawInit
 
 type ExcalidrawApiRef = React.MutableRefObject<ExcalidrawImperativeAPI | null>;
 
+/** What the backend holds after the last successful save, enough to compute the next patch. */
+interface SavedScene {
+  elementVersions: Map<string, number>;
+  fileIds: Set<string>;
+  appState: string;
+}
+
+// New boards are stored in the legacy layout, which has no `elements` array.
+const toSavedScene = (data: ExcalidrawData): SavedScene => ({
+  elementVersions: new Map((data.elements ?? []).map((element) => [element.id, element.version])),
+  fileIds: new Set(Object.keys(data.files ?? {})),
+  appState: JSON.stringify(data.appState ?? {}),
+});
+
+// Excalidraw bumps an element's `version` on every change, so comparing versions finds the
+// changed elements without serializing the scene. Returns null when nothing changed.
+const diffScene = (saved: SavedScene, data: ExcalidrawData): BoardDataPatch | null => {
+  const currentIds = new Set(data.elements.map((element) => element.id));
+  const elements = data.elements.filter(
+    (element) => saved.elementVersions.get(element.id) !== element.version,
+  );
+  const deletedElementIds = [...saved.elementVersions.keys()].filter((id) => !currentIds.has(id));
+  const files = Object.fromEntries(
+    Object.entries(data.files ?? {}).filter(([fileId]) => !saved.fileIds.has(fileId)),
+  );
+  const appState = JSON.stringify(data.appState ?? {});
+  const appStateChanged = appState !== saved.appState;
+
+  if (
+    elements.length === 0 &&
+    deletedElementIds.length === 0 &&
+    Object.keys(files).length === 0 &&
+    !appStateChanged
+  ) {
+    return null;
+  }
+
+  return {
+    elements,
+    deleted_element_ids: deletedElementIds,
+    files,
+    app_state: appStateChanged ? (data.appState ?? null) : null,
+  };
+};
+
 const useExcalidrawExports = (
   excalidrawApiRef: ExcalidrawApiRef,
   boardId: string | null,
@@ -286,10 +349,17 @@ const useExcalidrawExports = (
 const useExcalidrawDataPersistence = (
   excalidrawApiRef: ExcalidrawApiRef,
   boardId: string | null,
-  onDataChange: (boardId: string, data: ExcalidrawData) => Promise<void>,
+  initialData: ExcalidrawData | null,
+  onDataChange: ExcalidrawFrameProps['onDataChange'],
+  onJournalChange: ExcalidrawFrameProps['onJournalChange'],
 ) => {
   const saveTimeoutRef = useRef<number | null>(null);
-  const lastSavedDataRef = useRef<string | null>(null);
+  const journalTimeoutRef = useRef<number | null>(null);
+  const [initialSavedScene] = useState(() => (initialData ? toSavedScene(initialData) : null));
+  // `savedSceneRef` advances when a save starts; `confirmedSceneRef` once it succeeded, which
+  // is what the journal's base revision refers to.
+  const savedSceneRef = useRef<SavedScene | null>(initialSavedScene);
+  const confirmedSceneRef = useRef<SavedScene | null>(initialSavedScene);
 
   const collectData = useCallback((): ExcalidrawData | null => {
     const api = excalidrawApiRef.current;
@@ -306,13 +376,21 @@ const useExcalidrawDataPersistence = (
         return;
       }
 
-      const dataStr = JSON.stringify(data);
-      if (dataStr === lastSavedDataRef.current) {
+      const saved = savedSceneRef.current;
+      const patch = saved ? diffScene(saved, data) : null;
+      if (saved && !patch) {
         return;
       }
 
-      lastSavedDataRef.current = dataStr;
-      await onDataChange(boardId, data);
+      const next = toSavedScene(data);
+      savedSceneRef.current = next;
+      const persisted = await onDataChange(boardId, data, patch);
+      if (persisted) {
+        confirmedSceneRef.current = next;
+      } else if (savedSceneRef.current === next) {
+        // Include these changes again in the next save.
+        savedSceneRef.current = saved;
+      }
     },
     [boardId, onDataChange],
   );
@@ -343,14 +421,35 @@ const useExcalidrawDataPersistence = (
     }, SAVE_DEBOUNCE_MS);
   }, [collectData, saveData]);
 
+  const scheduleJournal = useCallback(() => {
+    if (journalTimeoutRef.current) {
+      return;
+    }
+
+    journalTimeoutRef.current = window.setTimeout(() => {
+      journalTimeoutRef.current = null;
+      const confirmed = confirmedSceneRef.current;
+      const data = collectData();
+      if (!boardId || !confirmed || !data) {
+        return;
+      }
+
+      const patch = diffScene(confirmed, data);
+      if (patch) {
+        onJournalChange(boardId, patch);
+      }
+    }, JOURNAL_THROTTLE_MS);
+  }, [boardId, collectData, onJournalChange]);
+
   useEffect(
     () => () => {
       clearTimer(saveTimeoutRef);
+      clearTimer(journalTimeoutRef);
     },
     [],
   );
 
-  return { flushSave, scheduleSave };
+  return { collectData, flushSave, scheduleSave, scheduleJournal };
 };
 
 interface ThumbnailSource {
@@ -445,7 +544,14 @@ function EmptyBoardPlaceholder() {
 
 export const ExcalidrawFrame = forwardRef<ExcalidrawFrameHandle, ExcalidrawFrameProps>(
   function ExcalidrawFrame(
-    { boardId, boardName, onDataChange, onThumbnailGenerated, initialData }: ExcalidrawFrameProps,
+    {
+      boardId,
+      boardName,
+      onDataChange,
+      onJournalChange,
+      onThumbnailGenerated,
+      initialData,
+    }: ExcalidrawFrameProps,
     ref,
   ) {
     const excalidrawApiRef = useRef<ExcalidrawImperativeAPI | null>(null);
@@ -455,10 +561,12 @@ export const ExcalidrawFrame = forwardRef<ExcalidrawFrameHandle, ExcalidrawFrame
       boardId,
       boardName,
     );
-    const { flushSave, scheduleSave } = useExcalidrawDataPersistence(
+    const { collectData, flushSave, scheduleSave, scheduleJournal } = useExcalidrawDataPersistence(
       excalidrawApiRef,
       boardId,
+      initialData,
       onDataChange,
+      onJournalChange,
     );
     const { flushThumbnail, scheduleThumbnail } = useExcalidrawThumbnailPersistence(
       excalidrawApiRef,
@@ -479,15 +587,20 @@ export const ExcalidrawFrame = forwardRef<ExcalidrawFrameHandle, ExcalidrawFrame
         await flushSave();
         await flushThumbnail();
       },
+      getSceneData: () => {
+        const api = excalidrawApiRef.current;
+        return api ? toSerializableData(getSceneSnapshotIncludingDeleted(api)) : null;
+      },
     }));
 
     const handleChange = useCallback(
       (_elements: readonly ExcalidrawElement[], _appState: AppState, _files: BinaryFiles) => {
         if (!boardId || !isReady) return;
+        scheduleJournal();
         scheduleSave();
         scheduleThumbnail();
       },
-      [boardId, isReady, scheduleSave, scheduleThumbnail],
+      [boardId, isReady, scheduleJournal, scheduleSave, scheduleThumbnail],
     );
 
     if (!boardId) {
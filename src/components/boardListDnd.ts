@@ -1,4 +1,10 @@
-import type { Board, BoardFolder, BoardListBoard, BoardListItem } from '../types/board';
+import type {
+  Board,
+  BoardFolder,
+  BoardListChange,
+  BoardListItem,
+  BoardSmartFolder,
+} from '../types/board';
 
 export type DropPosition = 'before' | 'after' | 'inside';
 
@@ -7,99 +13,65 @@ export interface DragItemRef {
   id: string;
 }
 
-interface BoardLocation {
-  rootIndex: number;
-  folderIndex?: number;
+interface ItemLocation {
+  parentFolderId: string | null;
+  index: number;
 }
 
-interface BoardDropContext {
-  sourceBoard: Board;
-  sourceBoardId: string;
-  items: BoardListItem[];
-}
-
-interface FolderTargetDropInput {
-  context: BoardDropContext;
-  targetFolderId: string;
-  dropPosition: DropPosition;
-}
-
-interface BoardTargetDropInput {
-  context: BoardDropContext;
-  targetBoardId: string;
-  dropPosition: DropPosition;
-}
-
-interface FolderInsideDropInput {
-  context: BoardDropContext;
-  targetFolderId: string;
-}
-
-interface CreateFolderDropInput {
-  context: BoardDropContext;
-  targetBoardId: string;
-}
-
-const asBoardListBoard = (board: Board): BoardListBoard => ({
-  ...board,
-  type: 'board',
-});
+const isSameItem = (item: BoardListItem, ref: DragItemRef): boolean =>
+  item.type === ref.type && item.id === ref.id;
 
-const insertAt = <T>(items: T[], index: number, value: T): T[] => [
-  ...items.slice(0, index),
-  value,
-  ...items.slice(index),
-];
-
-const isFolder = (item: BoardListItem): item is BoardFolder => item.type === 'folder';
-
-const findRootBoardIndex = (items: BoardListItem[], boardId: string): number =>
-  items.findIndex((item) => item.type === 'board' && item.id === boardId);
+const findItem = (items: BoardListItem[], ref: DragItemRef): BoardListItem | undefined => {
+  for (const item of items) {
+    if (isSameItem(item, ref)) return item;
+    if (item.type === 'folder') {
+      const nested = findItem(item.items, ref);
+      if (nested) return nested;
+    }
+  }
+  return undefined;
+};
 
-const findFolderContainingBoard = (
+const locateItem = (
   items: BoardListItem[],
-  boardId: string,
-): { rootIndex: number; folder: BoardFolder; folderIndex: number } | null => {
-  const rootIndex = items.findIndex(
-    (item) => isFolder(item) && item.items.some((board) => board.id === boardId),
-  );
-  if (rootIndex === -1) return null;
-
-  const folder = items[rootIndex] as BoardFolder;
-  const folderIndex = folder.items.findIndex((board) => board.id === boardId);
-  if (folderIndex === -1) return null;
-
-  return { rootIndex, folder, folderIndex };
+  ref: DragItemRef,
+  parentFolderId: string | null = null,
+): ItemLocation | null => {
+  const index = items.findIndex((item) => isSameItem(item, ref));
+  if (index !== -1) return { parentFolderId, index };
+
+  for (const item of items) {
+    if (item.type !== 'folder') continue;
+    const nested = locateItem(item.items, ref, item.id);
+    if (nested) return nested;
+  }
+  return null;
 };
 
-const canCreateFolderFromBoards = (
-  over: DragItemRef,
-  dropPosition: DropPosition,
-  isOverInFolder: boolean,
-): boolean => over.type === 'board' && dropPosition === 'inside' && !isOverInFolder;
-
-const isFolderEdgeDrop = (over: DragItemRef, dropPosition: DropPosition): boolean =>
-  over.type === 'folder' && (dropPosition === 'before' || dropPosition === 'after');
-
-const ensureDropIndex = (targetIndex: number, dropPosition: DropPosition): number =>
-  dropPosition === 'after' || dropPosition === 'inside' ? targetIndex + 1 : targetIndex;
+// Folders emptied by the removal are kept, as they are on the backend until the move completes,
+// so positions computed on the result match the ones the commands expect.
+const removeItemFromTree = (items: BoardListItem[], ref: DragItemRef): BoardListItem[] =>
+  items
+    .filter((item) => !isSameItem(item, ref))
+    .map((item) =>
+      item.type === 'folder' ? { ...item, items: removeItemFromTree(item.items, ref) } : item,
+    );
 
-const resolveFolderDropTarget = (
+/** Where an item dropped relative to `over` ends up, in a tree the item has already left. */
+const resolveDropLocation = (
+  items: BoardListItem[],
   over: DragItemRef,
-  isOverInFolder: boolean,
-  parentFolderId?: string,
-): DragItemRef => {
-  if (isOverInFolder && parentFolderId) {
-    return { type: 'folder', id: parentFolderId };
+  dropPosition: DropPosition,
+): ItemLocation | null => {
+  if (dropPosition === 'inside') {
+    const folder = findItem(items, over);
+    if (folder?.type !== 'folder') return null;
+    return { parentFolderId: folder.id, index: folder.items.length };
   }
-  return { type: isOverInFolder ? 'folder' : over.type, id: over.id };
-};
 
-const findTargetIndex = (items: BoardListItem[], target: DragItemRef): number => {
-  if (target.type === 'folder') {
-    return items.findIndex((item) => item.type === 'folder' && item.id === target.id);
-  }
-  return items.findIndex((item) => item.type === 'board' && item.id === target.id);
+  const location = locateItem(items, over);
+  if (!location) return null;
+  return dropPosition === 'after' ? { ...location, index: location.index + 1 } : location;
 };
 
 export const parseDragId = (id: string): DragItemRef => {
@@ -112,83 +84,31 @@ export const parseDragId = (id: string): DragItemRef => {
 export const makeDragId = (type: 'board' | 'folder', id: string): string =>
   type === 'folder' ? `folder:${id}` : id;
 
-export const stripBoardType = (board: Board): Board => {
-  const maybeTyped = board as Board & { type?: string };
-  if (maybeTyped.type) {
-    const { type: _type, ...rest } = maybeTyped;
-    return rest;
-  }
-  return board;
-};
-
-export const generateFolderId = (): string =>
-  globalThis.crypto?.randomUUID?.() ??
-  `folder-${Date.now()}-${Math.random().toString(16).slice(2)}`;
-
-export const cleanupFolders = (nextItems: BoardListItem[]): BoardListItem[] => {
-  const seen = new Set<string>();
-  const normalized: BoardListItem[] = [];
-
-  for (const item of nextItems) {
-    if (item.type === 'board') {
-      if (!seen.has(item.id)) {
-        seen.add(item.id);
-        normalized.push(item);
-      }
-      continue;
-    }
-
-    const remaining = item.items.filter((board) => !seen.has(board.id));
-    if (remaining.length === 0) continue;
-
-    remaining.forEach((board) => seen.add(board.id));
-    if (remaining.length === 1) {
-      normalized.push(asBoardListBoard(remaining[0]));
-      continue;
-    }
-
-    normalized.push({ ...item, items: remaining });
-  }
-
-  return normalized;
-};
-
-export const removeBoardFromItems = (
-  boardId: string,
-  sourceItems: BoardListItem[],
-): BoardListItem[] =>
-  sourceItems
-    .map((item) => {
-      if (item.type === 'board') {
-        return item.id === boardId ? null : item;
-      }
-      const remaining = item.items.filter((board) => board.id !== boardId);
-      return remaining.length === 0 ? null : { ...item, items: remaining };
-    })
-    .filter((item): item is BoardListItem => item !== null);
-
-export const removeFolderFromItems = (
-  folderId: string,
-  sourceItems: BoardListItem[],
-): BoardListItem[] =>
-  sourceItems.filter((item) => !(item.type === 'folder' && item.id === folderId));
-
 export const findBoardById = (items: BoardListItem[], boardId: string): Board | undefined => {
-  const rootIndex = findRootBoardIndex(items, boardId);
-  if (rootIndex !== -1) {
-    return items[rootIndex] as BoardListBoard;
-  }
-  return findFolderContainingBoard(items, boardId)?.folder.items.find(
-    (board) => board.id === boardId,
-  );
+  const item = findItem(items, { type: 'board', id: boardId });
+  return item?.type === 'board' ? item : undefined;
 };
 
 export const findFolderById = (
   items: BoardListItem[],
   folderId: string,
 ): BoardFolder | undefined => {
-  const folder = items.find((item) => item.type === 'folder' && item.id === folderId);
-  return folder && folder.type === 'folder' ? folder : undefined;
+  const item = findItem(items, { type: 'folder', id: folderId });
+  return item?.type === 'folder' ? item : undefined;
+};
+
+export const findSmartFolderById = (
+  items: BoardListItem[],
+  smartFolderId: string,
+): BoardSmartFolder | undefined => {
+  for (const item of items) {
+    if (item.type === 'smart_folder' && item.id === smartFolderId) return item;
+    if (item.type === 'folder') {
+      const nested = findSmartFolderById(item.items, smartFolderId);
+      if (nested) return nested;
+    }
+  }
+  return undefined;
 };
 
 export const calculateDropPosition = (
@@ -196,16 +116,13 @@ export const calculateDropPosition = (
   targetRect: { top: number; height: number },
   activeType: 'board' | 'folder',
   overType: 'board' | 'folder',
-  isOverInFolder: boolean,
 ): DropPosition => {
   const relativeY = pointerY - targetRect.top;
   const ratio = Math.max(0, Math.min(1, relativeY / targetRect.height));
 
-  if (activeType === 'folder') {
-    return ratio < 0.5 ? 'before' : 'after';
-  }
-
-  if (overType === 'folder' || (!isOverInFolder && overType === 'board')) {
+  // Boards can be dropped into folders or onto boards (to group them); folders only nest
+  // into other folders.
+  if (overType === 'folder' || activeType === 'board') {
     if (ratio < 0.3) return 'before';
     if (ratio > 0.7) return 'after';
     return 'inside';
@@ -214,97 +131,9 @@ export const calculateDropPosition = (
   return ratio < 0.5 ? 'before' : 'after';
 };
 
-const findBoardLocation = (items: BoardListItem[], boardId: string): BoardLocation | null => {
-  const rootIndex = findRootBoardIndex(items, boardId);
-  if (rootIndex !== -1) {
-    return { rootIndex };
-  }
-
-  const folderMatch = findFolderContainingBoard(items, boardId);
-  if (!folderMatch) return null;
-
-  return { rootIndex: folderMatch.rootIndex, folderIndex: folderMatch.folderIndex };
-};
-
-const dropBoardInsideFolder = (input: FolderInsideDropInput): BoardListItem[] | null => {
-  const { context, targetFolderId } = input;
-  const targetFolder = findFolderById(context.items, targetFolderId);
-  if (!targetFolder) return null;
-
-  const withoutSource = removeBoardFromItems(context.sourceBoardId, context.items);
-  const withInserted = withoutSource.map((item) =>
-    item.type === 'folder' && item.id === targetFolder.id
-      ? { ...item, items: [...item.items, stripBoardType(context.sourceBoard)] }
-      : item,
-  );
-  return cleanupFolders(withInserted);
-};
-
-const createFolderFromBoards = (input: CreateFolderDropInput): BoardListItem[] | null => {
-  const { context, targetBoardId } = input;
-  const targetBoard = findBoardById(context.items, targetBoardId);
-  if (!targetBoard || targetBoard.id === context.sourceBoardId) return null;
-
-  const targetRootIndex = findRootBoardIndex(context.items, targetBoardId);
-  if (targetRootIndex === -1) return null;
-
-  let nextItems = removeBoardFromItems(context.sourceBoardId, context.items);
-  nextItems = removeBoardFromItems(targetBoardId, nextItems);
-
-  const newFolder: BoardFolder = {
-    type: 'folder',
-    id: generateFolderId(),
-    name: targetBoard.name,
-    items: [stripBoardType(targetBoard), stripBoardType(context.sourceBoard)],
-  };
-
-  const insertIndex = Math.min(targetRootIndex, nextItems.length);
-  return cleanupFolders(insertAt(nextItems, insertIndex, newFolder));
-};
-
-const dropBoardAroundFolder = (input: FolderTargetDropInput): BoardListItem[] | null => {
-  const { context, targetFolderId, dropPosition } = input;
-  const nextItems = removeBoardFromItems(context.sourceBoardId, context.items);
-  const targetIndex = nextItems.findIndex(
-    (item) => item.type === 'folder' && item.id === targetFolderId,
-  );
-  if (targetIndex === -1) return null;
-
-  const insertIndex = dropPosition === 'after' ? targetIndex + 1 : targetIndex;
-  return cleanupFolders(insertAt(nextItems, insertIndex, asBoardListBoard(context.sourceBoard)));
-};
-
-const dropBoardAroundBoard = (input: BoardTargetDropInput): BoardListItem[] | null => {
-  const { context, targetBoardId, dropPosition } = input;
-  const nextItems = removeBoardFromItems(context.sourceBoardId, context.items);
-  const location = findBoardLocation(nextItems, targetBoardId);
-  if (!location) return null;
-
-  if (location.folderIndex !== undefined) {
-    const folder = nextItems[location.rootIndex] as BoardFolder;
-    const insertIndex = dropPosition === 'after' ? location.folderIndex + 1 : location.folderIndex;
-    const nextFolderItems = insertAt(
-      folder.items,
-      insertIndex,
-      stripBoardType(context.sourceBoard),
-    );
-    const withFolderUpdate = nextItems.map((item, index) =>
-      index === location.rootIndex ? { ...item, items: nextFolderItems } : item,
-    );
-    return cleanupFolders(withFolderUpdate);
-  }
-
-  const rootInsertIndex = dropPosition === 'after' ? location.rootIndex + 1 : location.rootIndex;
-  return cleanupFolders(
-    insertAt(nextItems, rootInsertIndex, asBoardListBoard(context.sourceBoard)),
-  );
-};
-
 interface ApplyFolderDropInput {
   folderId: string;
   over: DragItemRef;
-  isOverInFolder: boolean;
-  parentFolderId?: string;
   dropPosition: DropPosition;
   items: BoardListItem[];
 }
@@ -312,29 +141,33 @@ interface ApplyFolderDropInput {
 export const applyFolderDrop = ({
   folderId,
   over,
-  isOverInFolder,
-  parentFolderId,
   dropPosition,
   items,
-}: ApplyFolderDropInput): BoardListItem[] | null => {
-  const target = resolveFolderDropTarget(over, isOverInFolder, parentFolderId);
-  if (target.type === 'folder' && target.id === folderId) return null;
-
+}: ApplyFolderDropInput): BoardListChange | null => {
   const sourceFolder = findFolderById(items, folderId);
   if (!sourceFolder) return null;
 
-  const nextItems = removeFolderFromItems(folderId, items);
-  const targetIndex = findTargetIndex(nextItems, target);
-  const resolvedTargetIndex = targetIndex === -1 ? nextItems.length : targetIndex;
-  const insertIndex = ensureDropIndex(resolvedTargetIndex, dropPosition);
+  // A folder cannot move into itself or anything nested in it.
+  if (isSameItem(sourceFolder, over) || findItem(sourceFolder.items, over)) return null;
 
-  return cleanupFolders(insertAt(nextItems, insertIndex, sourceFolder));
+  const position = over.type === 'board' && dropPosition === 'inside' ? 'after' : dropPosition;
+  const location = resolveDropLocation(
+    removeItemFromTree(items, { type: 'folder', id: folderId }),
+    over,
+    position,
+  );
+  if (!location) return null;
+  return {
+    kind: 'move_folder',
+    folderId,
+    parentFolderId: location.parentFolderId,
+    position: location.index,
+  };
 };
 
 interface ApplyBoardDropInput {
   boardId: string;
   over: DragItemRef;
-  isOverInFolder: boolean;
   dropPosition: DropPosition;
   items: BoardListItem[];
 }
@@ -342,30 +175,32 @@ interface ApplyBoardDropInput {
 export const applyBoardDrop = ({
   boardId,
   over,
-  isOverInFolder,
   dropPosition,
   items,
-}: ApplyBoardDropInput): BoardListItem[] | null => {
-  const sourceBoard = findBoardById(items, boardId);
-  if (!sourceBoard) return null;
-
-  const context: BoardDropContext = { sourceBoard, sourceBoardId: boardId, items };
-
-  if (over.type === 'folder' && dropPosition === 'inside') {
-    return dropBoardInsideFolder({ context, targetFolderId: over.id });
-  }
-
-  if (canCreateFolderFromBoards(over, dropPosition, isOverInFolder)) {
-    return createFolderFromBoards({ context, targetBoardId: over.id });
-  }
-
-  if (isFolderEdgeDrop(over, dropPosition)) {
-    return dropBoardAroundFolder({ context, targetFolderId: over.id, dropPosition });
-  }
-
-  if (over.type === 'board') {
-    return dropBoardAroundBoard({ context, targetBoardId: over.id, dropPosition });
+}: ApplyBoardDropInput): BoardListChange | null => {
+  if (!findBoardById(items, boardId)) return null;
+
+  const withoutSource = removeItemFromTree(items, { type: 'board', id: boardId });
+  if (over.type === 'board' && dropPosition === 'inside') {
+    // Dropping a board onto another groups the two in a new folder named after the target.
+    const targetBoard = findBoardById(withoutSource, over.id);
+    const location = locateItem(withoutSource, over);
+    if (!targetBoard || !location) return null;
+    return {
+      kind: 'create_folder',
+      name: targetBoard.name,
+      parentFolderId: location.parentFolderId,
+      position: location.index,
+      boardIds: [targetBoard.id, boardId],
+    };
   }
 
-  return null;
+  const location = resolveDropLocation(withoutSource, over, dropPosition);
+  if (!location) return null;
+  return {
+    kind: 'move_board',
+    boardId,
+    folderId: location.parentFolderId,
+    position: location.index,
+  };
 };
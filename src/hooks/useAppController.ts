@@ -1,22 +1,29 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { save } from '@tauri-apps/plugin-dialog';
 import type { ExcalidrawFrameHandle } from '../components/ExcalidrawFrame';
 import { useBoards } from './useBoards';
 import { useUiPreferences } from './useUiPreferences';
 import type {
+  BoardDataPatch,
   BoardListItem,
   BoardsImportResponse,
   BoardsImportResult,
   BoardsIndex,
   ExcalidrawData,
+  ImportConflictStrategy,
+  JournalRecovery,
+  TrashedBoard,
 } from '../types/board';
+import { flattenBoardTree } from '../types/board';
+import { describeError } from '../types/error';
 
 type FrameExportAction = 'exportPng' | 'copyPng' | 'exportSvg';
 type ExcalidrawRef = { current: ExcalidrawFrameHandle | null };
 
 const flattenBoards = (items: BoardListItem[]) =>
-  items.flatMap((item) => (item.type === 'board' ? [item] : item.items));
+  flattenBoardTree(items).map((entry) => entry.board);
 
 const findBoardNameById = (items: BoardListItem[], boardId: string | null) => {
   if (!boardId) {
@@ -39,15 +46,19 @@ const buildBoardsExportName = () => {
   const now = new Date();
   const pad = (value: number) => String(value).padStart(2, '0');
   const dateStamp = `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())}`;
-  return `excastoneboards-${dateStamp}.json`;
+  return `excastoneboards-${dateStamp}.esb`;
 };
 
+const isArchiveExportPath = (filePath: string) => filePath.toLowerCase().endsWith('.esb');
+
 const useCurrentBoardData = (
   activeBoardId: string | null,
   loadBoardData: (boardId: string) => Promise<ExcalidrawData | null>,
 ) => {
   const [currentBoardData, setCurrentBoardData] = useState<ExcalidrawData | null>(null);
   const [boardDataLoading, setBoardDataLoading] = useState(false);
+  // Bumped to load the active board again, e.g. after a save was rejected as stale.
+  const [reloadToken, setReloadToken] = useState(0);
 
   useEffect(() => {
     let cancelled = false;
@@ -85,9 +96,13 @@ const useCurrentBoardData = (
     return () => {
       cancelled = true;
     };
-  }, [activeBoardId, loadBoardData]);
+  }, [activeBoardId, loadBoardData, reloadToken]);
+
+  const reloadBoardData = useCallback(() => {
+    setReloadToken((token) => token + 1);
+  }, []);
 
-  return { currentBoardData, boardDataLoading };
+  return { currentBoardData, boardDataLoading, reloadBoardData };
 };
 
 const useFrameExportActions = (excalidrawRef: ExcalidrawRef) => {
@@ -171,7 +186,10 @@ const useBoardsTransferActions = (
         ? testExportPath
         : await save({
             defaultPath: buildBoardsExportName(),
-            filters: [{ name: 'Boards export', extensions: ['json'] }],
+            filters: [
+              { name: 'Boards archive', extensions: ['esb'] },
+              { name: 'Boards export (JSON)', extensions: ['json'] },
+            ],
           });
 
       if (!filePath) {
@@ -182,7 +200,9 @@ const useBoardsTransferActions = (
         await excalidrawRef.current.flushSave();
       }
 
-      await invoke('export_boards', { filePath });
+      await invoke(isArchiveExportPath(filePath) ? 'export_boards_archive' : 'export_boards', {
+        filePath,
+      });
     } catch (e) {
       console.error('Boards export failed:', e);
       setSettingsError('Boards export failed. Please try again.');
@@ -192,7 +212,11 @@ const useBoardsTransferActions = (
   }, [boardsExportBusy, excalidrawRef]);
 
   const handleImportBoards = useCallback(
-    async (filePath: string, selectedIndices: number[]): Promise<BoardsImportResult> => {
+    async (
+      filePath: string,
+      selectedIndices: number[],
+      strategy: ImportConflictStrategy,
+    ): Promise<BoardsImportResult> => {
       if (boardsImportBusy) {
         return { imported: 0, skipped: selectedIndices.length };
       }
@@ -204,6 +228,7 @@ const useBoardsTransferActions = (
         const result = await invoke<BoardsImportResponse>('import_boards', {
           filePath,
           selectedIndices,
+          strategy,
         });
         applyBoardsIndex(result.index);
         return result;
@@ -229,6 +254,7 @@ const useBoardsTransferActions = (
 
 const useBoardPersistenceActions = (
   saveBoardData: (boardId: string, data: ExcalidrawData) => Promise<boolean>,
+  patchBoardData: (boardId: string, patch: BoardDataPatch) => Promise<boolean>,
   saveBoardThumbnail: (boardId: string, thumbnail: string | null) => Promise<boolean>,
 ) => {
   const persistThumbnail = useCallback(
@@ -239,10 +265,9 @@ const useBoardPersistenceActions = (
   );
 
   const handleDataChange = useCallback(
-    async (boardId: string, data: ExcalidrawData) => {
-      await saveBoardData(boardId, data);
-    },
-    [saveBoardData],
+    (boardId: string, data: ExcalidrawData, patch: BoardDataPatch | null) =>
+      patch ? patchBoardData(boardId, patch) : saveBoardData(boardId, data),
+    [patchBoardData, saveBoardData],
   );
 
   return { persistThumbnail, handleDataChange };
@@ -269,6 +294,221 @@ const useThumbnails = (
   return { thumbnails, handleThumbnailGenerated };
 };
 
+const useStaleBoardMerge = (
+  activeBoardId: string | null,
+  mergeBoardData: (boardId: string, data: ExcalidrawData) => Promise<boolean>,
+  reloadBoardData: () => void,
+  excalidrawRef: ExcalidrawRef,
+) => {
+  return useCallback(async () => {
+    const data = excalidrawRef.current?.getSceneData();
+    if (!activeBoardId || !data) {
+      return;
+    }
+
+    if (await mergeBoardData(activeBoardId, data)) {
+      reloadBoardData();
+    }
+  }, [activeBoardId, excalidrawRef, mergeBoardData, reloadBoardData]);
+};
+
+// Boards left with unsaved journaled changes by the previous session, offered for recovery
+// once at startup.
+const useJournalRecovery = (activeBoardId: string | null, reloadBoardData: () => void) => {
+  const [journalRecoveries, setJournalRecoveries] = useState<JournalRecovery[]>([]);
+
+  useEffect(() => {
+    invoke<JournalRecovery[]>('list_board_journals')
+      .then(setJournalRecoveries)
+      .catch((e) => {
+        console.error('Failed to check for unsaved changes:', e);
+      });
+  }, []);
+
+  // The save itself succeeded; the leftover journal is discarded at the next startup.
+  useEffect(() => {
+    const unlisten = listen<unknown>('board-journal-clear-failed', (event) => {
+      console.error('Failed to clear unsaved changes after saving:', event.payload);
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  const resolveJournal = useCallback(
+    async (boardId: string, command: 'recover_board_journal' | 'discard_board_journal') => {
+      try {
+        await invoke(command, { boardId });
+        if (command === 'recover_board_journal' && boardId === activeBoardId) {
+          reloadBoardData();
+        }
+      } catch (e) {
+        console.error('Failed to resolve unsaved changes:', e);
+      }
+      setJournalRecoveries((current) =>
+        current.filter((recovery) => recovery.board_id !== boardId),
+      );
+    },
+    [activeBoardId, reloadBoardData],
+  );
+
+  const recoverJournal = useCallback(
+    (boardId: string) => resolveJournal(boardId, 'recover_board_journal'),
+    [resolveJournal],
+  );
+  const discardJournal = useCallback(
+    (boardId: string) => resolveJournal(boardId, 'discard_board_journal'),
+    [resolveJournal],
+  );
+
+  return { journalRecoveries, recoverJournal, discardJournal };
+};
+
+// Scheduled backups run in the background, so their failures arrive as an event rather than
+// a rejected command.
+const useScheduledBackupError = () => {
+  const [backupError, setBackupError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<unknown>('scheduled-backup-failed', (event) => {
+      setBackupError(`Scheduled backup failed. ${describeError(event.payload)}`);
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  return backupError;
+};
+
+// Deleted boards wait in the trash until they are restored, purged, or expire after the retention
+// period. Opened from the settings and the command palette through the `app:open-trash` event.
+const useTrash = (applyBoardsIndex: (index: BoardsIndex) => void) => {
+  const [trashOpen, setTrashOpen] = useState(false);
+  const [trashedBoards, setTrashedBoards] = useState<TrashedBoard[]>([]);
+  const [trashRetentionDays, setTrashRetentionDaysState] = useState<number | null>(null);
+  const [trashError, setTrashError] = useState<string | null>(null);
+
+  const loadTrash = useCallback(async () => {
+    try {
+      const [boards, retentionDays] = await Promise.all([
+        invoke<TrashedBoard[]>('list_trash'),
+        invoke<number>('get_trash_retention_days'),
+      ]);
+      setTrashedBoards(boards);
+      setTrashRetentionDaysState(retentionDays);
+      setTrashError(null);
+    } catch (e) {
+      console.error('Failed to load the trash:', e);
+      setTrashError(describeError(e));
+    }
+  }, []);
+
+  useEffect(() => {
+    const handleOpenTrash = () => {
+      setTrashOpen(true);
+      void loadTrash();
+    };
+
+    window.addEventListener('app:open-trash', handleOpenTrash);
+    return () => {
+      window.removeEventListener('app:open-trash', handleOpenTrash);
+    };
+  }, [loadTrash]);
+
+  const closeTrash = useCallback(() => {
+    setTrashOpen(false);
+    setTrashError(null);
+  }, []);
+
+  // Runs a trash action, then reloads the trash so it reflects what is left.
+  const runTrashAction = useCallback(
+    async (action: () => Promise<void>) => {
+      try {
+        await action();
+      } catch (e) {
+        console.error('Trash action failed:', e);
+        setTrashError(describeError(e));
+        return;
+      }
+      await loadTrash();
+    },
+    [loadTrash],
+  );
+
+  const restoreTrashedBoard = useCallback(
+    (boardId: string) =>
+      runTrashAction(async () => {
+        applyBoardsIndex(await invoke<BoardsIndex>('restore_board', { boardId }));
+      }),
+    [applyBoardsIndex, runTrashAction],
+  );
+  const restoreTrashedFolder = useCallback(
+    (folderId: string) =>
+      runTrashAction(async () => {
+        applyBoardsIndex(await invoke<BoardsIndex>('restore_folder', { folderId }));
+      }),
+    [applyBoardsIndex, runTrashAction],
+  );
+  const purgeTrashedBoard = useCallback(
+    (boardId: string) =>
+      runTrashAction(async () => {
+        await invoke('purge_board', { boardId });
+      }),
+    [runTrashAction],
+  );
+  const emptyTrash = useCallback(
+    () =>
+      runTrashAction(async () => {
+        await invoke('empty_trash');
+      }),
+    [runTrashAction],
+  );
+
+  const setTrashRetentionDays = useCallback(async (days: number) => {
+    try {
+      await invoke('set_trash_retention_days', { days });
+      setTrashRetentionDaysState(days);
+    } catch (e) {
+      console.error('Failed to set the trash retention:', e);
+      setTrashError(describeError(e));
+    }
+  }, []);
+
+  return {
+    trashOpen,
+    trashedBoards,
+    trashRetentionDays,
+    trashError,
+    closeTrash,
+    restoreTrashedBoard,
+    restoreTrashedFolder,
+    purgeTrashedBoard,
+    emptyTrash,
+    setTrashRetentionDays,
+  };
+};
+
+// Housekeeping that failed while the app started, such as purging expired trash or collecting
+// thumbnail garbage. The app runs regardless, so this is only shown.
+const useStartupError = () => {
+  const [startupError, setStartupError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<unknown[]>('take_startup_errors')
+      .then((errors) => {
+        if (errors.length > 0) {
+          setStartupError(`Startup maintenance failed. ${describeError(errors[0])}`);
+        }
+      })
+      .catch((e) => {
+        console.error('Failed to check startup maintenance:', e);
+      });
+  }, []);
+
+  return startupError;
+};
+
 const useBoardSelection = (
   activeBoardId: string | null,
   setActiveBoard: (boardId: string) => Promise<boolean>,
@@ -297,6 +537,8 @@ export function useAppController() {
     activeBoardId,
     loadBoardData,
     saveBoardData,
+    patchBoardData,
+    mergeBoardData,
     applyBoardsIndex,
     setActiveBoard,
     saveBoardThumbnail,
@@ -317,7 +559,10 @@ export function useAppController() {
     [items, activeBoardId],
   );
 
-  const { currentBoardData, boardDataLoading } = useCurrentBoardData(activeBoardId, loadBoardData);
+  const { currentBoardData, boardDataLoading, reloadBoardData } = useCurrentBoardData(
+    activeBoardId,
+    loadBoardData,
+  );
   const { exportBusy, exportError, handleExportPng, handleCopyPng, handleExportSvg } =
     useFrameExportActions(excalidrawRef);
   const {
@@ -330,20 +575,38 @@ export function useAppController() {
 
   const { persistThumbnail, handleDataChange } = useBoardPersistenceActions(
     saveBoardData,
+    patchBoardData,
     saveBoardThumbnail,
   );
   const { thumbnails, handleThumbnailGenerated } = useThumbnails(items, persistThumbnail);
   const handleSelectBoard = useBoardSelection(activeBoardId, setActiveBoard, excalidrawRef);
+  const { journalRecoveries, recoverJournal, discardJournal } = useJournalRecovery(
+    activeBoardId,
+    reloadBoardData,
+  );
+  const handleMergeStaleBoard = useStaleBoardMerge(
+    activeBoardId,
+    mergeBoardData,
+    reloadBoardData,
+    excalidrawRef,
+  );
+  const backupError = useScheduledBackupError();
+  const startupError = useStartupError();
+  const trash = useTrash(applyBoardsIndex);
 
   return {
     ...boards,
+    ...trash,
     currentBoardData,
     boardDataLoading,
+    reloadBoardData,
     exportBusy,
     exportError,
     boardsExportBusy,
     boardsImportBusy,
     settingsError,
+    backupError,
+    startupError,
     excalidrawRef,
     thumbnails,
     activeBoardName,
@@ -355,6 +618,10 @@ export function useAppController() {
     handleExportBoards,
     handleImportBoards,
     handleSelectBoard,
+    handleMergeStaleBoard,
+    journalRecoveries,
+    recoverJournal,
+    discardJournal,
     hideExportRow,
     showTimestamps,
     setHideExportRow,
@@ -1,28 +1,52 @@
-import { useState, useEffect, useCallback } from 'react';
+import { useState, useEffect, useCallback, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import {
   Board,
+  BoardContent,
+  BoardDataPatch,
+  BoardMergeResult,
   BoardMutationResult,
   BoardsIndex,
+  BoardListChange,
   BoardListItem,
   ExcalidrawData,
 } from '../types/board';
+import { describeError, isAppError } from '../types/error';
+
+const replaceBoardInItems = (items: BoardListItem[], board: Board): BoardListItem[] =>
+  items.map((item) => {
+    if (item.type === 'board') {
+      return item.id === board.id ? { ...board, type: 'board' } : item;
+    }
+    if (item.type === 'smart_folder') {
+      return {
+        ...item,
+        boards: item.boards.map((entry) => (entry.id === board.id ? board : entry)),
+      };
+    }
+    return { ...item, items: replaceBoardInItems(item.items, board) };
+  });
 
 export function useBoards() {
   const [items, setItems] = useState<BoardListItem[]>([]);
+  const [pinnedBoards, setPinnedBoards] = useState<Board[]>([]);
   const [activeBoardId, setActiveBoardId] = useState<string | null>(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
+  // Board whose last save was rejected because its stored scene changed since it was loaded.
+  const [staleBoardId, setStaleBoardId] = useState<string | null>(null);
+  const boardRevisionsRef = useRef<Record<string, number>>({});
 
   const loadBoards = useCallback(async () => {
     try {
       setLoading(true);
       const index = await invoke<BoardsIndex>('get_boards');
       setItems(index.items);
+      setPinnedBoards(index.pinned);
       setActiveBoardId(index.active_board_id);
       setError(null);
     } catch (e) {
-      setError(String(e));
+      setError(describeError(e));
     } finally {
       setLoading(false);
     }
@@ -37,19 +61,32 @@ export function useBoards() {
 
   const applyIndex = useCallback((index: BoardsIndex) => {
     setItems(index.items);
+    setPinnedBoards(index.pinned);
     setActiveBoardId(index.active_board_id);
   }, []);
 
-  const runMutation = useCallback(async <T>(action: () => Promise<T>, fallback: T): Promise<T> => {
-    try {
-      const result = await action();
-      setError(null);
-      return result;
-    } catch (e) {
-      setError(String(e));
-      return fallback;
-    }
-  }, []);
+  const runMutation = useCallback(
+    async <T>(action: () => Promise<T>, fallback: T): Promise<T> => {
+      try {
+        const result = await action();
+        setError(null);
+        return result;
+      } catch (e) {
+        if (isAppError(e) && e.kind === 'conflict' && e.current_revision !== undefined) {
+          // The scene was saved elsewhere since we loaded it; the app offers a reload.
+          setStaleBoardId(e.board_id ?? null);
+          return fallback;
+        }
+        setError(describeError(e));
+        if (isAppError(e) && e.kind === 'not_found') {
+          // Another window removed the board; resync the list with the database.
+          void loadBoards();
+        }
+        return fallback;
+      }
+    },
+    [loadBoards],
+  );
 
   const createBoard = (name: string): Promise<Board | null> => {
     return runMutation(async () => {
@@ -62,21 +99,9 @@ export function useBoards() {
   const renameBoard = (boardId: string, newName: string): Promise<boolean> => {
     return runMutation(async () => {
       const renamedBoard = await invoke<Board>('rename_board', { boardId, newName });
-      setItems((currentItems) =>
-        currentItems.map((item) => {
-          if (item.type === 'board') {
-            return item.id === boardId ? { ...renamedBoard, type: 'board' } : item;
-          }
-
-          if (!item.items.some((board) => board.id === boardId)) {
-            return item;
-          }
-
-          return {
-            ...item,
-            items: item.items.map((board) => (board.id === boardId ? renamedBoard : board)),
-          };
-        }),
+      setItems((currentItems) => replaceBoardInItems(currentItems, renamedBoard));
+      setPinnedBoards((current) =>
+        current.map((board) => (board.id === renamedBoard.id ? renamedBoard : board)),
       );
       return true;
     }, false);
@@ -98,6 +123,14 @@ export function useBoards() {
     }, false);
   };
 
+  const setBoardPinned = (boardId: string, pinned: boolean): Promise<boolean> => {
+    return runMutation(async () => {
+      const index = await invoke<BoardsIndex>('set_board_pinned', { boardId, pinned });
+      applyIndex(index);
+      return true;
+    }, false);
+  };
+
   const duplicateBoard = (boardId: string, newName: string): Promise<Board | null> => {
     return runMutation(async () => {
       const result = await invoke<BoardMutationResult>('duplicate_board', { boardId, newName });
@@ -109,20 +142,80 @@ export function useBoards() {
   const saveBoardData = useCallback(
     (boardId: string, data: ExcalidrawData): Promise<boolean> =>
       runMutation(async () => {
-        await invoke('save_board_data', { boardId, data: JSON.stringify(data) });
+        const revision = await invoke<number>('save_board_data', {
+          boardId,
+          data: JSON.stringify(data),
+          expectedRevision: boardRevisionsRef.current[boardId],
+        });
+        boardRevisionsRef.current[boardId] = revision;
         return true;
       }, false),
     [runMutation],
   );
 
+  const patchBoardData = useCallback(
+    (boardId: string, patch: BoardDataPatch): Promise<boolean> =>
+      runMutation(async () => {
+        const revision = await invoke<number>('patch_board_data', {
+          boardId,
+          patch,
+          expectedRevision: boardRevisionsRef.current[boardId],
+        });
+        boardRevisionsRef.current[boardId] = revision;
+        return true;
+      }, false),
+    [runMutation],
+  );
+
+  // Fire-and-forget: the journal only matters if the app dies before the next save.
+  const journalBoardChanges = useCallback((boardId: string, patch: BoardDataPatch) => {
+    const baseRevision = boardRevisionsRef.current[boardId];
+    if (baseRevision === undefined) {
+      return;
+    }
+
+    invoke('append_board_journal', { boardId, baseRevision, patch }).catch((e) => {
+      console.error('Failed to journal board changes:', e);
+    });
+  }, []);
+
+  // Merges the given scene into the stored one, e.g. to keep edits from a stale window.
+  const mergeBoardData = useCallback(
+    (boardId: string, data: ExcalidrawData): Promise<boolean> =>
+      runMutation(async () => {
+        const result = await invoke<BoardMergeResult>('merge_board_data', {
+          boardId,
+          data: JSON.stringify(data),
+        });
+        boardRevisionsRef.current[boardId] = result.revision;
+        setStaleBoardId((current) => (current === boardId ? null : current));
+        return true;
+      }, false),
+    [runMutation],
+  );
+
+  // Replaces the board's scene with an earlier revision and returns the restored scene.
+  const restoreBoardRevision = useCallback(
+    (boardId: string, revisionId: number): Promise<ExcalidrawData | null> =>
+      runMutation(async () => {
+        const content = await invoke<BoardContent>('restore_board_revision', {
+          boardId,
+          revisionId,
+        });
+        boardRevisionsRef.current[boardId] = content.revision;
+        setStaleBoardId((current) => (current === boardId ? null : current));
+        return JSON.parse(content.data) as ExcalidrawData;
+      }, null),
+    [runMutation],
+  );
+
   const loadBoardData = useCallback(async (boardId: string): Promise<ExcalidrawData | null> => {
     try {
-      const dataStr = await invoke<string | null>('load_board_data', { boardId });
-      if (!dataStr) {
-        return null;
-      }
+      const content = await invoke<BoardContent>('load_board_data', { boardId });
+      boardRevisionsRef.current[boardId] = content.revision;
+      setStaleBoardId((current) => (current === boardId ? null : current));
       // Deserialize JSON string to ExcalidrawData
-      const data = JSON.parse(dataStr) as ExcalidrawData;
+      const data = JSON.parse(content.data) as ExcalidrawData;
       return data;
     } catch (e) {
       // If parsing fails, return null (might be first load or corrupted data)
@@ -146,9 +239,11 @@ export function useBoards() {
 
   return {
     items,
+    pinnedBoards,
     activeBoardId,
     loading,
     error,
+    staleBoardId,
     loadBoards,
     applyBoardsIndex: applyIndex,
     createBoard,
@@ -156,13 +251,19 @@ export function useBoards() {
     deleteBoard,
     setActiveBoard,
     duplicateBoard,
-    setBoardsIndex: (nextItems: BoardListItem[]): Promise<boolean> =>
+    setBoardPinned,
+    applyBoardListChange: (change: BoardListChange): Promise<boolean> =>
       runMutation(async () => {
-        const index = await invoke<BoardsIndex>('set_boards_index', { items: nextItems });
+        const { kind, ...args } = change;
+        const index = await invoke<BoardsIndex>(kind, args);
         applyIndex(index);
         return true;
       }, false),
     saveBoardData,
+    patchBoardData,
+    journalBoardChanges,
+    mergeBoardData,
+    restoreBoardRevision,
     loadBoardData,
     saveBoardThumbnail,
   };
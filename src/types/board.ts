@@ -8,8 +8,19 @@ export interface Board {
   updated_at: string;
   collaboration_link: string | null;
   thumbnail: string | null;
+  tags: Tag[];
+  pinned: boolean;
+  last_opened_at: string | null;
 }
 
+export interface Tag {
+  id: string;
+  name: string;
+  color: string | null;
+}
+
+export type TagMatch = 'any' | 'all';
+
 export interface BoardListBoard extends Board {
   type: 'board';
 }
@@ -18,14 +29,70 @@ export interface BoardFolder {
   type: 'folder';
   id: string;
   name: string;
-  items: Board[];
+  items: BoardListItem[];
+}
+
+export type SmartFolderCondition =
+  | { kind: 'tag'; tag_id: string }
+  | { kind: 'updated_within_days'; days: number }
+  | { kind: 'name_contains'; text: string }
+  | { kind: 'has_collaboration_link' };
+
+/** Matches the boards that meet every condition. */
+export interface SmartFolderQuery {
+  conditions: SmartFolderCondition[];
+}
+
+/** A saved query; `boards` is evaluated when the index loads, most recently updated first. */
+export interface BoardSmartFolder {
+  type: 'smart_folder';
+  id: string;
+  name: string;
+  query: SmartFolderQuery;
+  boards: Board[];
 }
 
-export type BoardListItem = BoardListBoard | BoardFolder;
+export type BoardListItem = BoardListBoard | BoardFolder | BoardSmartFolder;
+
+/**
+ * Every board in the tree in display order, with its enclosing folders from the root in.
+ * Smart folders are skipped since their boards are listed in their own place.
+ */
+export const flattenBoardTree = (
+  items: BoardListItem[],
+  folderPath: BoardFolder[] = [],
+): { board: BoardListBoard; folderPath: BoardFolder[] }[] =>
+  items.flatMap((item) => {
+    if (item.type === 'board') return [{ board: item, folderPath }];
+    if (item.type === 'smart_folder') return [];
+    return flattenBoardTree(item.items, [...folderPath, item]);
+  });
+
+export type FolderDeleteMode = 'trash_boards' | 'keep_boards';
+
+/**
+ * One edit to the board list, named after the command that applies it. Positions count entries
+ * in the destination after the moved item has left its old place.
+ */
+export type BoardListChange =
+  | { kind: 'move_board'; boardId: string; folderId: string | null; position: number }
+  | { kind: 'move_folder'; folderId: string; parentFolderId: string | null; position: number }
+  | {
+      kind: 'create_folder';
+      name: string;
+      parentFolderId: string | null;
+      position: number;
+      boardIds: string[];
+    }
+  | { kind: 'rename_folder'; folderId: string; name: string }
+  | { kind: 'delete_folder'; folderId: string; mode: FolderDeleteMode }
+  | { kind: 'delete_smart_folder'; smartFolderId: string };
 
 export interface BoardsIndex {
   items: BoardListItem[];
   active_board_id: string | null;
+  /** Pinned boards sorted by name; they also appear in their place in `items`. */
+  pinned: Board[];
 }
 
 export interface BoardMutationResult {
@@ -33,6 +100,49 @@ export interface BoardMutationResult {
   index: BoardsIndex;
 }
 
+/** Scene JSON as stored, plus the revision to send back as `expectedRevision` when saving. */
+export interface BoardContent {
+  data: string;
+  revision: number;
+}
+
+export type SceneConflictKind = 'concurrent_edit' | 'deleted_vs_edited' | 'file_mismatch';
+
+/** A choice made while merging two copies of a scene; `id` is an element or file id. */
+export interface SceneConflict {
+  kind: SceneConflictKind;
+  id: string;
+  kept: 'current' | 'incoming';
+}
+
+export interface BoardMergeResult {
+  data: string;
+  revision: number;
+  conflicts: SceneConflict[];
+}
+
+export interface TrashedBoard {
+  board: Board;
+  deleted_at: string;
+  folder_id: string | null;
+  folder_name: string | null;
+}
+
+export interface BoardSearchHit {
+  board_id: string;
+  board_name: string;
+  element_id: string | null;
+  snippet: string;
+}
+
+export interface BoardRevision {
+  id: number;
+  board_id: string;
+  created_at: string;
+  element_count: number;
+  size_bytes: number;
+}
+
 export interface BoardsExportEntry {
   id: string;
   name: string;
@@ -43,10 +153,22 @@ export interface BoardsExportEntry {
   data: ExcalidrawData | null;
 }
 
+export type BoardsExportIndexItem =
+  | { type: 'board'; id: string }
+  | {
+      type: 'folder';
+      id: string;
+      name: string;
+      items?: BoardsExportIndexItem[];
+      /** Version 2 files list a folder's boards here instead of in `items`. */
+      board_ids?: string[];
+    };
+
 export interface BoardsExportFile {
-  version: 1;
+  version: 1 | 2 | 3;
   exported_at: string;
   boards: BoardsExportEntry[];
+  index?: BoardsExportIndexItem[];
 }
 
 export interface BoardsImportResult {
@@ -58,6 +180,40 @@ export interface BoardsImportResponse extends BoardsImportResult {
   index: BoardsIndex;
 }
 
+export type ImportConflictStrategy =
+  | 'skip'
+  | 'replace_if_newer'
+  | 'keep_both'
+  | 'preserve_id'
+  | 'merge';
+
+export type ImportAction = 'create' | 'create_with_id' | 'copy' | 'replace' | 'merge' | 'skip';
+
+export interface ImportEntryDecision {
+  index: number;
+  id: string;
+  name: string;
+  action: ImportAction;
+}
+
+/** Changes since the last save, sent to `patch_board_data` instead of the whole scene. */
+export interface BoardDataPatch {
+  elements: ExcalidrawElement[];
+  deleted_element_ids: string[];
+  /** Files added since the last save. */
+  files: BinaryFiles;
+  app_state: Partial<AppState> | null;
+}
+
+/** A board with unsaved changes left in its journal, e.g. after a crash. */
+export interface JournalRecovery {
+  board_id: string;
+  board_name: string;
+  written_at: string;
+  changed_elements: number;
+  deleted_elements: number;
+}
+
 export interface ExcalidrawData {
   elements: ExcalidrawElement[];
   appState?: Partial<AppState>;
@@ -0,0 +1,45 @@
+export type AppErrorKind =
+  | 'not_found'
+  | 'conflict'
+  | 'validation'
+  | 'io'
+  | 'database'
+  | 'migration';
+
+/** Error payload rejected by every Tauri command. */
+export interface AppError {
+  kind: AppErrorKind;
+  message: string;
+  board_id?: string;
+  /** Set on `conflict` errors from `save_board_data`. */
+  current_revision?: number;
+}
+
+export const isAppError = (value: unknown): value is AppError =>
+  typeof value === 'object' &&
+  value !== null &&
+  typeof (value as AppError).kind === 'string' &&
+  typeof (value as AppError).message === 'string';
+
+export const describeError = (error: unknown): string => {
+  if (!isAppError(error)) {
+    return String(error);
+  }
+
+  switch (error.kind) {
+    case 'not_found':
+      return error.board_id
+        ? 'This board no longer exists. It may have been deleted in another window.'
+        : error.message;
+    case 'conflict':
+      return error.current_revision !== undefined
+        ? 'This board was changed in another window. Reload it to see the latest version.'
+        : error.message;
+    case 'io':
+      return `Could not read or write files: ${error.message}`;
+    case 'migration':
+      return `Could not open the boards database: ${error.message}`;
+    default:
+      return error.message;
+  }
+};